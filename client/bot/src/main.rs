@@ -0,0 +1,347 @@
+//! Headless load and soak client: opens N WebSocket sessions to the server,
+//! replays input traffic on each and reports how state broadcasts hold up.
+//!
+//!     empire-bot [ws://host:8080/ws] [--sessions N] [--duration SECS]
+//!                [--interval MS] [--seed N] [--script FILE] [--patches]
+//!
+//! Traffic comes from a seeded random policy (session i uses seed + i) or,
+//! with `--script`, from a script every session replays in a loop. Sessions
+//! ask for full snapshots unless `--patches` is given.
+//!
+//! Reported per frame: size, decode time with the `ServerMessage` decoder
+//! and the gap since the previous frame. Across sessions: the spread in
+//! arrival time of the same broadcast (by `seq`), and the time from sending
+//! an input to the next frame.
+
+mod stats;
+mod traffic;
+
+use std::collections::HashMap;
+use std::io;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use empire_client_core::protocol::{ClientMessage, Feature, ServerMessage, PROTOCOL_VERSION};
+use empire_client_core::state::GameState;
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::Message;
+
+use stats::{Row, Samples, HEADER};
+use traffic::{parse_script, RandomPolicy, Step, Traffic};
+
+const USAGE: &str = "usage: empire-bot [ws://host:port/ws] [--sessions N] [--duration SECS] \
+                     [--interval MS] [--seed N] [--script FILE] [--patches]";
+const DEFAULT_URL: &str = "ws://localhost:8080/ws";
+// Short so arrival times are taken close to when the frame landed.
+const READ_TIMEOUT: Duration = Duration::from_millis(2);
+
+struct Config {
+    url: String,
+    sessions: usize,
+    duration: Duration,
+    interval: Duration,
+    seed: u64,
+    script: Option<Vec<Step>>,
+    patches: bool,
+}
+
+fn parse_args(args: &[String]) -> Result<Config, String> {
+    let mut config = Config {
+        url: DEFAULT_URL.to_string(),
+        sessions: 1,
+        duration: Duration::from_secs(10),
+        interval: Duration::from_millis(100),
+        seed: 1,
+        script: None,
+        patches: false,
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{} needs a value", name));
+        let number = |name: &str, text: &String| text.parse::<u64>().map_err(|_| format!("{}: not a number: {}", name, text));
+        match arg.as_str() {
+            "--sessions" => config.sessions = number(arg, value(arg)?)?.max(1) as usize,
+            "--duration" => config.duration = Duration::from_secs(number(arg, value(arg)?)?),
+            "--interval" => config.interval = Duration::from_millis(number(arg, value(arg)?)?.max(1)),
+            "--seed" => config.seed = number(arg, value(arg)?)?,
+            "--script" => {
+                let path = value(arg)?;
+                let text = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+                let steps = parse_script(&text).map_err(|err| format!("{}: {}", path, err))?;
+                if !steps.iter().any(|s| matches!(s, Step::Send(_))) {
+                    return Err(format!("{}: script sends nothing", path));
+                }
+                config.script = Some(steps);
+            }
+            "--patches" => config.patches = true,
+            url if !url.starts_with("--") => config.url = url.to_string(),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    Ok(config)
+}
+
+/// Everything one session measured.
+#[derive(Default)]
+struct SessionReport {
+    states: usize,
+    patches: usize,
+    other: usize,
+    decode_errors: usize,
+    inputs: usize,
+    frame_bytes: Samples,
+    decode_us: Samples,
+    gap_ms: Samples,
+    response_ms: Samples,
+    /// Arrival time of each broadcast, by `seq`.
+    arrivals: Vec<(u64, Instant)>,
+    error: Option<String>,
+}
+
+fn send(ws: &mut tungstenite::WebSocket<MaybeTlsStream<std::net::TcpStream>>, msg: &ClientMessage) -> tungstenite::Result<()> {
+    ws.send(Message::text(serde_json::to_string(msg).unwrap_or_default()))
+}
+
+fn run_session(config: &Config, mut traffic: Traffic, until: Instant) -> SessionReport {
+    let mut report = SessionReport::default();
+    if let Err(err) = drive_session(config, &mut traffic, until, &mut report) {
+        report.error = Some(err.to_string());
+    }
+    report
+}
+
+fn drive_session(
+    config: &Config,
+    traffic: &mut Traffic,
+    until: Instant,
+    report: &mut SessionReport,
+) -> tungstenite::Result<()> {
+    let (mut ws, _) = tungstenite::connect(config.url.as_str())?;
+    if let MaybeTlsStream::Plain(stream) = ws.get_mut() {
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    }
+    let features = if config.patches { vec![Feature::Patches] } else { Vec::new() };
+    send(&mut ws, &ClientMessage::Hello { protocol_version: PROTOCOL_VERSION, features })?;
+
+    let mut state = GameState::new();
+    let mut next_step = Instant::now();
+    let mut last_frame: Option<Instant> = None;
+    let mut pending_input: Option<Instant> = None;
+
+    while Instant::now() < until {
+        if Instant::now() >= next_step {
+            match traffic.next(state.map_size) {
+                Step::Send(msgs) => {
+                    for msg in &msgs {
+                        send(&mut ws, msg)?;
+                    }
+                    report.inputs += 1;
+                    pending_input.get_or_insert_with(Instant::now);
+                    next_step += config.interval;
+                }
+                Step::Wait(delay) => next_step += delay,
+            }
+        }
+
+        let text = match ws.read() {
+            Ok(Message::Text(text)) => text,
+            Ok(_) => continue,
+            Err(tungstenite::Error::Io(err))
+                if matches!(err.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) =>
+            {
+                continue
+            }
+            Err(err) => return Err(err),
+        };
+        let arrived = Instant::now();
+        report.frame_bytes.push(text.len() as f64);
+        let decode_start = Instant::now();
+        let decoded = serde_json::from_str::<ServerMessage>(text.as_str());
+        report.decode_us.push(decode_start.elapsed().as_secs_f64() * 1e6);
+
+        let msg = match decoded {
+            Ok(msg) => msg,
+            Err(_) => {
+                report.decode_errors += 1;
+                continue;
+            }
+        };
+        let broadcast = match &msg {
+            ServerMessage::State(_) => {
+                report.states += 1;
+                true
+            }
+            ServerMessage::Patch(_) => {
+                report.patches += 1;
+                true
+            }
+            _ => {
+                report.other += 1;
+                false
+            }
+        };
+        state.apply_message(msg);
+        if state.take_resync_request() {
+            send(&mut ws, &ClientMessage::Resync)?;
+        }
+        if !broadcast {
+            continue;
+        }
+
+        if let Some(prev) = last_frame.replace(arrived) {
+            report.gap_ms.push((arrived - prev).as_secs_f64() * 1e3);
+        }
+        if let Some(sent) = pending_input.take() {
+            report.response_ms.push((arrived - sent).as_secs_f64() * 1e3);
+        }
+        if let Some(seq) = state.seq {
+            report.arrivals.push((seq, arrived));
+        }
+    }
+    let _ = ws.close(None);
+    Ok(())
+}
+
+/// Spread between the first and last session to receive each broadcast,
+/// for broadcasts seen by at least two sessions.
+fn fan_out_ms(reports: &[SessionReport]) -> Samples {
+    let mut by_seq: HashMap<u64, (Instant, Instant, usize)> = HashMap::new();
+    for (seq, at) in reports.iter().flat_map(|r| r.arrivals.iter()) {
+        let entry = by_seq.entry(*seq).or_insert((*at, *at, 0));
+        entry.0 = entry.0.min(*at);
+        entry.1 = entry.1.max(*at);
+        entry.2 += 1;
+    }
+    let mut samples = Samples::default();
+    for (first, last, seen) in by_seq.into_values() {
+        if seen > 1 {
+            samples.push((last - first).as_secs_f64() * 1e3);
+        }
+    }
+    samples
+}
+
+fn print_report(config: &Config, reports: Vec<SessionReport>, elapsed: Duration) {
+    let fan_out = fan_out_ms(&reports);
+    let mut failed = 0;
+    for (i, err) in reports.iter().enumerate().filter_map(|(i, r)| Some((i, r.error.as_ref()?))) {
+        eprintln!("session {}: {}", i, err);
+        failed += 1;
+    }
+
+    let mut total = SessionReport::default();
+    for r in reports {
+        total.states += r.states;
+        total.patches += r.patches;
+        total.other += r.other;
+        total.decode_errors += r.decode_errors;
+        total.inputs += r.inputs;
+        total.frame_bytes.extend(r.frame_bytes);
+        total.decode_us.extend(r.decode_us);
+        total.gap_ms.extend(r.gap_ms);
+        total.response_ms.extend(r.response_ms);
+    }
+
+    let secs = elapsed.as_secs_f64().max(1e-3);
+    println!(
+        "{}  sessions {} ({} failed)  {:.1}s  {}",
+        config.url,
+        config.sessions,
+        failed,
+        secs,
+        if config.patches { "patches" } else { "full snapshots" }
+    );
+    println!(
+        "frames {} (state {}, patch {}, other {}, undecodable {})  inputs {}",
+        total.states + total.patches + total.other,
+        total.states,
+        total.patches,
+        total.other,
+        total.decode_errors,
+        total.inputs
+    );
+    println!(
+        "received {:.2} MB/s total, {:.1} frames/s per session",
+        total.frame_bytes.sum() / secs / 1e6,
+        (total.states + total.patches) as f64 / secs / config.sessions as f64
+    );
+    println!("{}", HEADER);
+    println!("{}", Row("frame bytes", &total.frame_bytes));
+    println!("{}", Row("decode us", &total.decode_us));
+    println!("{}", Row("frame gap ms", &total.gap_ms));
+    println!("{}", Row("fan-out ms", &fan_out));
+    println!("{}", Row("input->frame ms", &total.response_ms));
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let config = match parse_args(&args) {
+        Ok(config) => Arc::new(config),
+        Err(err) => {
+            eprintln!("{}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let start = Instant::now();
+    let until = start + config.duration;
+    let handles: Vec<_> = (0..config.sessions)
+        .map(|i| {
+            let config = Arc::clone(&config);
+            let traffic = match &config.script {
+                Some(steps) => Traffic::Script(steps.clone(), 0),
+                None => Traffic::Random(RandomPolicy::new(config.seed.wrapping_add(i as u64))),
+            };
+            thread::spawn(move || run_session(&config, traffic, until))
+        })
+        .collect();
+    let reports: Vec<SessionReport> = handles
+        .into_iter()
+        .map(|h| h.join().unwrap_or_else(|_| SessionReport { error: Some("panicked".to_string()), ..Default::default() }))
+        .collect();
+
+    let all_failed = reports.iter().all(|r| r.error.is_some());
+    print_report(&config, reports, start.elapsed());
+    if all_failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(text: &str) -> Vec<String> {
+        text.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn parses_arguments() {
+        let config = parse_args(&args("ws://box:9000/ws --sessions 16 --duration 60 --seed 42 --patches")).unwrap();
+        assert_eq!(config.url, "ws://box:9000/ws");
+        assert_eq!((config.sessions, config.duration, config.seed), (16, Duration::from_secs(60), 42));
+        assert!(config.patches && config.script.is_none());
+
+        let config = parse_args(&[]).unwrap();
+        assert_eq!((config.url.as_str(), config.sessions), (DEFAULT_URL, 1));
+
+        assert_eq!(parse_args(&args("--sessions")).err().unwrap(), "--sessions needs a value");
+        assert_eq!(parse_args(&args("--interval soon")).err().unwrap(), "--interval: not a number: soon");
+        assert_eq!(parse_args(&args("--verbose")).err().unwrap(), USAGE);
+    }
+
+    #[test]
+    fn fan_out_only_counts_broadcasts_seen_twice() {
+        let t = Instant::now();
+        let ms = |n: u64| t + Duration::from_millis(n);
+        let a = SessionReport { arrivals: vec![(1, ms(0)), (2, ms(33)), (3, ms(66))], ..Default::default() };
+        let b = SessionReport { arrivals: vec![(1, ms(4)), (2, ms(35))], ..Default::default() };
+        let mut spread = fan_out_ms(&[a, b]).summary().unwrap();
+        spread.mean = spread.mean.round();
+        assert_eq!((spread.count, spread.min, spread.max, spread.mean), (2, 2.0, 4.0, 3.0));
+    }
+}
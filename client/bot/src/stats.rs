@@ -0,0 +1,97 @@
+//! Sample collection and percentile summaries for the report.
+
+use std::fmt;
+
+/// Raw samples of one measurement.
+#[derive(Debug, Clone, Default)]
+pub struct Samples(Vec<f64>);
+
+impl Samples {
+    pub fn push(&mut self, value: f64) {
+        self.0.push(value);
+    }
+
+    pub fn extend(&mut self, other: Samples) {
+        self.0.extend(other.0);
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.0.iter().fold(0.0, |sum, v| sum + v)
+    }
+
+    pub fn summary(&self) -> Option<Summary> {
+        if self.0.is_empty() {
+            return None;
+        }
+        let mut sorted = self.0.clone();
+        sorted.sort_by(f64::total_cmp);
+        // Nearest-rank percentile
+        let pct = |p: f64| sorted[((p / 100.0 * sorted.len() as f64).ceil() as usize).clamp(1, sorted.len()) - 1];
+        Some(Summary {
+            count: sorted.len(),
+            min: sorted[0],
+            mean: self.sum() / sorted.len() as f64,
+            p50: pct(50.0),
+            p95: pct(95.0),
+            p99: pct(99.0),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub count: usize,
+    pub min: f64,
+    pub mean: f64,
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+pub const HEADER: &str = "                       count       min      mean       p50       p95       p99       max";
+
+/// One report row: label, then the summary or a dash when there are no samples.
+pub struct Row<'a>(pub &'a str, pub &'a Samples);
+
+impl fmt::Display for Row<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Row(label, samples) = self;
+        match samples.summary() {
+            Some(s) => write!(
+                f,
+                "{:<18} {:>9} {:>9.2} {:>9.2} {:>9.2} {:>9.2} {:>9.2} {:>9.2}",
+                label, s.count, s.min, s.mean, s.p50, s.p95, s.p99, s.max
+            ),
+            None => write!(f, "{:<18} {:>9}", label, "-"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_rank_percentiles() {
+        let mut samples = Samples::default();
+        for v in (1..=100).rev() {
+            samples.push(v as f64);
+        }
+        let s = samples.summary().unwrap();
+        assert_eq!((s.count, s.min, s.max), (100, 1.0, 100.0));
+        assert_eq!((s.p50, s.p95, s.p99), (50.0, 95.0, 99.0));
+        assert_eq!(s.mean, 50.5);
+    }
+
+    #[test]
+    fn single_sample_and_empty() {
+        let mut samples = Samples::default();
+        assert!(samples.summary().is_none());
+        assert_eq!(Row("decode us", &samples).to_string().split_whitespace().last(), Some("-"));
+        samples.push(3.0);
+        let s = samples.summary().unwrap();
+        assert_eq!((s.p50, s.p99), (3.0, 3.0));
+    }
+}
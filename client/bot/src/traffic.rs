@@ -0,0 +1,211 @@
+//! Where a session's input comes from: a seeded random policy or a script.
+//!
+//! Script lines, one step each (blank lines and `#` comments are skipped):
+//!
+//!     key <key> [col row]     browser key name, e.g. `key Q` or `key Escape`
+//!     click <col> <row> [right]
+//!     hover <col> <row>
+//!     wait <ms>
+//!
+//! Keys go through the browser's `map_key_event` table and are followed by
+//! `key_up`, since the server ignores a key while another one is held.
+
+use std::time::Duration;
+
+use empire_client_core::input::map_key_event;
+use empire_client_core::protocol::{ClientMessage, MouseButton};
+
+// Keys the random policy presses: movement, production and skip.
+// Save, load and map-cycling keys are left out so a soak run doesn't
+// write files or change what other sessions see.
+const RANDOM_KEYS: [&str; 13] = ["q", "w", "e", "a", "d", "z", "x", "c", "f", "t", "p", "b", " "];
+
+/// One step of traffic.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    Send(Vec<ClientMessage>),
+    Wait(Duration),
+}
+
+/// Small deterministic PRNG (xorshift64*), so a seed reproduces a run.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // Zero is a fixed point of xorshift
+        Self(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniform in 0..n (n > 0).
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+fn key_step(key: &str, shift: bool, col: i32, row: i32) -> Option<Step> {
+    let (_, msg) = map_key_event(key, shift, col, row)?;
+    Some(Step::Send(vec![msg, ClientMessage::KeyUp]))
+}
+
+/// Picks hovers, key presses and clicks at random cells.
+#[derive(Debug, Clone)]
+pub struct RandomPolicy {
+    rng: Rng,
+}
+
+impl RandomPolicy {
+    pub fn new(seed: u64) -> Self {
+        Self { rng: Rng::new(seed) }
+    }
+
+    /// Next step on a map of `map_size` (cols, rows); hovers only until
+    /// the first state has arrived.
+    pub fn next(&mut self, map_size: (usize, usize)) -> Step {
+        if map_size.0 == 0 || map_size.1 == 0 {
+            return Step::Send(vec![ClientMessage::Hover { col: -1, row: -1 }]);
+        }
+        let col = self.rng.below(map_size.0) as i32;
+        let row = self.rng.below(map_size.1) as i32;
+        match self.rng.below(100) {
+            0..=59 => Step::Send(vec![ClientMessage::Hover { col, row }]),
+            60..=89 => {
+                let key = RANDOM_KEYS[self.rng.below(RANDOM_KEYS.len())];
+                let shift = key != " " && self.rng.below(4) == 0;
+                let key = if shift { key.to_uppercase() } else { key.to_string() };
+                key_step(&key, shift, col, row).unwrap_or(Step::Send(Vec::new()))
+            }
+            _ => Step::Send(vec![ClientMessage::Click { col, row, button: MouseButton::Left }]),
+        }
+    }
+}
+
+fn parse_line(line: &str) -> Result<Step, String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let num = |i: usize| -> Result<i32, String> {
+        let word = words.get(i).ok_or("missing number")?;
+        word.parse().map_err(|_| format!("not a number: {}", word))
+    };
+    match words[..] {
+        ["key", key] | ["key", key, _, _] => {
+            let (col, row) = if words.len() == 4 { (num(2)?, num(3)?) } else { (0, 0) };
+            // A single capital letter is typed with shift, as in the browser
+            let shift = key.len() == 1 && key.chars().all(|c| c.is_ascii_uppercase());
+            key_step(key, shift, col, row).ok_or(format!("unmapped key: {}", key))
+        }
+        ["click", _, _] => Ok(Step::Send(vec![ClientMessage::Click { col: num(1)?, row: num(2)?, button: MouseButton::Left }])),
+        ["click", _, _, "right"] => {
+            Ok(Step::Send(vec![ClientMessage::Click { col: num(1)?, row: num(2)?, button: MouseButton::Right }]))
+        }
+        ["hover", _, _] => Ok(Step::Send(vec![ClientMessage::Hover { col: num(1)?, row: num(2)? }])),
+        ["wait", ms] => ms.parse().map(|ms| Step::Wait(Duration::from_millis(ms))).map_err(|_| format!("not a number: {}", ms)),
+        _ => Err(format!("unrecognised step: {}", line)),
+    }
+}
+
+/// Parse a script; errors carry the 1-based line number.
+pub fn parse_script(text: &str) -> Result<Vec<Step>, String> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i, line.split('#').next().unwrap_or("").trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(i, line)| parse_line(line).map_err(|err| format!("line {}: {}", i + 1, err)))
+        .collect()
+}
+
+/// A session's traffic source.
+#[derive(Debug, Clone)]
+pub enum Traffic {
+    Random(RandomPolicy),
+    /// Steps replayed in a loop, and the position of the next one.
+    Script(Vec<Step>, usize),
+}
+
+impl Traffic {
+    pub fn next(&mut self, map_size: (usize, usize)) -> Step {
+        match self {
+            Traffic::Random(policy) => policy.next(map_size),
+            Traffic::Script(steps, _) if steps.is_empty() => Step::Send(Vec::new()),
+            Traffic::Script(steps, pos) => {
+                let step = steps[*pos].clone();
+                *pos = (*pos + 1) % steps.len();
+                step
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeded_policy_is_reproducible_and_stays_on_the_map() {
+        let mut a = RandomPolicy::new(7);
+        let mut b = RandomPolicy::new(7);
+        let steps: Vec<Step> = (0..200).map(|_| a.next((10, 5))).collect();
+        assert_eq!(steps, (0..200).map(|_| b.next((10, 5))).collect::<Vec<_>>());
+        assert_ne!(steps, (0..200).map(|_| RandomPolicy::new(8).next((10, 5))).collect::<Vec<_>>());
+
+        let mut kinds = [0; 3];
+        for step in &steps {
+            let Step::Send(msgs) = step else { panic!("random policy never waits") };
+            match &msgs[..] {
+                [ClientMessage::Hover { col, row }] => {
+                    assert!((0..10).contains(col) && (0..5).contains(row));
+                    kinds[0] += 1;
+                }
+                [ClientMessage::Key { .. }, ClientMessage::KeyUp] => kinds[1] += 1,
+                [ClientMessage::Click { col, row, .. }] => {
+                    assert!((0..10).contains(col) && (0..5).contains(row));
+                    kinds[2] += 1;
+                }
+                other => panic!("unexpected step {:?}", other),
+            }
+        }
+        assert!(kinds.iter().all(|&n| n > 0), "{:?}", kinds);
+    }
+
+    #[test]
+    fn parses_scripts() {
+        let steps = parse_script("# opening\nkey Q 3 4\nclick 1 2 right\n\nhover 5 6  # look\nwait 250\nkey Escape\n").unwrap();
+        assert_eq!(
+            steps,
+            vec![
+                Step::Send(vec![
+                    ClientMessage::Key { key: "Q".to_string(), shift: true, mouse_x: 3, mouse_y: 4 },
+                    ClientMessage::KeyUp,
+                ]),
+                Step::Send(vec![ClientMessage::Click { col: 1, row: 2, button: MouseButton::Right }]),
+                Step::Send(vec![ClientMessage::Hover { col: 5, row: 6 }]),
+                Step::Wait(Duration::from_millis(250)),
+                Step::Send(vec![
+                    ClientMessage::Key { key: "escape".to_string(), shift: false, mouse_x: 0, mouse_y: 0 },
+                    ClientMessage::KeyUp,
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn script_errors_name_the_line() {
+        assert_eq!(parse_script("hover 1 1\nkey j").unwrap_err(), "line 2: unmapped key: j");
+        assert_eq!(parse_script("click x 1").unwrap_err(), "line 1: not a number: x");
+        assert_eq!(parse_script("jump").unwrap_err(), "line 1: unrecognised step: jump");
+    }
+
+    #[test]
+    fn scripts_loop() {
+        let mut traffic = Traffic::Script(parse_script("wait 1\nwait 2").unwrap(), 0);
+        let waits: Vec<Step> = (0..3).map(|_| traffic.next((0, 0))).collect();
+        assert_eq!(waits[0], Step::Wait(Duration::from_millis(1)));
+        assert_eq!(waits[2], waits[0]);
+    }
+}
@@ -0,0 +1,194 @@
+//! Which part of the map is on screen and at what size. The map view sits
+//! at the canvas origin; the camera maps between canvas pixels and
+//! (fractional) map cells for drawing and hit-testing.
+
+use std::ops::Range;
+
+use crate::layout::Rect;
+use crate::renderer::{CELL_H, CELL_W};
+
+/// Cell scale at each zoom level, relative to `CELL_W` x `CELL_H`.
+pub const ZOOM_LEVELS: [f64; 7] = [0.25, 0.5, 0.75, 1.0, 1.5, 2.0, 3.0];
+const DEFAULT_ZOOM: usize = 3;
+
+/// Width of the band along the view's edges that scrolls the map while
+/// the pointer rests in it.
+pub const EDGE_SCROLL_BAND: f64 = 16.0;
+/// Edge-scroll speed in canvas pixels per second.
+pub const EDGE_SCROLL_SPEED: f64 = 480.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    /// Index into `ZOOM_LEVELS`.
+    level: usize,
+    /// Map position at the top-left of the view, in cells.
+    pub left: f64,
+    pub top: f64,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self { level: DEFAULT_ZOOM, left: 0.0, top: 0.0 }
+    }
+}
+
+impl Camera {
+    pub fn zoom(&self) -> f64 {
+        ZOOM_LEVELS[self.level]
+    }
+
+    pub fn cell_w(&self) -> f64 {
+        CELL_W * self.zoom()
+    }
+
+    pub fn cell_h(&self) -> f64 {
+        CELL_H * self.zoom()
+    }
+
+    /// Canvas rectangle of a cell; may lie partly or wholly off the view.
+    pub fn cell_rect(&self, col: usize, row: usize) -> Rect {
+        Rect::new(
+            (col as f64 - self.left) * self.cell_w(),
+            (row as f64 - self.top) * self.cell_h(),
+            self.cell_w(),
+            self.cell_h(),
+        )
+    }
+
+    /// Map position, in fractional cells, under canvas point (x, y).
+    pub fn map_point(&self, x: f64, y: f64) -> (f64, f64) {
+        (self.left + x / self.cell_w(), self.top + y / self.cell_h())
+    }
+
+    /// Columns and rows at least partly inside a `view_w` x `view_h` view.
+    pub fn visible(&self, cols: usize, rows: usize, view_w: f64, view_h: f64) -> (Range<usize>, Range<usize>) {
+        let span = |start: f64, len: f64, count: usize| {
+            let first = (start.floor().max(0.0) as usize).min(count);
+            let end = ((start + len).ceil().max(0.0) as usize).min(count);
+            first..end.max(first)
+        };
+        (
+            span(self.left, view_w / self.cell_w(), cols),
+            span(self.top, view_h / self.cell_h(), rows),
+        )
+    }
+
+    /// Move the view by (dx, dy) canvas pixels.
+    pub fn pan(&mut self, dx: f64, dy: f64) {
+        self.left += dx / self.cell_w();
+        self.top += dy / self.cell_h();
+    }
+
+    /// Step `steps` zoom levels in (positive) or out, keeping the map
+    /// point under canvas point (x, y) where it is. Returns whether the
+    /// zoom changed.
+    pub fn zoom_at(&mut self, steps: i32, x: f64, y: f64) -> bool {
+        let level = (self.level as i32 + steps).clamp(0, ZOOM_LEVELS.len() as i32 - 1) as usize;
+        if level == self.level {
+            return false;
+        }
+        let (col, row) = self.map_point(x, y);
+        self.level = level;
+        self.left = col - x / self.cell_w();
+        self.top = row - y / self.cell_h();
+        true
+    }
+
+    /// Back to the default zoom, keeping the map point under (x, y).
+    pub fn reset_zoom(&mut self, x: f64, y: f64) {
+        self.zoom_at(DEFAULT_ZOOM as i32 - self.level as i32, x, y);
+    }
+
+    /// Centre the view on a map position given in cells.
+    pub fn center_on(&mut self, col: f64, row: f64, view_w: f64, view_h: f64) {
+        self.left = col - view_w / self.cell_w() / 2.0;
+        self.top = row - view_h / self.cell_h() / 2.0;
+    }
+
+    /// Keep the view on the map: no panning past its edges, and a map
+    /// smaller than the view stays at the top-left.
+    pub fn clamp(&mut self, cols: usize, rows: usize, view_w: f64, view_h: f64) {
+        let max_left = (cols as f64 - view_w / self.cell_w()).max(0.0);
+        let max_top = (rows as f64 - view_h / self.cell_h()).max(0.0);
+        self.left = self.left.clamp(0.0, max_left);
+        self.top = self.top.clamp(0.0, max_top);
+    }
+}
+
+/// Direction to scroll with the pointer at (x, y) in `view`: each
+/// component is -1, 0 or 1, non-zero inside the band along that edge.
+pub fn edge_scroll(view: Rect, x: f64, y: f64) -> (f64, f64) {
+    if !view.contains(x, y) {
+        return (0.0, 0.0);
+    }
+    let axis = |p: f64, start: f64, end: f64| {
+        if p < start + EDGE_SCROLL_BAND {
+            -1.0
+        } else if p >= end - EDGE_SCROLL_BAND {
+            1.0
+        } else {
+            0.0
+        }
+    };
+    (axis(x, view.x, view.right()), axis(y, view.y, view.bottom()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_camera_matches_the_fixed_grid() {
+        let cam = Camera::default();
+        assert_eq!((cam.cell_w(), cam.cell_h()), (CELL_W, CELL_H));
+        assert_eq!(cam.cell_rect(2, 3), Rect::new(28.0, 60.0, 14.0, 20.0));
+        assert_eq!(cam.map_point(29.0, 61.0), (29.0 / 14.0, 61.0 / 20.0));
+    }
+
+    #[test]
+    fn zoom_keeps_the_point_under_the_pointer() {
+        let mut cam = Camera { left: 10.0, top: 5.0, ..Camera::default() };
+        let before = cam.map_point(140.0, 100.0);
+        assert!(cam.zoom_at(2, 140.0, 100.0));
+        assert_eq!(cam.zoom(), 2.0);
+        assert_eq!(cam.map_point(140.0, 100.0), before);
+
+        // Zoom stops at the last level
+        assert!(cam.zoom_at(1, 0.0, 0.0));
+        assert!(!cam.zoom_at(1, 0.0, 0.0));
+        cam.reset_zoom(140.0, 100.0);
+        assert_eq!(cam.zoom(), 1.0);
+    }
+
+    #[test]
+    fn visible_range_covers_partial_cells_and_stays_on_the_map() {
+        let cam = Camera { left: 2.5, top: 0.0, ..Camera::default() };
+        // 70px is exactly five cells wide, straddling six columns
+        assert_eq!(cam.visible(100, 100, 70.0, 40.0), (2..8, 0..2));
+        assert_eq!(cam.visible(5, 1, 70.0, 40.0), (2..5, 0..1));
+        let off_map = Camera { left: -10.0, top: 200.0, ..Camera::default() };
+        assert_eq!(off_map.visible(5, 5, 70.0, 40.0), (0..0, 5..5));
+    }
+
+    #[test]
+    fn clamp_keeps_the_view_on_the_map() {
+        let mut cam = Camera::default();
+        cam.pan(-50.0, 10_000.0);
+        cam.clamp(100, 60, 700.0, 400.0);
+        assert_eq!((cam.left, cam.top), (0.0, 40.0));
+
+        // A map smaller than the view stays at the top-left
+        cam.center_on(3.0, 3.0, 700.0, 400.0);
+        cam.clamp(10, 10, 700.0, 400.0);
+        assert_eq!((cam.left, cam.top), (0.0, 0.0));
+    }
+
+    #[test]
+    fn edge_scroll_only_near_the_edges() {
+        let view = Rect::new(0.0, 0.0, 700.0, 400.0);
+        assert_eq!(edge_scroll(view, 350.0, 200.0), (0.0, 0.0));
+        assert_eq!(edge_scroll(view, 2.0, 395.0), (-1.0, 1.0));
+        assert_eq!(edge_scroll(view, 699.0, 20.0), (1.0, 0.0));
+        assert_eq!(edge_scroll(view, 800.0, 5.0), (0.0, 0.0));
+    }
+}
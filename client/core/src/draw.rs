@@ -0,0 +1,283 @@
+//! The drawing surface the renderer paints on. It mirrors the subset of the
+//! Canvas 2D API the renderer uses, so the browser canvas, a recorder for
+//! tests, and a software rasterizer can all stand behind it.
+
+use std::fmt;
+
+use crate::layout::Rect;
+
+/// A 2D drawing surface with canvas-like state: styles, font, alignment
+/// and alpha persist until changed. Colours and fonts use CSS syntax as
+/// produced by the renderer (`rgb(..)`, `rgba(..)`, `"bold 14px ..."`).
+pub trait DrawTarget {
+    /// Current surface size in pixels.
+    fn size(&self) -> (u32, u32);
+    /// Resize the surface. Like a canvas, resizing clears it.
+    fn set_size(&mut self, width: u32, height: u32);
+
+    fn set_fill_style(&mut self, style: &str);
+    fn set_stroke_style(&mut self, style: &str);
+    fn set_line_width(&mut self, width: f64);
+    fn set_global_alpha(&mut self, alpha: f64);
+    fn set_font(&mut self, font: &str);
+    /// "left", "right" or "center".
+    fn set_text_align(&mut self, align: &str);
+    /// "top", "middle" or "alphabetic".
+    fn set_text_baseline(&mut self, baseline: &str);
+
+    fn fill_rect(&mut self, x: f64, y: f64, w: f64, h: f64);
+    fn stroke_rect(&mut self, x: f64, y: f64, w: f64, h: f64);
+
+    fn begin_path(&mut self);
+    fn move_to(&mut self, x: f64, y: f64);
+    fn line_to(&mut self, x: f64, y: f64);
+    fn close_path(&mut self);
+    fn stroke(&mut self);
+    fn fill(&mut self);
+
+    fn fill_text(&mut self, text: &str, x: f64, y: f64);
+    /// Width in pixels of `text` in the current font.
+    fn measure_text(&self, text: &str) -> f64;
+
+    /// A new offscreen surface of the same kind, for content the renderer
+    /// keeps between frames. `None` when the target can't make one; the
+    /// renderer then draws that content directly every frame.
+    fn create_layer(&self) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    /// Copy the `src` part of `layer` onto `dst` on this surface, scaled
+    /// to fit.
+    fn draw_layer(&mut self, _layer: &Self, _src: Rect, _dst: Rect)
+    where
+        Self: Sized,
+    {
+    }
+}
+
+/// Pixel size of a CSS font string such as `"500 15px 'JetBrains Mono'"`.
+pub fn font_px(font: &str) -> f64 {
+    font.split_whitespace()
+        .find_map(|part| part.strip_suffix("px").and_then(|n| n.parse().ok()))
+        .unwrap_or(10.0)
+}
+
+/// Advance of one glyph in a monospace font, as a fraction of its size.
+pub const MONO_ADVANCE: f64 = 0.6;
+
+/// One recorded call on a [`RecordingTarget`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawCommand {
+    SetSize(u32, u32),
+    FillStyle(String),
+    StrokeStyle(String),
+    LineWidth(f64),
+    GlobalAlpha(f64),
+    Font(String),
+    TextAlign(String),
+    TextBaseline(String),
+    FillRect(f64, f64, f64, f64),
+    StrokeRect(f64, f64, f64, f64),
+    BeginPath,
+    MoveTo(f64, f64),
+    LineTo(f64, f64),
+    ClosePath,
+    Stroke,
+    Fill,
+    FillText(String, f64, f64),
+    DrawLayer(Rect, Rect),
+}
+
+impl fmt::Display for DrawCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DrawCommand::SetSize(w, h) => write!(f, "set_size {} {}", w, h),
+            DrawCommand::FillStyle(s) => write!(f, "fill_style {}", s),
+            DrawCommand::StrokeStyle(s) => write!(f, "stroke_style {}", s),
+            DrawCommand::LineWidth(w) => write!(f, "line_width {}", w),
+            DrawCommand::GlobalAlpha(a) => write!(f, "global_alpha {}", a),
+            DrawCommand::Font(s) => write!(f, "font {}", s),
+            DrawCommand::TextAlign(s) => write!(f, "text_align {}", s),
+            DrawCommand::TextBaseline(s) => write!(f, "text_baseline {}", s),
+            DrawCommand::FillRect(x, y, w, h) => write!(f, "fill_rect {} {} {} {}", x, y, w, h),
+            DrawCommand::StrokeRect(x, y, w, h) => write!(f, "stroke_rect {} {} {} {}", x, y, w, h),
+            DrawCommand::BeginPath => write!(f, "begin_path"),
+            DrawCommand::MoveTo(x, y) => write!(f, "move_to {} {}", x, y),
+            DrawCommand::LineTo(x, y) => write!(f, "line_to {} {}", x, y),
+            DrawCommand::ClosePath => write!(f, "close_path"),
+            DrawCommand::Stroke => write!(f, "stroke"),
+            DrawCommand::Fill => write!(f, "fill"),
+            DrawCommand::FillText(t, x, y) => write!(f, "fill_text {:?} {} {}", t, x, y),
+            DrawCommand::DrawLayer(s, d) => {
+                write!(f, "draw_layer {} {} {} {} -> {} {} {} {}", s.x, s.y, s.w, s.h, d.x, d.y, d.w, d.h)
+            }
+        }
+    }
+}
+
+/// A draw target that records every call instead of drawing, for
+/// snapshot tests. Text is measured as a monospace font.
+#[derive(Debug, Default)]
+pub struct RecordingTarget {
+    width: u32,
+    height: u32,
+    font: String,
+    commands: Vec<DrawCommand>,
+}
+
+impl RecordingTarget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn commands(&self) -> &[DrawCommand] {
+        &self.commands
+    }
+
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+
+    /// All recorded commands, one per line.
+    pub fn dump(&self) -> String {
+        let mut out = String::new();
+        for cmd in &self.commands {
+            out.push_str(&cmd.to_string());
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Texts passed to `fill_text`, in draw order.
+    pub fn texts(&self) -> Vec<&str> {
+        self.commands
+            .iter()
+            .filter_map(|c| match c {
+                DrawCommand::FillText(t, _, _) => Some(t.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl DrawTarget for RecordingTarget {
+    fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn set_size(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.commands.push(DrawCommand::SetSize(width, height));
+    }
+
+    fn set_fill_style(&mut self, style: &str) {
+        self.commands.push(DrawCommand::FillStyle(style.to_string()));
+    }
+
+    fn set_stroke_style(&mut self, style: &str) {
+        self.commands.push(DrawCommand::StrokeStyle(style.to_string()));
+    }
+
+    fn set_line_width(&mut self, width: f64) {
+        self.commands.push(DrawCommand::LineWidth(width));
+    }
+
+    fn set_global_alpha(&mut self, alpha: f64) {
+        self.commands.push(DrawCommand::GlobalAlpha(alpha));
+    }
+
+    fn set_font(&mut self, font: &str) {
+        self.font = font.to_string();
+        self.commands.push(DrawCommand::Font(font.to_string()));
+    }
+
+    fn set_text_align(&mut self, align: &str) {
+        self.commands.push(DrawCommand::TextAlign(align.to_string()));
+    }
+
+    fn set_text_baseline(&mut self, baseline: &str) {
+        self.commands.push(DrawCommand::TextBaseline(baseline.to_string()));
+    }
+
+    fn fill_rect(&mut self, x: f64, y: f64, w: f64, h: f64) {
+        self.commands.push(DrawCommand::FillRect(x, y, w, h));
+    }
+
+    fn stroke_rect(&mut self, x: f64, y: f64, w: f64, h: f64) {
+        self.commands.push(DrawCommand::StrokeRect(x, y, w, h));
+    }
+
+    fn begin_path(&mut self) {
+        self.commands.push(DrawCommand::BeginPath);
+    }
+
+    fn move_to(&mut self, x: f64, y: f64) {
+        self.commands.push(DrawCommand::MoveTo(x, y));
+    }
+
+    fn line_to(&mut self, x: f64, y: f64) {
+        self.commands.push(DrawCommand::LineTo(x, y));
+    }
+
+    fn close_path(&mut self) {
+        self.commands.push(DrawCommand::ClosePath);
+    }
+
+    fn stroke(&mut self) {
+        self.commands.push(DrawCommand::Stroke);
+    }
+
+    fn fill(&mut self) {
+        self.commands.push(DrawCommand::Fill);
+    }
+
+    fn fill_text(&mut self, text: &str, x: f64, y: f64) {
+        self.commands.push(DrawCommand::FillText(text.to_string(), x, y));
+    }
+
+    fn measure_text(&self, text: &str) -> f64 {
+        text.chars().count() as f64 * font_px(&self.font) * MONO_ADVANCE
+    }
+
+    fn create_layer(&self) -> Option<Self> {
+        Some(Self::new())
+    }
+
+    /// Records only where the layer went; its own commands stay on the layer.
+    fn draw_layer(&mut self, _layer: &Self, src: Rect, dst: Rect) {
+        self.commands.push(DrawCommand::DrawLayer(src, dst));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn font_px_reads_css_size() {
+        assert_eq!(font_px("bold 14px 'JetBrains Mono', monospace"), 14.0);
+        assert_eq!(font_px("400 12px monospace"), 12.0);
+        assert_eq!(font_px("monospace"), 10.0);
+    }
+
+    #[test]
+    fn recording_measures_monospace() {
+        let mut t = RecordingTarget::new();
+        t.set_font("500 10px monospace");
+        assert_eq!(t.measure_text("abcd"), 24.0);
+    }
+
+    #[test]
+    fn dump_is_one_line_per_command() {
+        let mut t = RecordingTarget::new();
+        t.set_fill_style("rgb(1,2,3)");
+        t.fill_rect(0.0, 0.5, 10.0, 20.0);
+        t.fill_text("hi", 1.0, 2.0);
+        assert_eq!(t.dump(), "fill_style rgb(1,2,3)\nfill_rect 0 0.5 10 20\nfill_text \"hi\" 1 2\n");
+        assert_eq!(t.texts(), vec!["hi"]);
+    }
+}
@@ -0,0 +1,224 @@
+//! Short-lived battle effects, found by diffing consecutive snapshots:
+//! units destroyed next to an enemy, cities changing hands, and units
+//! losing hits. Each effect runs for a fixed time and is then dropped.
+
+use crate::motion::Move;
+use crate::protocol::{CellMsg, CityStatus, Owner, Terrain, UnitMsg};
+use crate::state::GameState;
+
+/// How long a destroyed unit's flash and shockwave last, in ms.
+pub const DESTROYED_MS: f64 = 700.0;
+/// How long the ring round a captured city lasts, in ms.
+pub const CAPTURED_MS: f64 = 1000.0;
+/// How long lost hits float above a damaged unit, in ms.
+pub const DAMAGED_MS: f64 = 1200.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EffectKind {
+    /// A unit vanished with an enemy beside it.
+    Destroyed,
+    /// A city went over to a new owner.
+    Captured(CityStatus),
+    /// A unit lost this many hits.
+    Damaged(u32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Effect {
+    pub col: usize,
+    pub row: usize,
+    pub kind: EffectKind,
+    pub started: f64,
+}
+
+impl Effect {
+    pub fn lifetime(&self) -> f64 {
+        match self.kind {
+            EffectKind::Destroyed => DESTROYED_MS,
+            EffectKind::Captured(_) => CAPTURED_MS,
+            EffectKind::Damaged(_) => DAMAGED_MS,
+        }
+    }
+
+    /// Share of its lifetime the effect has run at `now`, or None once
+    /// it's over.
+    pub fn age(&self, now: f64) -> Option<f64> {
+        let t = ((now - self.started) / self.lifetime()).max(0.0);
+        (t < 1.0).then_some(t)
+    }
+}
+
+/// Effects still running, and the snapshot they were found against.
+#[derive(Debug, Default)]
+pub struct Effects {
+    seq: Option<u64>,
+    cells: Vec<Vec<Option<CellMsg>>>,
+    active: Vec<Effect>,
+}
+
+impl Effects {
+    /// Diff `state` against the last snapshot seen and start an effect,
+    /// at `now`, for each event found. `moves` are the units that moved
+    /// between the two, which are not destroyed. A snapshot that isn't
+    /// `continuous` with the last one only becomes the new baseline.
+    pub fn update(&mut self, state: &GameState, continuous: bool, moves: &[Move], now: f64) {
+        self.active.retain(|e| e.age(now).is_some());
+        if !continuous {
+            self.active.clear();
+        } else if state.seq == self.seq {
+            return;
+        }
+        self.seq = state.seq;
+        if continuous && dims(&self.cells) == dims(&state.cells) {
+            for (col, row, kind) in events(&self.cells, &state.cells, moves) {
+                self.active.push(Effect { col, row, kind, started: now });
+            }
+        }
+        self.cells = state.cells.clone();
+    }
+
+    pub fn active(&self) -> &[Effect] {
+        &self.active
+    }
+}
+
+fn dims(cells: &[Vec<Option<CellMsg>>]) -> (usize, usize) {
+    (cells.len(), cells.first().map_or(0, |c| c.len()))
+}
+
+fn cell(cells: &[Vec<Option<CellMsg>>], col: usize, row: usize) -> Option<&CellMsg> {
+    cells.get(col)?.get(row)?.as_ref()
+}
+
+fn unit(cells: &[Vec<Option<CellMsg>>], col: usize, row: usize) -> Option<&UnitMsg> {
+    cell(cells, col, row)?.u.as_ref()
+}
+
+fn same_unit(a: &UnitMsg, b: &UnitMsg) -> bool {
+    a.t == b.t && a.o == b.o
+}
+
+/// Whether `cells` has a unit or city of the other side next to, or on,
+/// `(col, row)`.
+fn enemy_near(cells: &[Vec<Option<CellMsg>>], col: usize, row: usize, owner: &Owner) -> bool {
+    let enemy_city = match owner {
+        Owner::Player => CityStatus::Computer,
+        _ => CityStatus::Player,
+    };
+    for c in col.saturating_sub(1)..=col + 1 {
+        for r in row.saturating_sub(1)..=row + 1 {
+            let Some(cell) = cell(cells, c, r) else { continue };
+            if cell.u.as_ref().is_some_and(|u| u.o != *owner) || cell.cs.as_ref() == Some(&enemy_city) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+fn lost_hits(before: &UnitMsg, after: &UnitMsg) -> Option<u32> {
+    match (before.h, after.h) {
+        (Some(was), Some(now)) if now < was && same_unit(before, after) => Some(was - now),
+        _ => None,
+    }
+}
+
+fn events(before: &[Vec<Option<CellMsg>>], after: &[Vec<Option<CellMsg>>], moves: &[Move]) -> Vec<(usize, usize, EffectKind)> {
+    let mut events = Vec::new();
+    let (cols, rows) = dims(after);
+    for col in 0..cols {
+        for row in 0..rows {
+            let (Some(was), Some(now)) = (cell(before, col, row), cell(after, col, row)) else {
+                continue;
+            };
+
+            if was.t == Terrain::City && now.t == Terrain::City && was.cs.is_some() && now.cs != was.cs {
+                if let Some(ref cs) = now.cs {
+                    events.push((col, row, EffectKind::Captured(cs.clone())));
+                }
+            }
+
+            let Some(old) = was.u.as_ref() else { continue };
+            match now.u.as_ref().filter(|u| same_unit(old, u)) {
+                Some(new) => {
+                    if let Some(lost) = lost_hits(old, new) {
+                        events.push((col, row, EffectKind::Damaged(lost)));
+                    }
+                }
+                None => {
+                    if let Some(m) = moves.iter().find(|m| m.from == (col, row)) {
+                        // Hits lost on the way
+                        if let Some(lost) = unit(after, m.to.0, m.to.1).and_then(|new| lost_hits(old, new)) {
+                            events.push((m.to.0, m.to.1, EffectKind::Damaged(lost)));
+                        }
+                    } else if enemy_near(before, col, row, &old.o) || enemy_near(after, col, row, &old.o) {
+                        events.push((col, row, EffectKind::Destroyed));
+                    }
+                }
+            }
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn cells(value: Value) -> Vec<Vec<Option<CellMsg>>> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn sea_with(units: &[(usize, Value)]) -> Vec<Vec<Option<CellMsg>>> {
+        let mut column = vec![json!({"t": "sea"}); 4];
+        for (row, u) in units {
+            column[*row] = json!({"t": "sea", "u": u});
+        }
+        cells(json!([column, vec![json!({"t": "sea"}); 4]]))
+    }
+
+    #[test]
+    fn a_unit_lost_beside_an_enemy_is_destroyed() {
+        let destroyer = json!({"t": "destroyer", "o": "player", "m": "awake", "h": 3});
+        let sub = json!({"t": "submarine", "o": "computer", "m": "awake", "h": 2});
+        let before = sea_with(&[(0, destroyer.clone()), (1, sub.clone())]);
+        let after = sea_with(&[(1, sub)]);
+        assert_eq!(events(&before, &after, &[]), [(0, 0, EffectKind::Destroyed)]);
+
+        // Alone, it just went out of sight; moved, it wasn't lost at all
+        let before = sea_with(&[(0, destroyer.clone())]);
+        assert!(events(&before, &sea_with(&[]), &[]).is_empty());
+        let before = sea_with(&[(0, destroyer), (2, json!({"t": "army", "o": "computer", "m": "awake"}))]);
+        let moves = [Move { from: (0, 0), to: (1, 0) }];
+        let after = cells(json!([
+            [{"t": "sea"}, {"t": "sea"}, {"t": "sea", "u": {"t": "army", "o": "computer", "m": "awake"}}, {"t": "sea"}],
+            [{"t": "sea", "u": {"t": "destroyer", "o": "player", "m": "awake", "h": 1}}, {"t": "sea"}, {"t": "sea"}, {"t": "sea"}],
+        ]));
+        assert_eq!(events(&before, &after, &moves), [(1, 0, EffectKind::Damaged(2))]);
+    }
+
+    #[test]
+    fn lost_hits_and_captured_cities() {
+        let before = cells(json!([
+            [{"t": "city", "cs": "computer"}, {"t": "sea", "u": {"t": "battleship", "o": "player", "m": "awake", "h": 10}}],
+            [{"t": "city", "cs": "free"}, {"t": "city"}],
+        ]));
+        let after = cells(json!([
+            [{"t": "city", "cs": "player"}, {"t": "sea", "u": {"t": "battleship", "o": "player", "m": "awake", "h": 7}}],
+            [{"t": "city", "cs": "free"}, {"t": "city", "cs": "computer"}],
+        ]));
+        assert_eq!(events(&before, &after, &[]), [
+            (0, 0, EffectKind::Captured(CityStatus::Player)),
+            (0, 1, EffectKind::Damaged(3)),
+        ]);
+    }
+
+    #[test]
+    fn effects_expire_after_their_lifetime() {
+        let effect = Effect { col: 0, row: 0, kind: EffectKind::Damaged(1), started: 100.0 };
+        assert_eq!(effect.age(100.0), Some(0.0));
+        assert_eq!(effect.age(100.0 + DAMAGED_MS / 2.0), Some(0.5));
+        assert_eq!(effect.age(100.0 + DAMAGED_MS), None);
+    }
+}
@@ -0,0 +1,143 @@
+//! How far the selected fighter can still fly, and where it can land to
+//! refuel on the way. Fighters fly over land and sea alike and move
+//! diagonally, so every cell within `fuel` squares (king's-move distance)
+//! is reachable.
+
+use std::ops::Range;
+
+use crate::protocol::{CityStatus, Owner, UnitKind};
+use crate::state::GameState;
+use crate::units::{FIGHTER_FUEL, FIGHTER_SPEED};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuelRange {
+    pub col: usize,
+    pub row: usize,
+    pub fuel: u32,
+    /// Friendly cities and carriers within range, nearest first.
+    pub refuel: Vec<(usize, usize)>,
+}
+
+impl FuelRange {
+    /// Range of the player fighter on the selected cell, if there is one.
+    pub fn of_selected(state: &GameState) -> Option<Self> {
+        let (col, row) = state.selected_col.zip(state.selected_row)?;
+        let unit = state.cells.get(col)?.get(row)?.as_ref()?.u.as_ref()?;
+        if unit.t != UnitKind::Fighter || unit.o != Owner::Player {
+            return None;
+        }
+        let fuel = unit.fuel.unwrap_or(FIGHTER_FUEL);
+        let mut range = FuelRange { col, row, fuel, refuel: Vec::new() };
+
+        let mut refuel = Vec::new();
+        let (cols, rows) = range.cells(state.map_size);
+        for c in cols {
+            for r in rows.clone() {
+                let Some(Some(cell)) = state.cells.get(c).and_then(|column| column.get(r)) else {
+                    continue;
+                };
+                let carrier = cell.u.as_ref().is_some_and(|u| u.t == UnitKind::Carrier && u.o == Owner::Player);
+                if (c, r) != (col, row) && (cell.cs == Some(CityStatus::Player) || carrier) {
+                    refuel.push((c, r));
+                }
+            }
+        }
+        refuel.sort_by_key(|&(c, r)| range.distance(c, r));
+        range.refuel = refuel;
+        Some(range)
+    }
+
+    /// Squares from the fighter to (col, row).
+    pub fn distance(&self, col: usize, row: usize) -> u32 {
+        self.col.abs_diff(col).max(self.row.abs_diff(row)) as u32
+    }
+
+    /// Columns and rows of the reachable square, clipped to a map of
+    /// `(cols, rows)`.
+    pub fn cells(&self, (cols, rows): (usize, usize)) -> (Range<usize>, Range<usize>) {
+        self.square(self.fuel, cols, rows)
+    }
+
+    /// Columns and rows the fighter can reach this round.
+    pub fn this_round(&self, (cols, rows): (usize, usize)) -> (Range<usize>, Range<usize>) {
+        self.square(self.fuel.min(FIGHTER_SPEED), cols, rows)
+    }
+
+    /// Rounds of flying left before the tank is empty.
+    pub fn rounds_left(&self) -> u32 {
+        self.fuel.div_ceil(FIGHTER_SPEED)
+    }
+
+    /// No friendly city or carrier is within reach.
+    pub fn stranded(&self) -> bool {
+        self.refuel.is_empty()
+    }
+
+    fn square(&self, radius: u32, cols: usize, rows: usize) -> (Range<usize>, Range<usize>) {
+        let radius = radius as usize;
+        let span = |at: usize, count: usize| at.saturating_sub(radius).min(count)..(at + radius + 1).min(count);
+        (span(self.col, cols), span(self.row, rows))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn state(cells: Vec<(usize, usize, serde_json::Value)>) -> GameState {
+        let mut st = GameState::new();
+        st.map_size = (60, 40);
+        st.cells = vec![vec![Some(serde_json::from_value(json!({"t": "sea"})).unwrap()); 40]; 60];
+        for (col, row, cell) in cells {
+            st.cells[col][row] = Some(serde_json::from_value(cell).unwrap());
+        }
+        st
+    }
+
+    fn fighter(fuel: u32) -> serde_json::Value {
+        json!({"t": "land", "u": {"t": "fighter", "o": "player", "m": "awake", "fuel": fuel}})
+    }
+
+    #[test]
+    fn only_for_a_selected_player_fighter() {
+        let mut st = state(vec![
+            (5, 5, fighter(20)),
+            (6, 5, json!({"t": "land", "u": {"t": "army", "o": "player", "m": "awake"}})),
+        ]);
+        assert!(FuelRange::of_selected(&st).is_none());
+        st.selected_col = Some(6);
+        st.selected_row = Some(5);
+        assert!(FuelRange::of_selected(&st).is_none());
+        st.selected_col = Some(5);
+        let range = FuelRange::of_selected(&st).unwrap();
+        assert_eq!(range.fuel, 20);
+        assert_eq!(range.rounds_left(), 3);
+        // Clipped at the top-left corner of the map
+        assert_eq!(range.cells(st.map_size), (0..26, 0..26));
+        assert_eq!(range.this_round(st.map_size), (0..14, 0..14));
+    }
+
+    #[test]
+    fn refuel_points_in_range_nearest_first() {
+        let carrier = json!({"t": "sea", "u": {"t": "carrier", "o": "player", "m": "sentry"}});
+        let mut st = state(vec![
+            (30, 20, fighter(10)),
+            (38, 28, json!({"t": "city", "cs": "player"})),
+            (25, 20, carrier),
+            (30, 10, json!({"t": "city", "cs": "computer"})),
+            (41, 20, json!({"t": "city", "cs": "player"})),
+        ]);
+        st.selected_col = Some(30);
+        st.selected_row = Some(20);
+        let range = FuelRange::of_selected(&st).unwrap();
+        assert_eq!(range.refuel, vec![(25, 20), (38, 28)]);
+        assert!(!range.stranded());
+
+        // Two squares less and only the carrier is left; one more and nothing
+        st.cells[30][20] = Some(serde_json::from_value(fighter(7)).unwrap());
+        assert_eq!(FuelRange::of_selected(&st).unwrap().refuel, vec![(25, 20)]);
+        st.cells[30][20] = Some(serde_json::from_value(fighter(4)).unwrap());
+        assert!(FuelRange::of_selected(&st).unwrap().stranded());
+    }
+}
@@ -0,0 +1,264 @@
+//! Text content of the help and inspector panels, independent of how it is drawn.
+
+use crate::protocol::{CityStatus, Terrain, UnitMsg};
+use crate::state::GameState;
+use crate::units::max_hits;
+
+/// Contextual key hints for the help panel.
+pub fn compute_controls_lines(state: &GameState) -> Vec<String> {
+    // Keep this small and action-oriented. 3-6 lines max.
+    let mut lines: Vec<String> = Vec::new();
+
+    // Global-ish controls
+    let globals: Vec<&str> = vec![
+        "P pause",
+        "+ map",
+        "? tutorial",
+        "h help",
+        "i tips on/off",
+        "I inspector",
+    ];
+    lines.push(format!("Keys: {}", globals.join("  ")));
+
+    // Contextual controls
+    if state.load_menu.is_some() {
+        lines.push("Load menu: click a file, ESC closes".to_string());
+        return lines;
+    }
+    if state.tutorial_menu.is_some() {
+        lines.push("Tutorial menu: click a scenario, ESC closes".to_string());
+        return lines;
+    }
+    if let Some(ref tut) = state.tutorial {
+        if tut.overlay_visible {
+            lines.push("Tutorial: N next, B back, ESC hide".to_string());
+        } else {
+            lines.push("Tutorial: ESC show overlay".to_string());
+        }
+    }
+
+    if state.waiting_for_input {
+        if let Some(&(col, row)) = state.attention_coords.first() {
+            // Try to infer what kind of attention is needed from the cell.
+            let cell = state.cells.get(col).and_then(|c| c.get(row)).and_then(|c| c.clone());
+            if let Some(cell) = cell {
+                if cell.cs == Some(CityStatus::Player) && cell.t == Terrain::City && cell.u.is_none() {
+                    lines.push("Now: choose production (F/T/P/D/S/C/B/Z), X none, SPACE skip".to_string());
+                } else {
+                    lines.push("Now: move QWEASDZXC, SPACE skip, U unload/wake, S sentry, L explore".to_string());
+                }
+            } else {
+                lines.push("Now: act on the highlighted item".to_string());
+            }
+        }
+    } else if state.paused || state.pause_requested {
+        lines.push("Now: SPACE steps one round".to_string());
+    }
+
+    // “New mechanic” nudges (only when relevant and not yet used)
+    let mut nudges: Vec<&str> = Vec::new();
+    if !state.used_map_cycle {
+        nudges.push("+ shows enemy/actual map");
+    }
+    if !state.used_tutorial_menu {
+        nudges.push("? opens tutorial menu");
+    }
+    if !state.used_pause {
+        nudges.push("P pauses at round end");
+    }
+    // Only nudge destination/waypoint once we can aim them (selection preferred; hover fallback)
+    if state.selected_col.is_some() || (state.hover_col.is_some() && state.hover_row.is_some()) {
+        if !state.used_destination {
+            nudges.push(". sets destination at selection");
+        }
+        if !state.used_waypoint {
+            nudges.push("* toggles waypoint at selection");
+        }
+    }
+    if !nudges.is_empty() {
+        lines.push(format!("New: {}", nudges.join("  ")));
+    }
+
+    lines
+}
+
+/// Description of the selected cell for the help and inspector panels.
+pub fn compute_selected_lines(state: &GameState) -> Vec<String> {
+    let (Some(col), Some(row)) = (state.selected_col, state.selected_row) else {
+        return vec![];
+    };
+
+    let mut lines: Vec<String> = Vec::new();
+
+    let cell = state
+        .cells
+        .get(col)
+        .and_then(|c| c.get(row))
+        .and_then(|c| c.as_ref());
+
+    let Some(cell) = cell else {
+        lines.push(format!("Selected: ({}, {})", col, row));
+        return lines;
+    };
+
+    if cell.t == Terrain::City {
+        let owner = cell.cs.as_ref().map_or("unknown", CityStatus::as_str);
+        lines.push(format!("Selected: City ({}) @ ({}, {})", owner, col, row));
+
+        if let Some(ref prod) = cell.prod {
+            lines.push(format!(
+                "Production: {} ({} rounds left)",
+                prod.item, prod.remaining
+            ));
+        } else {
+            lines.push("Production: none".to_string());
+        }
+
+        // Garrison/containers summary when present
+        let mut extras: Vec<String> = Vec::new();
+        if let Some(fc) = cell.fc {
+            if fc > 0 {
+                extras.push(format!("Fighters: {}", fc));
+            }
+        }
+        if let Some(ac) = cell.ac {
+            if ac > 0 {
+                extras.push(format!("Armies: {}", ac));
+            }
+        }
+        if let Some(af) = cell.af {
+            if af > 0 {
+                extras.push(format!("Awake F: {}", af));
+            }
+        }
+        if let Some(aa) = cell.aa {
+            if aa > 0 {
+                extras.push(format!("Awake A: {}", aa));
+            }
+        }
+        if !extras.is_empty() {
+            lines.push(extras.join("  "));
+        }
+
+        if let Some(ref unit) = cell.u {
+            lines.push(unit_line(unit));
+        }
+
+        // Suggestions (browser-friendly: most commands target hover)
+        if cell.cs == Some(CityStatus::Player) {
+            if cell.u.is_some() {
+                lines.push("Suggestion: move the unit first; city production is set when no active unit is in the city".to_string());
+            } else if cell.prod.is_none() {
+                lines.push("Suggestion: set production (F/T/P/D/S/C/B/Z) or X for none".to_string());
+            } else {
+                lines.push("Suggestion: wait, or X to clear and pick a new unit".to_string());
+            }
+        } else {
+            lines.push("Suggestion: capture with an adjacent Army".to_string());
+        }
+    } else {
+        lines.push(format!("Selected: {} @ ({}, {})", cell.t, col, row));
+        if let Some(ref unit) = cell.u {
+            lines.push(unit_line(unit));
+            if state.waiting_for_input {
+                lines.push("Suggestion: move QWEASDZXC or SPACE to skip".to_string());
+            }
+        }
+    }
+
+    lines
+}
+
+fn unit_line(unit: &UnitMsg) -> String {
+    match (unit.h, max_hits(&unit.t)) {
+        (Some(h), Some(max)) => format!("Unit: {} ({}), hits {}/{}", unit.t, unit.m, h, max),
+        _ => format!("Unit: {} ({})", unit.t, unit.m),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::CellMsg;
+
+    fn state_with_cell(json: &str) -> GameState {
+        let mut st = GameState::new();
+        st.map_size = (1, 1);
+        st.cells = vec![vec![Some(serde_json::from_str::<CellMsg>(json).unwrap())]];
+        st
+    }
+
+    #[test]
+    fn nothing_selected_has_no_lines() {
+        assert!(compute_selected_lines(&GameState::new()).is_empty());
+    }
+
+    #[test]
+    fn player_city_lists_production_and_garrison() {
+        let mut st = state_with_cell(
+            r#"{"t":"city","cs":"player","fc":3,"af":1,"prod":{"item":"fighter","remaining":4}}"#,
+        );
+        st.selected_col = Some(0);
+        st.selected_row = Some(0);
+        let lines = compute_selected_lines(&st);
+        assert_eq!(lines[0], "Selected: City (player) @ (0, 0)");
+        assert_eq!(lines[1], "Production: fighter (4 rounds left)");
+        assert_eq!(lines[2], "Fighters: 3  Awake F: 1");
+        assert!(lines[3].starts_with("Suggestion: wait"));
+    }
+
+    #[test]
+    fn enemy_city_suggests_capture() {
+        let mut st = state_with_cell(r#"{"t":"city","cs":"computer"}"#);
+        st.selected_col = Some(0);
+        st.selected_row = Some(0);
+        let lines = compute_selected_lines(&st);
+        assert_eq!(lines.last().unwrap(), "Suggestion: capture with an adjacent Army");
+    }
+
+    #[test]
+    fn unit_on_terrain_shows_type_and_mode() {
+        let mut st = state_with_cell(r#"{"t":"sea","u":{"t":"destroyer","o":"player","m":"sentry"}}"#);
+        st.selected_col = Some(0);
+        st.selected_row = Some(0);
+        let lines = compute_selected_lines(&st);
+        assert_eq!(lines, vec!["Selected: sea @ (0, 0)", "Unit: destroyer (sentry)"]);
+    }
+
+    #[test]
+    fn unit_shows_hits_when_known() {
+        let mut st = state_with_cell(r#"{"t":"sea","u":{"t":"battleship","o":"player","m":"awake","h":4}}"#);
+        st.selected_col = Some(0);
+        st.selected_row = Some(0);
+        assert_eq!(compute_selected_lines(&st)[1], "Unit: battleship (awake), hits 4/10");
+    }
+
+    #[test]
+    fn controls_prompt_for_production_at_attention_city() {
+        let mut st = state_with_cell(r#"{"t":"city","cs":"player"}"#);
+        st.waiting_for_input = true;
+        st.attention_coords = vec![(0, 0)];
+        let lines = compute_controls_lines(&st);
+        assert!(lines[0].starts_with("Keys: P pause"));
+        assert!(lines.iter().any(|l| l.starts_with("Now: choose production")));
+    }
+
+    #[test]
+    fn menus_short_circuit_controls() {
+        let mut st = GameState::new();
+        st.load_menu = Some(crate::protocol::LoadMenuMsg { files: vec![], hovered: None });
+        let lines = compute_controls_lines(&st);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1], "Load menu: click a file, ESC closes");
+    }
+
+    #[test]
+    fn nudges_disappear_once_used() {
+        let mut st = GameState::new();
+        assert!(compute_controls_lines(&st).iter().any(|l| l.contains("P pauses at round end")));
+        st.used_pause = true;
+        st.used_map_cycle = true;
+        st.used_tutorial_menu = true;
+        assert!(!compute_controls_lines(&st).iter().any(|l| l.starts_with("New:")));
+    }
+}
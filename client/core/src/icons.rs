@@ -0,0 +1,189 @@
+//! Vector silhouettes for the nine unit types, the alternative to drawing
+//! units as letters. Shapes are polygons in a unit square (y down), scaled
+//! into a square centred in the cell.
+
+use crate::draw::DrawTarget;
+use crate::layout::Rect;
+use crate::protocol::UnitKind;
+
+/// How units are drawn on the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnitStyle {
+    /// `unit_char` glyphs, lowercase for the computer.
+    #[default]
+    Letters,
+    /// Silhouettes from this module, owner shown by colour and outline.
+    Icons,
+}
+
+impl UnitStyle {
+    pub fn toggled(self) -> Self {
+        match self {
+            UnitStyle::Letters => UnitStyle::Icons,
+            UnitStyle::Icons => UnitStyle::Letters,
+        }
+    }
+}
+
+/// Share of the cell's shorter side the icon square takes up.
+const ICON_SCALE: f64 = 0.95;
+
+type Shape = &'static [&'static [(f64, f64)]];
+
+const TANK: Shape = &[
+    &[(0.04, 0.52), (0.96, 0.52), (0.84, 0.86), (0.16, 0.86)],
+    &[(0.26, 0.28), (0.66, 0.28), (0.66, 0.52), (0.26, 0.52)],
+    &[(0.66, 0.36), (0.98, 0.36), (0.98, 0.44), (0.66, 0.44)],
+];
+
+const PLANE: Shape = &[&[
+    (0.5, 0.02),
+    (0.58, 0.3),
+    (0.95, 0.55),
+    (0.95, 0.65),
+    (0.58, 0.55),
+    (0.56, 0.8),
+    (0.72, 0.92),
+    (0.72, 0.98),
+    (0.5, 0.92),
+    (0.28, 0.98),
+    (0.28, 0.92),
+    (0.44, 0.8),
+    (0.42, 0.55),
+    (0.05, 0.65),
+    (0.05, 0.55),
+    (0.42, 0.3),
+]];
+
+const PATROL_BOAT: Shape = &[
+    &[(0.1, 0.5), (0.9, 0.5), (0.75, 0.8), (0.25, 0.8)],
+    &[(0.32, 0.25), (0.62, 0.25), (0.62, 0.5), (0.32, 0.5)],
+];
+
+const DESTROYER: Shape = &[
+    &[(0.02, 0.5), (0.98, 0.45), (0.82, 0.8), (0.1, 0.8)],
+    &[(0.28, 0.22), (0.55, 0.22), (0.55, 0.5), (0.28, 0.5)],
+    &[(0.62, 0.35), (0.78, 0.35), (0.78, 0.47), (0.62, 0.47)],
+];
+
+const SUBMARINE: Shape = &[
+    &[(0.03, 0.62), (0.18, 0.45), (0.85, 0.45), (0.98, 0.62), (0.85, 0.8), (0.18, 0.8)],
+    &[(0.4, 0.2), (0.58, 0.2), (0.62, 0.45), (0.36, 0.45)],
+];
+
+const TRANSPORT: Shape = &[
+    &[(0.04, 0.5), (0.96, 0.5), (0.88, 0.82), (0.12, 0.82)],
+    &[(0.14, 0.22), (0.46, 0.22), (0.46, 0.5), (0.14, 0.5)],
+    &[(0.52, 0.22), (0.84, 0.22), (0.84, 0.5), (0.52, 0.5)],
+];
+
+const CARRIER: Shape = &[
+    &[(0.0, 0.45), (1.0, 0.45), (1.0, 0.58), (0.0, 0.58)],
+    &[(0.06, 0.58), (0.94, 0.58), (0.8, 0.82), (0.2, 0.82)],
+    &[(0.6, 0.18), (0.78, 0.18), (0.78, 0.45), (0.6, 0.45)],
+];
+
+const BATTLESHIP: Shape = &[
+    &[(0.0, 0.52), (1.0, 0.46), (0.86, 0.84), (0.08, 0.84)],
+    &[(0.36, 0.14), (0.6, 0.14), (0.64, 0.5), (0.32, 0.5)],
+    &[(0.08, 0.34), (0.28, 0.34), (0.28, 0.5), (0.08, 0.5)],
+    &[(0.7, 0.32), (0.9, 0.32), (0.9, 0.48), (0.7, 0.48)],
+];
+
+const SATELLITE: Shape = &[
+    &[(0.36, 0.32), (0.64, 0.32), (0.64, 0.68), (0.36, 0.68)],
+    &[(0.02, 0.36), (0.3, 0.36), (0.3, 0.64), (0.02, 0.64)],
+    &[(0.7, 0.36), (0.98, 0.36), (0.98, 0.64), (0.7, 0.64)],
+    &[(0.3, 0.46), (0.7, 0.46), (0.7, 0.54), (0.3, 0.54)],
+];
+
+const UNKNOWN: Shape = &[&[(0.5, 0.15), (0.85, 0.5), (0.5, 0.85), (0.15, 0.5)]];
+
+fn shape(kind: &UnitKind) -> Shape {
+    match kind {
+        UnitKind::Army => TANK,
+        UnitKind::Fighter => PLANE,
+        UnitKind::PatrolBoat => PATROL_BOAT,
+        UnitKind::Destroyer => DESTROYER,
+        UnitKind::Submarine => SUBMARINE,
+        UnitKind::Transport => TRANSPORT,
+        UnitKind::Carrier => CARRIER,
+        UnitKind::Battleship => BATTLESHIP,
+        UnitKind::Satellite => SATELLITE,
+        UnitKind::Unknown(_) => UNKNOWN,
+    }
+}
+
+/// The square, centred in `cell`, that an icon is drawn into.
+pub fn icon_box(cell: Rect) -> Rect {
+    let side = cell.w.min(cell.h) * ICON_SCALE;
+    Rect::new(cell.x + (cell.w - side) / 2.0, cell.y + (cell.h - side) / 2.0, side, side)
+}
+
+/// The silhouette of `kind` in `cell`, ringed `line_width` px wide in
+/// `outline`. The outline is stroked first, at double width, so the fill
+/// covers its inner half and thin hulls keep their colour.
+pub fn draw_unit_icon(ctx: &mut impl DrawTarget, kind: &UnitKind, cell: Rect, fill: &str, outline: &str, line_width: f64) {
+    let b = icon_box(cell);
+    ctx.begin_path();
+    for polygon in shape(kind) {
+        for (i, &(x, y)) in polygon.iter().enumerate() {
+            let (px, py) = (b.x + x * b.w, b.y + y * b.h);
+            if i == 0 {
+                ctx.move_to(px, py);
+            } else {
+                ctx.line_to(px, py);
+            }
+        }
+        ctx.close_path();
+    }
+    ctx.set_stroke_style(outline);
+    ctx.set_line_width(2.0 * line_width);
+    ctx.stroke();
+    ctx.set_line_width(1.0);
+    ctx.set_fill_style(fill);
+    ctx.fill();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::draw::{DrawCommand, RecordingTarget};
+
+    const KINDS: [&str; 9] = [
+        "army",
+        "fighter",
+        "patrol-boat",
+        "destroyer",
+        "submarine",
+        "transport",
+        "carrier",
+        "battleship",
+        "satellite",
+    ];
+
+    #[test]
+    fn every_unit_type_has_its_own_shape() {
+        for (i, a) in KINDS.iter().enumerate() {
+            assert!(!std::ptr::eq(shape(&UnitKind::from(*a)), UNKNOWN), "{} has no icon", a);
+            for b in &KINDS[i + 1..] {
+                assert!(!std::ptr::eq(shape(&UnitKind::from(*a)), shape(&UnitKind::from(*b))));
+            }
+        }
+    }
+
+    #[test]
+    fn icons_stay_inside_the_cell() {
+        let cell = Rect::new(28.0, 40.0, 14.0, 20.0);
+        for kind in KINDS {
+            let mut target = RecordingTarget::new();
+            draw_unit_icon(&mut target, &UnitKind::from(kind), cell, "red", "black", 1.0);
+            for cmd in target.commands() {
+                if let DrawCommand::MoveTo(x, y) | DrawCommand::LineTo(x, y) = *cmd {
+                    assert!(cell.contains(x, y), "{} point ({}, {}) outside the cell", kind, x, y);
+                }
+            }
+            assert!(target.commands().contains(&DrawCommand::Fill));
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use crate::layout::{Control, Layout};
+use crate::protocol::ClientMessage;
+use crate::state::GameState;
+
+/// Act on a click on an overlay control: apply local UI toggles to
+/// `state` and return the messages to send to the server, in order.
+pub fn activate_control(state: &mut GameState, control: Control) -> Vec<ClientMessage> {
+    // Server-side tutorial navigation is keyboard driven; press and release
+    // so the next real key press is not swallowed.
+    let press = |key: &str| {
+        vec![
+            ClientMessage::Key { key: key.to_string(), shift: false, mouse_x: 0, mouse_y: 0 },
+            ClientMessage::KeyUp,
+        ]
+    };
+    match control {
+        Control::TutorialMenuItem(idx) => {
+            let Some(scenario) = state.tutorial_menu.as_ref().and_then(|m| m.scenarios.get(idx)) else {
+                return Vec::new();
+            };
+            let id = scenario.id.clone();
+            state.used_tutorial_menu = true;
+            vec![ClientMessage::TutorialSelect { id }]
+        }
+        Control::LoadMenuItem(idx) => state
+            .load_menu
+            .as_ref()
+            .and_then(|m| m.files.get(idx))
+            .map(|file| vec![ClientMessage::LoadSelect { file: file.clone() }])
+            .unwrap_or_default(),
+        Control::HideHelp => {
+            state.show_help_overlay = false;
+            Vec::new()
+        }
+        Control::ToggleInspector => {
+            state.show_inspector = !state.show_inspector;
+            Vec::new()
+        }
+        Control::HideInspector => {
+            state.show_inspector = false;
+            Vec::new()
+        }
+        Control::TutorialNext => press("n"),
+        Control::TutorialBack => press("b"),
+        Control::TutorialHide => press("escape"),
+        // The replay player lives in the front end, which handles these
+        Control::ReplayPlayPause
+        | Control::ReplayStep
+        | Control::ReplaySpeed
+        | Control::ReplayExit
+        | Control::ReplaySeek => Vec::new(),
+        // Needs the pointer position; see `jump_to_minimap`
+        Control::Minimap => Vec::new(),
+    }
+}
+
+/// Scroll the map view by (dx, dy) canvas pixels, stopping at the map
+/// edges. `layout` is the last frame's, so panning starts from the view
+/// on screen.
+pub fn pan_camera(state: &mut GameState, layout: &Layout, dx: f64, dy: f64) {
+    let mut camera = layout.camera;
+    camera.pan(dx, dy);
+    camera.clamp(layout.cols, layout.rows, layout.map.w, layout.map.h);
+    state.camera = camera;
+}
+
+/// Zoom the map view `steps` levels in (positive) or out around canvas
+/// point (x, y). The next layout keeps the result on the map.
+pub fn zoom_camera(state: &mut GameState, layout: &Layout, steps: i32, x: f64, y: f64) {
+    let mut camera = layout.camera;
+    if camera.zoom_at(steps, x, y) {
+        state.camera = camera;
+    }
+}
+
+/// Centre the map view on the minimap point under canvas point (x, y).
+pub fn jump_to_minimap(state: &mut GameState, layout: &Layout, x: f64, y: f64) {
+    let Some(minimap) = &layout.minimap else {
+        return;
+    };
+    let (col, row) = minimap.map_point(x, y);
+    let mut camera = layout.camera;
+    camera.center_on(col, row, layout.map.w, layout.map.h);
+    camera.clamp(layout.cols, layout.rows, layout.map.w, layout.map.h);
+    state.camera = camera;
+}
+
+/// Local map view keys: arrows scroll by a quarter of the view, `=` and
+/// `-` zoom around its centre and `0` restores the default zoom. Returns
+/// whether `key` was one of them.
+pub fn camera_key(state: &mut GameState, layout: &Layout, key: &str) -> bool {
+    let (w, h) = (layout.map.w, layout.map.h);
+    let (cx, cy) = (w / 2.0, h / 2.0);
+    match key {
+        "ArrowLeft" => pan_camera(state, layout, -w / 4.0, 0.0),
+        "ArrowRight" => pan_camera(state, layout, w / 4.0, 0.0),
+        "ArrowUp" => pan_camera(state, layout, 0.0, -h / 4.0),
+        "ArrowDown" => pan_camera(state, layout, 0.0, h / 4.0),
+        "=" => zoom_camera(state, layout, 1, cx, cy),
+        "-" => zoom_camera(state, layout, -1, cx, cy),
+        "0" => {
+            let mut camera = layout.camera;
+            camera.reset_zoom(cx, cy);
+            state.camera = camera;
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// Translate a browser-style key name (`KeyboardEvent.key`) into the key
+/// the server expects, paired with the `key` message to send. Returns None
+/// for keys the server does not handle.
+pub fn map_key_event(key: &str, shift: bool, mouse_x: i32, mouse_y: i32) -> Option<(String, ClientMessage)> {
+    let mapped = match key {
+        // Movement keys
+        "q" | "w" | "e" | "a" | "d" | "z" | "x" | "c" => {
+            if shift { key.to_uppercase() } else { key.to_string() }
+        }
+        "Q" | "W" | "E" | "A" | "D" | "Z" | "X" | "C" => key.to_string(),
+        // Production keys
+        "f" | "t" | "p" | "s" | "b" => {
+            if shift { key.to_uppercase() } else { key.to_string() }
+        }
+        "F" | "T" | "P" | "S" | "B" => key.to_string(),
+        // Special keys
+        " " => "space".to_string(),
+        "Escape" => "escape".to_string(),
+        "`" => "`".to_string(),
+        "!" => "!".to_string(),
+        "." => ".".to_string(),
+        "*" => "*".to_string(),
+        "+" => "+".to_string(),
+        "^" => "^".to_string(),
+        // Unit command keys
+        "u" => if shift { "U".to_string() } else { "u".to_string() },
+        "U" => "U".to_string(),
+        "l" => if shift { "L".to_string() } else { "l".to_string() },
+        "L" => "L".to_string(),
+        "m" => if shift { "M".to_string() } else { "m".to_string() },
+        "M" => "M".to_string(),
+        "o" => if shift { "O".to_string() } else { "o".to_string() },
+        "O" => "O".to_string(),
+        // Tutorial keys
+        "n" => if shift { "N".to_string() } else { "n".to_string() },
+        "N" => "N".to_string(),
+        "?" => "?".to_string(),
+
+        // Tips toggle (server-side)
+        "h" => "h".to_string(),
+        // Tips toggle (server-side) alternate binding for the browser client.
+        // Lowercase 'h' is reserved for hiding the local help panel.
+        "i" => "h".to_string(),
+        _ => return None,
+    };
+
+    let msg = ClientMessage::Key {
+        key: mapped.clone(),
+        shift,
+        mouse_x,
+        mouse_y,
+    };
+
+    Some((mapped, msg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{LoadMenuMsg, TutorialMenuMsg, TutorialScenarioMsg};
+
+    fn menu(n: usize) -> TutorialMenuMsg {
+        TutorialMenuMsg {
+            scenarios: (0..n)
+                .map(|i| TutorialScenarioMsg {
+                    id: format!("s{}", i),
+                    name: format!("Scenario {}", i),
+                    description: String::new(),
+                })
+                .collect(),
+        }
+    }
+
+    fn large_map_on_small_screen() -> (GameState, Layout) {
+        let mut state = GameState::new();
+        state.map_size = (200, 100);
+        state.screen_size = Some((700.0, 568.0));
+        let layout = Layout::compute(&state, &mut crate::draw::RecordingTarget::new());
+        (state, layout)
+    }
+
+    #[test]
+    fn panning_stops_at_the_map_edges() {
+        let (mut state, layout) = large_map_on_small_screen();
+        assert_eq!((layout.map.w, layout.map.h), (700.0, 500.0));
+
+        pan_camera(&mut state, &layout, -100.0, 140.0);
+        assert_eq!((state.camera.left, state.camera.top), (0.0, 7.0));
+        pan_camera(&mut state, &layout, 1e6, 1e6);
+        // 700px shows 50 columns and 500px 25 rows
+        assert_eq!((state.camera.left, state.camera.top), (150.0, 75.0));
+    }
+
+    #[test]
+    fn minimap_click_centres_the_view() {
+        let (mut state, layout) = large_map_on_small_screen();
+        let minimap = layout.minimap.as_ref().unwrap();
+        let (x, y) = (minimap.map.x + 100.0 * minimap.scale, minimap.map.y + 50.0 * minimap.scale);
+        jump_to_minimap(&mut state, &layout, x, y);
+        assert_eq!((state.camera.left, state.camera.top), (75.0, 37.5));
+
+        // Near a corner the view stops at the map edge
+        jump_to_minimap(&mut state, &layout, minimap.map.x, minimap.map.y);
+        assert_eq!((state.camera.left, state.camera.top), (0.0, 0.0));
+    }
+
+    #[test]
+    fn camera_keys_pan_and_zoom() {
+        let (mut state, layout) = large_map_on_small_screen();
+        assert!(camera_key(&mut state, &layout, "ArrowRight"));
+        assert_eq!(state.camera.left, 175.0 / 14.0);
+        assert!(camera_key(&mut state, &layout, "="));
+        assert_eq!(state.camera.zoom(), 1.5);
+        assert!(camera_key(&mut state, &layout, "0"));
+        assert_eq!(state.camera.zoom(), 1.0);
+        assert!(!camera_key(&mut state, &layout, "q"));
+    }
+
+    #[test]
+    fn movement_keys_respect_shift() {
+        let (k, _) = map_key_event("q", false, 0, 0).unwrap();
+        assert_eq!(k, "q");
+        let (k, _) = map_key_event("q", true, 0, 0).unwrap();
+        assert_eq!(k, "Q");
+    }
+
+    #[test]
+    fn special_keys_are_renamed() {
+        assert_eq!(map_key_event(" ", false, 0, 0).unwrap().0, "space");
+        assert_eq!(map_key_event("Escape", false, 0, 0).unwrap().0, "escape");
+        assert_eq!(map_key_event("i", false, 0, 0).unwrap().0, "h");
+    }
+
+    #[test]
+    fn unmapped_keys_are_ignored() {
+        assert!(map_key_event("F3", false, 0, 0).is_none());
+        assert!(map_key_event("I", true, 0, 0).is_none());
+    }
+
+    #[test]
+    fn key_message_carries_target_cell() {
+        let (_, msg) = map_key_event("s", false, 4, 9).unwrap();
+        assert_eq!(
+            msg,
+            ClientMessage::Key { key: "s".to_string(), shift: false, mouse_x: 4, mouse_y: 9 }
+        );
+    }
+
+    #[test]
+    fn tutorial_menu_item_selects_scenario() {
+        let mut st = GameState::new();
+        st.tutorial_menu = Some(menu(3));
+        let msgs = activate_control(&mut st, Control::TutorialMenuItem(2));
+        assert_eq!(msgs, vec![ClientMessage::TutorialSelect { id: "s2".to_string() }]);
+        assert!(st.used_tutorial_menu);
+        // Stale index from a menu that has since changed
+        assert!(activate_control(&mut st, Control::TutorialMenuItem(7)).is_empty());
+    }
+
+    #[test]
+    fn load_menu_item_selects_file() {
+        let mut st = GameState::new();
+        st.load_menu = Some(LoadMenuMsg { files: vec!["a.edn".to_string(), "b.edn".to_string()], hovered: None });
+        assert_eq!(
+            activate_control(&mut st, Control::LoadMenuItem(1)),
+            vec![ClientMessage::LoadSelect { file: "b.edn".to_string() }]
+        );
+        st.load_menu = None;
+        assert!(activate_control(&mut st, Control::LoadMenuItem(1)).is_empty());
+    }
+
+    #[test]
+    fn panel_buttons_toggle_locally() {
+        let mut st = GameState::new();
+        assert!(activate_control(&mut st, Control::HideHelp).is_empty());
+        assert!(!st.show_help_overlay);
+        activate_control(&mut st, Control::ToggleInspector);
+        assert!(!st.show_inspector);
+        activate_control(&mut st, Control::ToggleInspector);
+        assert!(st.show_inspector);
+        activate_control(&mut st, Control::HideInspector);
+        assert!(!st.show_inspector);
+    }
+
+    #[test]
+    fn tutorial_buttons_press_and_release_keys() {
+        let mut st = GameState::new();
+        let msgs = activate_control(&mut st, Control::TutorialBack);
+        assert_eq!(
+            msgs,
+            vec![
+                ClientMessage::Key { key: "b".to_string(), shift: false, mouse_x: 0, mouse_y: 0 },
+                ClientMessage::KeyUp,
+            ]
+        );
+    }
+}
@@ -0,0 +1,951 @@
+//! Screen layout of the map, message area and every overlay, computed once
+//! per frame. The renderer draws from it and the mouse handlers hit-test
+//! against it, so panel geometry is defined in exactly one place.
+
+use crate::camera::Camera;
+use crate::draw::{font_px, DrawTarget};
+use crate::hud::{compute_controls_lines, compute_selected_lines};
+use crate::protocol::{LoadMenuMsg, TutorialMenuMsg, TutorialMsg};
+use crate::replay::ReplayStatus;
+use crate::renderer::{
+    CELL_H, FONT_MENU_HINT, FONT_MENU_ITEM, MIN_CANVAS_W, TEXT_AREA_GAP, TEXT_AREA_ROWS,
+};
+use crate::state::GameState;
+
+// Canvas height while there is no map, enough for the protocol banner
+const EMPTY_CANVAS_H: f64 = 240.0;
+// Slack around text buttons so they are easy to hit
+const BUTTON_PAD: f64 = 2.0;
+// Replay controls strip below the message area
+const REPLAY_BAR_H: f64 = 32.0;
+// Largest minimap, and its distance from the corner of the map view
+const MINIMAP_MAX_W: f64 = 200.0;
+const MINIMAP_MAX_H: f64 = 140.0;
+const MINIMAP_MAX_SCALE: f64 = 4.0;
+const MINIMAP_MARGIN: f64 = 10.0;
+const MINIMAP_PAD: f64 = 4.0;
+
+/// An axis-aligned rectangle in canvas pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub w: f64,
+    pub h: f64,
+}
+
+impl Rect {
+    pub const fn new(x: f64, y: f64, w: f64, h: f64) -> Self {
+        Self { x, y, w, h }
+    }
+
+    pub fn right(&self) -> f64 {
+        self.x + self.w
+    }
+
+    pub fn bottom(&self) -> f64 {
+        self.y + self.h
+    }
+
+    /// Whether (px, py) lies inside; the right and bottom edges are
+    /// exclusive so adjacent rectangles never both claim a point.
+    pub fn contains(&self, px: f64, py: f64) -> bool {
+        px >= self.x && px < self.right() && py >= self.y && py < self.bottom()
+    }
+}
+
+/// An interactive element of an overlay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Control {
+    TutorialMenuItem(usize),
+    LoadMenuItem(usize),
+    HideHelp,
+    ToggleInspector,
+    HideInspector,
+    TutorialNext,
+    TutorialBack,
+    TutorialHide,
+    ReplayPlayPause,
+    ReplayStep,
+    ReplaySpeed,
+    ReplayExit,
+    /// The replay timeline; where on it is read with `ReplayBarLayout::time_at`.
+    ReplaySeek,
+    /// The minimap; where on it is read with `MinimapLayout::map_point`.
+    Minimap,
+}
+
+/// What lies under a canvas position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hit {
+    Control(Control),
+    /// Over an overlay (or a modal menu's backdrop) but not on a control.
+    /// The pointer must not reach the map underneath.
+    Overlay,
+    Cell { col: usize, row: usize },
+}
+
+/// A clickable text label. The label is drawn with a top baseline at
+/// (`x`, `y`); `rect` is its hit area.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Button {
+    pub control: Control,
+    pub label: String,
+    pub x: f64,
+    pub y: f64,
+    pub rect: Rect,
+}
+
+/// Tutorial scenario menu or load game menu: a centred modal dialog with
+/// one row per entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MenuLayout {
+    pub panel: Rect,
+    pub padding: f64,
+    pub content_top: f64,
+    pub items: Vec<Rect>,
+}
+
+/// Contextual help panel (tip, selection summary and controls).
+#[derive(Debug, Clone, PartialEq)]
+pub struct HelpLayout {
+    pub panel: Rect,
+    pub padding: f64,
+    pub title: String,
+    pub separator_y: f64,
+    pub tip_lines: Vec<String>,
+    pub tip_top: f64,
+    /// Header position of the "Selected" section, when there is one.
+    pub selected_top: Option<f64>,
+    pub selected_lines: Vec<String>,
+    pub selected_lines_top: f64,
+    pub controls_lines: Vec<String>,
+    pub controls_top: f64,
+    pub buttons: Vec<Button>,
+}
+
+/// Inspector panel with details of the selected cell.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InspectorLayout {
+    pub panel: Rect,
+    pub padding: f64,
+    /// Wrapped body lines, already truncated to fit the panel.
+    pub lines: Vec<String>,
+    pub text_top: f64,
+    pub buttons: Vec<Button>,
+}
+
+/// Tutorial page overlay in the top right corner.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TutorialOverlayLayout {
+    pub panel: Rect,
+    pub padding: f64,
+    pub separator_y: f64,
+    pub lines: Vec<String>,
+    pub text_top: f64,
+    pub page_label: String,
+    pub nav_y: f64,
+    pub buttons: Vec<Button>,
+}
+
+/// Centred error banner for an incompatible server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BannerLayout {
+    pub panel: Rect,
+    pub padding: f64,
+    pub lines: Vec<String>,
+    pub text_top: f64,
+}
+
+/// Replay controls and timeline scrubber, shown while playing a recording.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayBarLayout {
+    pub panel: Rect,
+    pub buttons: Vec<Button>,
+    /// The scrubber; its full width spans the recording.
+    pub track: Rect,
+    pub time_label: String,
+    pub label_x: f64,
+    pub text_y: f64,
+}
+
+impl ReplayBarLayout {
+    /// Recording time under canvas x, clamped to the recording.
+    pub fn time_at(&self, x: f64, duration_ms: f64) -> f64 {
+        ((x - self.track.x) / self.track.w).clamp(0.0, 1.0) * duration_ms
+    }
+
+    /// Canvas x of recording time `t_ms`.
+    pub fn x_at(&self, t_ms: f64, duration_ms: f64) -> f64 {
+        let frac = if duration_ms > 0.0 { (t_ms / duration_ms).clamp(0.0, 1.0) } else { 0.0 };
+        self.track.x + frac * self.track.w
+    }
+}
+
+/// Overview of the whole map in the corner of a scrolled map view.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MinimapLayout {
+    pub panel: Rect,
+    /// Where the map is drawn, `scale` pixels a cell.
+    pub map: Rect,
+    pub scale: f64,
+    /// The part of the map shown in the main view.
+    pub viewport: Rect,
+}
+
+impl MinimapLayout {
+    /// Map position, in fractional cells, under (x, y).
+    pub fn map_point(&self, x: f64, y: f64) -> (f64, f64) {
+        ((x - self.map.x) / self.scale, (y - self.map.y) / self.scale)
+    }
+
+    pub fn cell_rect(&self, col: usize, row: usize) -> Rect {
+        Rect::new(self.map.x + col as f64 * self.scale, self.map.y + row as f64 * self.scale, self.scale, self.scale)
+    }
+}
+
+/// Where everything goes on one frame.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Layout {
+    pub width: f64,
+    pub height: f64,
+    pub cols: usize,
+    pub rows: usize,
+    /// The map view; cells are placed in it by `camera`.
+    pub map: Rect,
+    /// `GameState::camera` kept on the map for this view.
+    pub camera: Camera,
+    pub message_area: Rect,
+    pub inspector: Option<InspectorLayout>,
+    pub tutorial_overlay: Option<TutorialOverlayLayout>,
+    pub help: Option<HelpLayout>,
+    pub tutorial_menu: Option<MenuLayout>,
+    pub load_menu: Option<MenuLayout>,
+    pub protocol_banner: Option<BannerLayout>,
+    pub replay_bar: Option<ReplayBarLayout>,
+    pub minimap: Option<MinimapLayout>,
+}
+
+impl Layout {
+    /// Lay out `state` for drawing on `ctx`, which is used to measure text
+    /// (its font is left changed).
+    pub fn compute(state: &GameState, ctx: &mut impl DrawTarget) -> Self {
+        let (cols, rows) = state.map_size;
+        if cols == 0 || rows == 0 {
+            let (width, height) = (MIN_CANVAS_W, EMPTY_CANVAS_H);
+            return Layout {
+                width,
+                height,
+                protocol_banner: state
+                    .protocol_error
+                    .as_deref()
+                    .map(|err| banner_layout(ctx, err, width, height)),
+                ..Layout::default()
+            };
+        }
+
+        let text_h = TEXT_AREA_GAP + (TEXT_AREA_ROWS as f64 * CELL_H);
+        let replay_h = if state.replay.is_some() { REPLAY_BAR_H } else { 0.0 };
+        let mut camera = state.camera;
+        let (full_w, full_h) = (cols as f64 * camera.cell_w(), rows as f64 * camera.cell_h());
+        // Maps larger than the screen are shown through a scrolling view
+        let (grid_w, map_h) = match state.screen_size {
+            Some((w, h)) => (
+                full_w.min(w.max(MIN_CANVAS_W).trunc()),
+                full_h.min((h - text_h - replay_h).max(camera.cell_h()).trunc()),
+            ),
+            None => (full_w, full_h),
+        };
+        camera.clamp(cols, rows, grid_w, map_h);
+        let map = Rect::new(0.0, 0.0, grid_w, map_h);
+        let scrolled = full_w > grid_w || full_h > map_h;
+        let canvas_w = grid_w.max(MIN_CANVAS_W);
+        // The canvas has whole-pixel dimensions
+        let width = canvas_w.trunc();
+        let height = (map_h + text_h + replay_h).trunc();
+
+        Layout {
+            width,
+            height,
+            cols,
+            rows,
+            map,
+            camera,
+            minimap: (state.show_minimap && scrolled).then(|| minimap_layout(cols, rows, &camera, map)),
+            message_area: Rect::new(0.0, map_h + TEXT_AREA_GAP, canvas_w, TEXT_AREA_ROWS as f64 * CELL_H),
+            inspector: state
+                .show_inspector
+                .then(|| inspector_layout(ctx, state, grid_w, map_h, canvas_w)),
+            tutorial_overlay: state
+                .tutorial
+                .as_ref()
+                .filter(|tut| tut.overlay_visible)
+                .map(|tut| tutorial_overlay_layout(ctx, tut, canvas_w)),
+            help: state
+                .show_help_overlay
+                .then(|| help_layout(ctx, state, canvas_w)),
+            tutorial_menu: state
+                .tutorial_menu
+                .as_ref()
+                .map(|menu| tutorial_menu_layout(menu, width, height)),
+            load_menu: state
+                .load_menu
+                .as_ref()
+                .map(|menu| load_menu_layout(menu, width, height)),
+            protocol_banner: state
+                .protocol_error
+                .as_deref()
+                .map(|err| banner_layout(ctx, err, width, height)),
+            replay_bar: state
+                .replay
+                .as_ref()
+                .map(|replay| replay_bar_layout(ctx, replay, map_h + text_h, width)),
+        }
+    }
+
+    /// The topmost thing at canvas position (x, y), or None for empty
+    /// canvas such as the message area.
+    pub fn hit(&self, x: f64, y: f64) -> Option<Hit> {
+        if self.protocol_banner.as_ref().is_some_and(|b| b.panel.contains(x, y)) {
+            return Some(Hit::Overlay);
+        }
+
+        // Replay controls stay usable under a recorded modal menu
+        if let Some(bar) = self.replay_bar.as_ref().filter(|b| b.panel.contains(x, y)) {
+            if let Some(button) = bar.buttons.iter().find(|b| b.rect.contains(x, y)) {
+                return Some(Hit::Control(button.control));
+            }
+            if bar.track.contains(x, y) {
+                return Some(Hit::Control(Control::ReplaySeek));
+            }
+            return Some(Hit::Overlay);
+        }
+
+        // Menus are modal: everything outside their items is backdrop
+        if let Some(menu) = &self.load_menu {
+            return Some(menu_hit(menu, x, y).map_or(Hit::Overlay, |i| Hit::Control(Control::LoadMenuItem(i))));
+        }
+        if let Some(menu) = &self.tutorial_menu {
+            return Some(
+                menu_hit(menu, x, y).map_or(Hit::Overlay, |i| Hit::Control(Control::TutorialMenuItem(i))),
+            );
+        }
+
+        if let Some(minimap) = self.minimap.as_ref().filter(|m| m.panel.contains(x, y)) {
+            let on_map = minimap.map.contains(x, y);
+            return Some(if on_map { Hit::Control(Control::Minimap) } else { Hit::Overlay });
+        }
+
+        let panels = [
+            self.help.as_ref().map(|h| (h.panel, &h.buttons)),
+            self.tutorial_overlay.as_ref().map(|t| (t.panel, &t.buttons)),
+            self.inspector.as_ref().map(|i| (i.panel, &i.buttons)),
+        ];
+        for (panel, buttons) in panels.into_iter().flatten() {
+            if let Some(button) = buttons.iter().find(|b| b.rect.contains(x, y)) {
+                return Some(Hit::Control(button.control));
+            }
+            if panel.contains(x, y) {
+                return Some(Hit::Overlay);
+            }
+        }
+
+        if self.map.contains(x, y) {
+            let (col, row) = self.camera.map_point(x, y);
+            let col = (col.max(0.0) as usize).min(self.cols.saturating_sub(1));
+            let row = (row.max(0.0) as usize).min(self.rows.saturating_sub(1));
+            return Some(Hit::Cell { col, row });
+        }
+        None
+    }
+
+    /// The control at (x, y), if any.
+    pub fn control_at(&self, x: f64, y: f64) -> Option<Control> {
+        match self.hit(x, y) {
+            Some(Hit::Control(control)) => Some(control),
+            _ => None,
+        }
+    }
+}
+
+fn minimap_layout(cols: usize, rows: usize, camera: &Camera, view: Rect) -> MinimapLayout {
+    let scale = (MINIMAP_MAX_W / cols as f64).min(MINIMAP_MAX_H / rows as f64).min(MINIMAP_MAX_SCALE);
+    let (w, h) = (cols as f64 * scale, rows as f64 * scale);
+    let map = Rect::new(
+        view.right() - MINIMAP_MARGIN - MINIMAP_PAD - w,
+        view.bottom() - MINIMAP_MARGIN - MINIMAP_PAD - h,
+        w,
+        h,
+    );
+    let panel = Rect::new(map.x - MINIMAP_PAD, map.y - MINIMAP_PAD, w + 2.0 * MINIMAP_PAD, h + 2.0 * MINIMAP_PAD);
+    let viewport = Rect::new(
+        map.x + camera.left * scale,
+        map.y + camera.top * scale,
+        (view.w / camera.cell_w() * scale).min(w),
+        (view.h / camera.cell_h() * scale).min(h),
+    );
+    MinimapLayout { panel, map, scale, viewport }
+}
+
+fn menu_hit(menu: &MenuLayout, x: f64, y: f64) -> Option<usize> {
+    menu.items.iter().position(|item| item.contains(x, y))
+}
+
+/// Word-wrap text to fit within `max_width` pixels in the target's current
+/// font. Respects explicit newlines in the input.
+pub fn wrap_text(ctx: &impl DrawTarget, text: &str, max_width: f64) -> Vec<String> {
+    let mut lines = Vec::new();
+    for raw_line in text.split('\n') {
+        if raw_line.is_empty() {
+            lines.push(String::new());
+            continue;
+        }
+        let words: Vec<&str> = raw_line.split(' ').collect();
+        let mut current = String::from(words[0]);
+        for word in &words[1..] {
+            let test = format!("{} {}", current, word);
+            if ctx.measure_text(&test) <= max_width {
+                current = test;
+            } else {
+                lines.push(current);
+                current = String::from(*word);
+            }
+        }
+        lines.push(current);
+    }
+    lines
+}
+
+fn wrap_all(ctx: &impl DrawTarget, lines: Vec<String>, max_width: f64) -> Vec<String> {
+    lines
+        .iter()
+        .flat_map(|line| wrap_text(ctx, line, max_width))
+        .collect()
+}
+
+/// Lay out a row of text buttons in `font` starting at (x, y), separated
+/// by `gap`.
+fn button_row(
+    ctx: &mut impl DrawTarget,
+    font: &str,
+    x: f64,
+    y: f64,
+    gap: &str,
+    items: &[(Control, &str)],
+) -> Vec<Button> {
+    ctx.set_font(font);
+    let text_h = font_px(font);
+    let mut buttons = Vec::new();
+    let mut cursor = x;
+    for (control, label) in items {
+        let w = ctx.measure_text(label);
+        buttons.push(Button {
+            control: *control,
+            label: label.to_string(),
+            x: cursor,
+            y,
+            rect: Rect::new(cursor - BUTTON_PAD, y - BUTTON_PAD, w + 2.0 * BUTTON_PAD, text_h + 2.0 * BUTTON_PAD),
+        });
+        cursor += w + ctx.measure_text(gap);
+    }
+    buttons
+}
+
+fn menu_layout(
+    count: usize,
+    width_cap: f64,
+    fraction: f64,
+    item_height: f64,
+    screen_w: f64,
+    screen_h: f64,
+) -> MenuLayout {
+    let padding = 24.0;
+    let title_height = 40.0;
+    let hint_height = 30.0;
+    let menu_w = width_cap.min(screen_w * fraction);
+    let menu_h = (title_height + padding + count as f64 * item_height + hint_height + padding).min(screen_h * fraction);
+    let left = (screen_w - menu_w) / 2.0;
+    let top = (screen_h - menu_h) / 2.0;
+    let content_top = top + padding + title_height;
+    MenuLayout {
+        panel: Rect::new(left, top, menu_w, menu_h),
+        padding,
+        content_top,
+        items: (0..count)
+            .map(|i| Rect::new(left, content_top + i as f64 * item_height, menu_w, item_height))
+            .collect(),
+    }
+}
+
+fn tutorial_menu_layout(menu: &TutorialMenuMsg, screen_w: f64, screen_h: f64) -> MenuLayout {
+    menu_layout(menu.scenarios.len(), 520.0, 0.85, 48.0, screen_w, screen_h)
+}
+
+fn load_menu_layout(menu: &LoadMenuMsg, screen_w: f64, screen_h: f64) -> MenuLayout {
+    menu_layout(menu.files.len(), 500.0, 0.8, 36.0, screen_w, screen_h)
+}
+
+fn banner_layout(ctx: &mut impl DrawTarget, message: &str, screen_w: f64, screen_h: f64) -> BannerLayout {
+    let padding = 16.0;
+    let banner_w = 560.0f64.min(screen_w * 0.9);
+    let content_w = banner_w - 2.0 * padding - 4.0;
+
+    ctx.set_font(FONT_MENU_ITEM);
+    let lines = wrap_text(ctx, message, content_w);
+    let title_h = 24.0;
+    let line_h = 20.0;
+    let banner_h = padding + title_h + 8.0 + lines.len() as f64 * line_h + padding;
+    let left = (screen_w - banner_w) / 2.0;
+    let top = (screen_h - banner_h) / 2.0;
+    BannerLayout {
+        panel: Rect::new(left, top, banner_w, banner_h),
+        padding,
+        lines,
+        text_top: top + padding + title_h + 8.0,
+    }
+}
+
+/// "m:ss" for a duration in ms.
+fn clock(ms: f64) -> String {
+    let secs = (ms / 1000.0).floor().max(0.0) as u64;
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+fn replay_bar_layout(ctx: &mut impl DrawTarget, replay: &ReplayStatus, top: f64, canvas_w: f64) -> ReplayBarLayout {
+    let padding = 12.0;
+    let panel = Rect::new(0.0, top, canvas_w, REPLAY_BAR_H);
+    let text_y = top + (REPLAY_BAR_H - font_px(FONT_MENU_ITEM)) / 2.0;
+
+    let play = if replay.playing { "Pause" } else { "Play" };
+    let speed = format!("{}x", replay.speed);
+    let buttons = button_row(
+        ctx,
+        FONT_MENU_ITEM,
+        padding,
+        text_y,
+        "  ",
+        &[
+            (Control::ReplayPlayPause, play),
+            (Control::ReplayStep, "Step"),
+            (Control::ReplaySpeed, &speed),
+            (Control::ReplayExit, "Exit"),
+        ],
+    );
+
+    let time_label = format!("{} / {}", clock(replay.time_ms), clock(replay.duration_ms));
+    let label_x = canvas_w - padding - ctx.measure_text(&time_label);
+    let track_x = buttons.last().map_or(padding, |b| b.rect.right()) + padding;
+    let track = Rect::new(track_x, top + 6.0, (label_x - padding - track_x).max(0.0), REPLAY_BAR_H - 12.0);
+
+    ReplayBarLayout { panel, buttons, track, time_label, label_x, text_y }
+}
+
+fn tutorial_overlay_layout(ctx: &mut impl DrawTarget, tut: &TutorialMsg, canvas_w: f64) -> TutorialOverlayLayout {
+    let padding = 16.0;
+    let panel_w = 380.0f64.min(canvas_w * 0.50);
+    let right_margin = 12.0;
+    let top_margin = 12.0;
+    let left = canvas_w - panel_w - right_margin;
+    let top = top_margin;
+    let content_w = panel_w - 2.0 * padding - 4.0;
+
+    ctx.set_font(FONT_MENU_ITEM);
+    let lines = wrap_text(ctx, &tut.page_text, content_w);
+
+    let title_h = 24.0;
+    let line_h = 20.0;
+    let text_h = lines.len() as f64 * line_h;
+    let nav_h = 28.0;
+    let panel_h = padding + title_h + 8.0 + text_h + 12.0 + nav_h + padding;
+
+    let separator_y = top + padding + title_h;
+    let text_top = separator_y + 8.0;
+    let nav_y = text_top + text_h + 12.0;
+
+    ctx.set_font(FONT_MENU_HINT);
+    let page_label = format!("Page {}/{}  ", tut.page_index + 1, tut.page_count);
+    let nav_x = left + padding + 4.0 + ctx.measure_text(&page_label);
+    let buttons = button_row(
+        ctx,
+        FONT_MENU_HINT,
+        nav_x,
+        nav_y,
+        " ",
+        &[
+            (Control::TutorialNext, "[N]ext"),
+            (Control::TutorialBack, "[B]ack"),
+            (Control::TutorialHide, "[ESC]hide"),
+        ],
+    );
+
+    TutorialOverlayLayout {
+        panel: Rect::new(left, top, panel_w, panel_h),
+        padding,
+        separator_y,
+        lines,
+        text_top,
+        page_label,
+        nav_y,
+        buttons,
+    }
+}
+
+fn help_layout(ctx: &mut impl DrawTarget, state: &GameState, canvas_w: f64) -> HelpLayout {
+    let padding = 14.0;
+    let panel_w = 360.0f64.min(canvas_w * 0.45);
+    let left = 12.0;
+    let top = 12.0;
+    let content_w = panel_w - 2.0 * padding - 4.0;
+
+    let title = state
+        .tips
+        .as_ref()
+        .map_or("Tip", |t| t.title.as_str())
+        .to_string();
+    let tip_text = state
+        .tips
+        .as_ref()
+        .map_or("Use [h] to hide this panel.", |t| t.text.as_str());
+
+    ctx.set_font(FONT_MENU_ITEM);
+    let tip_lines = wrap_text(ctx, tip_text, content_w);
+    let selected_lines = wrap_all(ctx, compute_selected_lines(state), content_w);
+    let controls_lines = wrap_all(ctx, compute_controls_lines(state), content_w);
+
+    let title_h = 20.0;
+    let section_gap = 10.0;
+    let line_h = 18.0;
+    let tip_h = (tip_lines.len().max(1) as f64) * line_h;
+    let selected_header_h = 18.0;
+    let selected_h = selected_lines.len() as f64 * line_h;
+    let controls_h = (controls_lines.len().max(1) as f64) * line_h;
+    let hint_h = 18.0;
+
+    let separator_y = top + padding + title_h;
+    let tip_top = separator_y + 8.0;
+    let mut after_tip_y = tip_top + tip_h;
+    let mut selected_top = None;
+    let mut selected_lines_top = after_tip_y;
+    if !selected_lines.is_empty() {
+        let header = after_tip_y + section_gap;
+        selected_top = Some(header);
+        selected_lines_top = header + 6.0 + selected_header_h;
+        after_tip_y = selected_lines_top + selected_h;
+    }
+    let controls_top = after_tip_y + section_gap;
+    let hint_y = controls_top + controls_h + 10.0;
+    let panel_h = hint_y + hint_h + padding - top;
+
+    let buttons = button_row(
+        ctx,
+        FONT_MENU_HINT,
+        left + padding + 4.0,
+        hint_y,
+        "   ",
+        &[
+            (Control::HideHelp, "[h/H] hide panel"),
+            (Control::ToggleInspector, "[I] inspector"),
+        ],
+    );
+
+    HelpLayout {
+        panel: Rect::new(left, top, panel_w, panel_h),
+        padding,
+        title,
+        separator_y,
+        tip_lines,
+        tip_top,
+        selected_top,
+        selected_lines,
+        selected_lines_top,
+        controls_lines,
+        controls_top,
+        buttons,
+    }
+}
+
+fn inspector_layout(
+    ctx: &mut impl DrawTarget,
+    state: &GameState,
+    map_w: f64,
+    map_h: f64,
+    canvas_w: f64,
+) -> InspectorLayout {
+    let padding = 14.0;
+    let panel_w = 320.0f64.min(canvas_w * 0.45);
+
+    // Prefer unused space to the right of the map; otherwise overlay on the map.
+    let left = if canvas_w > map_w + panel_w + 24.0 {
+        map_w + 12.0
+    } else {
+        canvas_w - panel_w - 12.0
+    };
+    let top = 12.0;
+    let content_w = panel_w - 2.0 * padding - 4.0;
+
+    let mut lines = compute_selected_lines(state);
+    if lines.is_empty() {
+        lines.push("Click a cell to inspect it".to_string());
+        lines.push("Tip: keys target the selected cell".to_string());
+    } else {
+        // Small reminder since this is a common browser-client confusion.
+        lines.push("Targeting: keyboard commands use selection".to_string());
+    }
+
+    ctx.set_font(FONT_MENU_ITEM);
+    let mut wrapped = wrap_all(ctx, lines, content_w);
+
+    let title_h = 20.0;
+    let line_h = 18.0;
+    let body_h = (wrapped.len().max(1) as f64) * line_h;
+    let hint_h = 18.0;
+    let max_h = (map_h - 24.0).max(140.0);
+    let desired_h = padding + title_h + 10.0 + body_h + 10.0 + hint_h + padding;
+    let panel_h = desired_h.min(max_h);
+
+    let text_top = top + padding + title_h + 8.0;
+    let max_lines = ((panel_h - (text_top - top) - padding - hint_h - 10.0) / line_h)
+        .floor()
+        .max(1.0) as usize;
+    wrapped.truncate(max_lines);
+
+    let hint_y = top + panel_h - padding - hint_h;
+    let buttons = button_row(
+        ctx,
+        FONT_MENU_HINT,
+        left + padding + 4.0,
+        hint_y,
+        "",
+        &[(Control::HideInspector, "[I] hide inspector")],
+    );
+
+    InspectorLayout {
+        panel: Rect::new(left, top, panel_w, panel_h),
+        padding,
+        lines: wrapped,
+        text_top,
+        buttons,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::draw::RecordingTarget;
+    use crate::protocol::TutorialScenarioMsg;
+
+    fn state(cols: usize, rows: usize) -> GameState {
+        let mut st = GameState::new();
+        st.map_size = (cols, rows);
+        st.cells = vec![vec![None; rows]; cols];
+        st.show_help_overlay = false;
+        st.show_inspector = false;
+        st
+    }
+
+    fn layout(st: &GameState) -> Layout {
+        Layout::compute(st, &mut RecordingTarget::new())
+    }
+
+    fn scenarios(n: usize) -> TutorialMenuMsg {
+        TutorialMenuMsg {
+            scenarios: (0..n)
+                .map(|i| TutorialScenarioMsg {
+                    id: format!("s{}", i),
+                    name: format!("Scenario {}", i),
+                    description: String::new(),
+                })
+                .collect(),
+        }
+    }
+
+    fn center(rect: Rect) -> (f64, f64) {
+        (rect.x + rect.w / 2.0, rect.y + rect.h / 2.0)
+    }
+
+    #[test]
+    fn large_maps_scroll_inside_the_screen() {
+        let mut st = state(200, 100);
+        st.screen_size = Some((1000.0, 668.0));
+        st.camera.pan(-1e6, 1e6);
+        let l = layout(&st);
+        assert_eq!((l.width, l.height), (1000.0, 668.0));
+        assert_eq!(l.map, Rect::new(0.0, 0.0, 1000.0, 600.0));
+        // The camera is kept on the map: the bottom-left 30 rows are in view
+        assert_eq!((l.camera.left, l.camera.top), (0.0, 70.0));
+        assert_eq!(l.hit(14.0 * 3.0 + 1.0, 1.0), Some(Hit::Cell { col: 3, row: 70 }));
+
+        // Zoomed in around the top-left, a cell covers twice the pixels
+        st.camera = l.camera;
+        st.camera.zoom_at(2, 0.0, 0.0);
+        let l = layout(&st);
+        assert_eq!(l.hit(28.0 * 3.0 + 1.0, 40.0 * 2.0 + 1.0), Some(Hit::Cell { col: 3, row: 72 }));
+    }
+
+    #[test]
+    fn minimap_only_when_the_map_scrolls() {
+        let mut st = state(200, 100);
+        assert!(layout(&st).minimap.is_none());
+
+        st.screen_size = Some((1000.0, 668.0));
+        st.camera.left = 50.0;
+        let l = layout(&st);
+        let m = l.minimap.as_ref().unwrap();
+        // 200x100 cells fit 200x140 at one pixel a cell, in the bottom-right
+        assert_eq!(m.scale, 1.0);
+        assert_eq!(m.map, Rect::new(1000.0 - 14.0 - 200.0, 600.0 - 14.0 - 100.0, 200.0, 100.0));
+        assert_eq!(m.viewport, Rect::new(m.map.x + 50.0, m.map.y, 1000.0 / 14.0, 30.0));
+        assert_eq!(l.hit(m.map.x + 1.0, m.map.y + 1.0), Some(Hit::Control(Control::Minimap)));
+        assert_eq!(l.hit(m.panel.x + 1.0, m.panel.y + 1.0), Some(Hit::Overlay));
+
+        st.show_minimap = false;
+        assert!(layout(&st).minimap.is_none());
+    }
+
+    #[test]
+    fn map_hits_cells_and_message_area_is_empty() {
+        let l = layout(&state(10, 8));
+        assert_eq!((l.width, l.height), (700.0, 8.0 * 20.0 + 8.0 + 60.0));
+        assert_eq!(l.hit(14.0 * 3.0 + 1.0, 20.0 * 2.0 + 1.0), Some(Hit::Cell { col: 3, row: 2 }));
+        // Right of the grid and below it
+        assert_eq!(l.hit(14.0 * 10.0 + 1.0, 5.0), None);
+        assert_eq!(l.hit(5.0, 20.0 * 8.0 + 20.0), None);
+    }
+
+    #[test]
+    fn tutorial_menu_items_are_centred_and_modal() {
+        let mut st = state(40, 25);
+        st.tutorial_menu = Some(scenarios(3));
+        let l = layout(&st);
+        let menu = l.tutorial_menu.as_ref().unwrap();
+        // menu_h = 40 + 24 + 144 + 30 + 24 = 262, centred
+        assert_eq!(menu.panel.w, 520.0);
+        assert_eq!(menu.panel.y, (l.height - 262.0) / 2.0);
+        assert_eq!(menu.content_top, menu.panel.y + 24.0 + 40.0);
+
+        let (x, _) = center(menu.panel);
+        assert_eq!(l.hit(x, menu.content_top + 1.0), Some(Hit::Control(Control::TutorialMenuItem(0))));
+        assert_eq!(l.hit(x, menu.content_top + 100.0), Some(Hit::Control(Control::TutorialMenuItem(2))));
+        assert_eq!(l.hit(x, menu.content_top + 150.0), Some(Hit::Overlay));
+        assert_eq!(l.hit(menu.panel.x - 1.0, menu.content_top + 1.0), Some(Hit::Overlay));
+        // The map underneath is unreachable while the menu is open
+        assert_eq!(l.hit(1.0, 1.0), Some(Hit::Overlay));
+    }
+
+    #[test]
+    fn load_menu_items_are_clickable() {
+        let mut st = state(40, 25);
+        st.load_menu = Some(LoadMenuMsg {
+            files: vec!["a.edn".to_string(), "b.edn".to_string()],
+            hovered: None,
+        });
+        let l = layout(&st);
+        let menu = l.load_menu.as_ref().unwrap();
+        assert_eq!(menu.items.len(), 2);
+        assert_eq!(menu.items[1].y - menu.items[0].y, 36.0);
+        assert_eq!(l.control_at(center(menu.items[1]).0, center(menu.items[1]).1), Some(Control::LoadMenuItem(1)));
+    }
+
+    #[test]
+    fn panels_swallow_clicks_and_expose_buttons() {
+        let mut st = state(40, 25);
+        st.show_help_overlay = true;
+        st.show_inspector = true;
+        let l = layout(&st);
+
+        let help = l.help.as_ref().unwrap();
+        assert_eq!(help.buttons.len(), 2);
+        let (x, y) = center(help.buttons[0].rect);
+        assert_eq!(l.control_at(x, y), Some(Control::HideHelp));
+        let (x, y) = center(help.buttons[1].rect);
+        assert_eq!(l.control_at(x, y), Some(Control::ToggleInspector));
+        assert_eq!(l.hit(help.panel.x + 20.0, help.panel.y + 20.0), Some(Hit::Overlay));
+
+        let inspector = l.inspector.as_ref().unwrap();
+        let (x, y) = center(inspector.buttons[0].rect);
+        assert_eq!(l.control_at(x, y), Some(Control::HideInspector));
+        assert_eq!(l.hit(inspector.panel.x + 20.0, inspector.panel.y + 20.0), Some(Hit::Overlay));
+    }
+
+    #[test]
+    fn help_buttons_line_up_with_their_labels() {
+        let mut st = state(40, 25);
+        st.show_help_overlay = true;
+        let mut target = RecordingTarget::new();
+        let l = Layout::compute(&st, &mut target);
+        let help = l.help.unwrap();
+        let (hide, inspector) = (&help.buttons[0], &help.buttons[1]);
+        target.set_font(FONT_MENU_HINT);
+        // The two labels read as the original single hint line
+        assert_eq!(inspector.x - hide.x, target.measure_text("[h/H] hide panel   "));
+        assert_eq!(hide.rect.h, font_px(FONT_MENU_HINT) + 2.0 * BUTTON_PAD);
+    }
+
+    #[test]
+    fn tutorial_overlay_has_navigation_buttons() {
+        let mut st = state(40, 25);
+        st.tutorial = Some(TutorialMsg {
+            page_text: "Move your army".to_string(),
+            page_index: 0,
+            page_count: 3,
+            scenario_name: "Basics".to_string(),
+            overlay_visible: true,
+        });
+        let l = layout(&st);
+        let tut = l.tutorial_overlay.as_ref().unwrap();
+        assert_eq!(tut.page_label, "Page 1/3  ");
+        let controls: Vec<Control> = tut.buttons.iter().map(|b| b.control).collect();
+        assert_eq!(controls, vec![Control::TutorialNext, Control::TutorialBack, Control::TutorialHide]);
+        for b in &tut.buttons {
+            assert_eq!(l.control_at(center(b.rect).0, center(b.rect).1), Some(b.control));
+        }
+
+        st.tutorial.as_mut().unwrap().overlay_visible = false;
+        assert!(layout(&st).tutorial_overlay.is_none());
+    }
+
+    #[test]
+    fn protocol_banner_without_map() {
+        let mut st = GameState::new();
+        st.protocol_error = Some("Server speaks protocol 9".to_string());
+        let l = layout(&st);
+        assert_eq!((l.width, l.height), (700.0, 240.0));
+        let banner = l.protocol_banner.as_ref().unwrap();
+        assert_eq!(l.hit(center(banner.panel).0, center(banner.panel).1), Some(Hit::Overlay));
+        assert_eq!(l.hit(1.0, 1.0), None);
+    }
+
+    #[test]
+    fn wrap_text_breaks_on_words_and_newlines() {
+        let mut t = RecordingTarget::new();
+        t.set_font("10px monospace");
+        // 6px per glyph: 5 glyphs fit in 30px
+        assert_eq!(wrap_text(&t, "ab cd ef", 30.0), vec!["ab cd", "ef"]);
+        assert_eq!(wrap_text(&t, "ab\n\ncd", 30.0), vec!["ab", "", "cd"]);
+    }
+
+    #[test]
+    fn replay_bar_sits_below_the_messages_and_beats_modal_menus() {
+        let mut st = state(40, 25);
+        let plain_h = layout(&st).height;
+        st.replay = Some(ReplayStatus { time_ms: 65_000.0, duration_ms: 200_000.0, speed: 2.0, ..Default::default() });
+        st.load_menu = Some(LoadMenuMsg { files: vec!["a.edn".to_string()], hovered: None });
+        let l = layout(&st);
+        let bar = l.replay_bar.as_ref().unwrap();
+        assert_eq!(l.height, plain_h + REPLAY_BAR_H);
+        assert_eq!(bar.panel.y, plain_h);
+        assert_eq!(bar.time_label, "1:05 / 3:20");
+        let labels: Vec<&str> = bar.buttons.iter().map(|b| b.label.as_str()).collect();
+        assert_eq!(labels, ["Play", "Step", "2x", "Exit"]);
+
+        let (x, y) = center(bar.buttons[2].rect);
+        assert_eq!(l.control_at(x, y), Some(Control::ReplaySpeed));
+        let (x, y) = center(bar.track);
+        assert_eq!(l.control_at(x, y), Some(Control::ReplaySeek));
+        assert_eq!(bar.time_at(x, 200_000.0), 100_000.0);
+        assert_eq!(bar.time_at(bar.track.x - 50.0, 200_000.0), 0.0);
+        assert_eq!(bar.x_at(200_000.0, 200_000.0), bar.track.right());
+        // The recorded load menu still owns the rest of the canvas
+        assert_eq!(l.hit(1.0, 1.0), Some(Hit::Overlay));
+    }
+}
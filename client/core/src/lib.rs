@@ -0,0 +1,19 @@
+//! Platform-independent core of the Empire client: wire protocol, client
+//! game state, and the input/text logic shared by every front end. Nothing
+//! here depends on the browser.
+
+pub mod camera;
+pub mod draw;
+pub mod effects;
+pub mod fuel;
+pub mod hud;
+pub mod icons;
+pub mod input;
+pub mod layout;
+pub mod motion;
+pub mod protocol;
+pub mod renderer;
+pub mod replay;
+pub mod state;
+pub mod theme;
+pub mod units;
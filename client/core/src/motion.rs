@@ -0,0 +1,207 @@
+//! Unit movement between server snapshots. The protocol carries no unit
+//! ids, so a unit that left its cell is matched to the nearest unit of the
+//! same type and owner that appeared within a round's move of it, and is
+//! slid from the old cell to the new one instead of jumping.
+
+use crate::protocol::{Owner, UnitKind};
+use crate::state::GameState;
+use crate::units::speed;
+
+/// How long a unit takes to slide to its new cell, in ms.
+pub const MOVE_ANIMATION_MS: f64 = 300.0;
+
+/// A unit that moved between the last two snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub from: (usize, usize),
+    pub to: (usize, usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Placed {
+    col: usize,
+    row: usize,
+    kind: UnitKind,
+    owner: Owner,
+}
+
+impl Placed {
+    fn same_unit(&self, other: &Placed) -> bool {
+        self.kind == other.kind && self.owner == other.owner
+    }
+}
+
+/// Moves found between the last two snapshots, and when they started.
+#[derive(Debug, Default)]
+pub struct Motion {
+    seq: Option<u64>,
+    units: Vec<Placed>,
+    moves: Vec<Move>,
+    started: f64,
+}
+
+impl Motion {
+    /// Compare `state` with the last snapshot seen; units that moved start
+    /// sliding at `now`. A snapshot that isn't `continuous` with the last
+    /// one only becomes the new starting point.
+    pub fn update(&mut self, state: &GameState, continuous: bool, now: f64) {
+        if !continuous {
+            self.seq = state.seq;
+            self.units = placed(state);
+            self.moves.clear();
+            return;
+        }
+        if state.seq.is_some() && state.seq == self.seq {
+            return;
+        }
+        self.seq = state.seq;
+        let units = placed(state);
+        if units == self.units {
+            return;
+        }
+        self.moves = match_moves(&self.units, &units);
+        self.started = now;
+        self.units = units;
+    }
+
+    /// Eased share of the slide done at `now`, or None once it's over.
+    pub fn progress(&self, now: f64, duration: f64) -> Option<f64> {
+        if self.moves.is_empty() || duration <= 0.0 {
+            return None;
+        }
+        let t = ((now - self.started) / duration).max(0.0);
+        (t < 1.0).then_some(t * t * (3.0 - 2.0 * t))
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+}
+
+fn placed(state: &GameState) -> Vec<Placed> {
+    let mut units = Vec::new();
+    for (col, column) in state.cells.iter().enumerate() {
+        for (row, cell) in column.iter().enumerate() {
+            if let Some(unit) = cell.as_ref().and_then(|c| c.u.as_ref()) {
+                units.push(Placed { col, row, kind: unit.t.clone(), owner: unit.o.clone() });
+            }
+        }
+    }
+    units
+}
+
+fn distance(a: &Placed, b: &Placed) -> usize {
+    a.col.abs_diff(b.col).max(a.row.abs_diff(b.row))
+}
+
+/// Pair units that left a cell with units that arrived in one, closest
+/// pairs first. Units still standing on their cell don't move.
+fn match_moves(before: &[Placed], after: &[Placed]) -> Vec<Move> {
+    let stayed = |u: &Placed, others: &[Placed]| {
+        others.iter().any(|o| o.col == u.col && o.row == u.row && o.same_unit(u))
+    };
+    let left: Vec<&Placed> = before.iter().filter(|u| !stayed(u, after)).collect();
+    let arrived: Vec<&Placed> = after.iter().filter(|u| !stayed(u, before)).collect();
+
+    let mut pairs = Vec::new();
+    for (i, from) in left.iter().enumerate() {
+        for (j, to) in arrived.iter().enumerate() {
+            let d = distance(from, to);
+            if from.same_unit(to) && d <= speed(&to.kind) as usize {
+                pairs.push((d, i, j));
+            }
+        }
+    }
+    pairs.sort_by_key(|&(d, ..)| d);
+
+    let mut left_used = vec![false; left.len()];
+    let mut arrived_used = vec![false; arrived.len()];
+    let mut moves = Vec::new();
+    for (_, i, j) in pairs {
+        if !left_used[i] && !arrived_used[j] {
+            left_used[i] = true;
+            arrived_used[j] = true;
+            moves.push(Move { from: (left[i].col, left[i].row), to: (arrived[j].col, arrived[j].row) });
+        }
+    }
+    moves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit(col: usize, row: usize, kind: &str, owner: &str) -> Placed {
+        Placed { col, row, kind: UnitKind::from(kind), owner: Owner::from(owner) }
+    }
+
+    #[test]
+    fn moved_units_pair_with_the_nearest_arrival() {
+        let before = [unit(0, 0, "army", "player"), unit(5, 5, "fighter", "player"), unit(9, 9, "army", "player")];
+        let after = [
+            unit(1, 0, "army", "player"),
+            unit(5, 5, "fighter", "player"),
+            unit(9, 9, "army", "player"),
+            unit(0, 1, "army", "player"),
+        ];
+        // Both new armies are one step away; the first pair found wins
+        assert_eq!(match_moves(&before, &after), [Move { from: (0, 0), to: (1, 0) }]);
+
+        let after = [unit(5, 13, "fighter", "player"), unit(3, 0, "army", "player")];
+        assert_eq!(match_moves(&before, &after), [Move { from: (5, 5), to: (5, 13) }]);
+    }
+
+    #[test]
+    fn units_dont_pair_across_types_owners_or_past_their_speed() {
+        let before = [unit(0, 0, "destroyer", "player"), unit(4, 4, "army", "computer")];
+        let after = [unit(1, 0, "battleship", "player"), unit(0, 3, "destroyer", "player"), unit(4, 5, "army", "player")];
+        assert!(match_moves(&before, &after).is_empty());
+    }
+
+    #[test]
+    fn snapshots_that_dont_follow_on_move_nothing() {
+        let frame = |seq: u64, view: &str, army_row: usize| {
+            let mut cells = vec![serde_json::json!({"t": "land"}); 3];
+            cells[army_row] = serde_json::json!({"t": "land", "u": {"t": "army", "o": "player", "m": "awake"}});
+            serde_json::json!({
+                "type": "state", "seq": seq, "map_size": [1, 3], "cells": [cells], "round": 1,
+                "paused": false, "pause_requested": false, "waiting_for_input": false,
+                "attention_coords": [], "attention_message": "", "turn_message": "",
+                "error_message": "", "error_until": 0, "hover_message": "",
+                "selected_cell": null, "production_status": "", "destination": null,
+                "map_to_display": view, "debug_message": "", "load_menu": null,
+                "tutorial": null, "tutorial_menu": null, "tips": null
+            })
+            .to_string()
+        };
+        let mut state = GameState::new();
+        let mut motion = Motion::default();
+        let mut feed = |text: String, motion: &mut Motion| {
+            let epoch = state.snapshot_epoch;
+            state.apply_frame(&text).unwrap();
+            motion.update(&state, state.snapshot_epoch == epoch, 0.0);
+            motion.moves().to_vec()
+        };
+        feed(frame(1, "player-map", 0), &mut motion);
+        assert_eq!(feed(frame(2, "player-map", 1), &mut motion), [Move { from: (0, 0), to: (0, 1) }]);
+        // Toggling to the computer's map, or seeking back, moves nothing
+        assert!(feed(frame(3, "computer-map", 2), &mut motion).is_empty());
+        assert!(feed(frame(1, "computer-map", 1), &mut motion).is_empty());
+        assert_eq!(feed(frame(2, "computer-map", 2), &mut motion), [Move { from: (0, 1), to: (0, 2) }]);
+    }
+
+    #[test]
+    fn slides_ease_in_and_end() {
+        let mut motion = Motion {
+            moves: vec![Move { from: (0, 0), to: (1, 0) }],
+            started: 1000.0,
+            ..Motion::default()
+        };
+        assert_eq!(motion.progress(1000.0, 300.0), Some(0.0));
+        assert_eq!(motion.progress(1150.0, 300.0), Some(0.5));
+        assert_eq!(motion.progress(1300.0, 300.0), None);
+        assert_eq!(motion.progress(1150.0, 0.0), None);
+        motion.moves.clear();
+        assert_eq!(motion.progress(1150.0, 300.0), None);
+    }
+}
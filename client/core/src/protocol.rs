@@ -0,0 +1,529 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Declares an enum for a keyword the server sends as a plain string.
+/// Unrecognised values land in `Unknown` so a newer server never breaks
+/// decoding of the whole frame.
+macro_rules! wire_enum {
+    ($(#[$meta:meta])* $name:ident { $($variant:ident => $wire:literal),+ $(,)? }) => {
+        $(#[$meta])*
+        #[derive(Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+        #[serde(from = "String")]
+        pub enum $name {
+            $($variant,)+
+            Unknown(String),
+        }
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $($name::$variant => $wire,)+
+                    $name::Unknown(s) => s,
+                }
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(s: String) -> Self {
+                match s.as_str() {
+                    $($wire => $name::$variant,)+
+                    _ => $name::Unknown(s),
+                }
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(s: &str) -> Self {
+                $name::from(s.to_string())
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+    };
+}
+
+wire_enum! {
+    /// Cell terrain (`CellMsg.t`).
+    Terrain {
+        Land => "land",
+        Sea => "sea",
+        City => "city",
+        Unexplored => "unexplored",
+    }
+}
+
+wire_enum! {
+    /// City ownership (`CellMsg.cs`).
+    CityStatus {
+        Player => "player",
+        Computer => "computer",
+        Free => "free",
+    }
+}
+
+wire_enum! {
+    /// Unit type (`UnitMsg.t`, `ProductionMsg.item`).
+    UnitKind {
+        Army => "army",
+        Fighter => "fighter",
+        Transport => "transport",
+        Carrier => "carrier",
+        PatrolBoat => "patrol-boat",
+        Destroyer => "destroyer",
+        Submarine => "submarine",
+        Battleship => "battleship",
+        Satellite => "satellite",
+    }
+}
+
+wire_enum! {
+    /// Unit owner (`UnitMsg.o`).
+    Owner {
+        Player => "player",
+        Computer => "computer",
+    }
+}
+
+wire_enum! {
+    /// Unit mode (`UnitMsg.m`).
+    UnitMode {
+        Awake => "awake",
+        Sentry => "sentry",
+        Explore => "explore",
+        CoastlineFollow => "coastline-follow",
+        CoastWalk => "coast-walk",
+        Moving => "moving",
+    }
+}
+
+wire_enum! {
+    /// Computer transport mission (`UnitMsg.transport-mission`).
+    TransportMission {
+        Idle => "idle",
+        Loading => "loading",
+        Unloading => "unloading",
+    }
+}
+
+wire_enum! {
+    /// Optional protocol capability, negotiated in `hello`/`welcome`.
+    Feature {
+        Patches => "patches",
+        BinaryFrames => "binary_frames",
+        Overlays => "overlays",
+    }
+}
+
+/// Wire protocol version spoken by this client.
+pub const PROTOCOL_VERSION: u32 = 1;
+/// Oldest server protocol version this client can talk to.
+pub const MIN_SERVER_PROTOCOL_VERSION: u32 = 1;
+/// Features this client implements and announces in `hello`.
+pub const CLIENT_FEATURES: [Feature; 2] = [Feature::Patches, Feature::Overlays];
+
+/// Messages sent to the server; mirrors `empire.server/handle-client-message!`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// First message on every connection.
+    Hello {
+        protocol_version: u32,
+        features: Vec<Feature>,
+    },
+    Key {
+        key: String,
+        shift: bool,
+        mouse_x: i32,
+        mouse_y: i32,
+    },
+    KeyUp,
+    Click {
+        col: i32,
+        row: i32,
+        button: MouseButton,
+    },
+    Hover {
+        col: i32,
+        row: i32,
+    },
+    TutorialSelect {
+        id: String,
+    },
+    /// Load a save picked from the load menu.
+    LoadSelect {
+        file: String,
+    },
+    /// Ask for a full `state` snapshot after a patch sequence gap.
+    Resync,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MouseButton {
+    Left,
+    Right,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type")]
+pub enum ServerMessage {
+    #[serde(rename = "state")]
+    State(GameStateMsg),
+    #[serde(rename = "patch")]
+    Patch(PatchMsg),
+    #[serde(rename = "welcome")]
+    Welcome(WelcomeMsg),
+    /// Any message type this client does not know yet.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Reply to `hello`. Its shape must stay stable across protocol versions
+/// so that any client can at least tell it is out of date.
+#[derive(Deserialize, Debug, Clone)]
+pub struct WelcomeMsg {
+    pub protocol_version: u32,
+    pub min_protocol_version: u32,
+    pub compatible: bool,
+    #[serde(default)]
+    pub features: Vec<Feature>,
+}
+
+impl WelcomeMsg {
+    /// Describes why this client cannot talk to the server, if it can't.
+    pub fn incompatibility(&self) -> Option<String> {
+        if !self.compatible || PROTOCOL_VERSION < self.min_protocol_version {
+            Some(format!(
+                "Client protocol v{} is too old for this server (needs v{}+). Rebuild the client (wasm-pack build) and reload.",
+                PROTOCOL_VERSION, self.min_protocol_version
+            ))
+        } else if self.protocol_version < MIN_SERVER_PROTOCOL_VERSION {
+            Some(format!(
+                "Server protocol v{} is too old for this client (needs v{}+). Update and restart the server.",
+                self.protocol_version, MIN_SERVER_PROTOCOL_VERSION
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GameStateMsg {
+    /// Broadcast sequence number; patches continue from it.
+    #[serde(default)]
+    pub seq: Option<u64>,
+    pub map_size: (usize, usize),
+    pub cells: Vec<Vec<Option<CellMsg>>>,
+    pub round: u32,
+    pub paused: bool,
+    pub pause_requested: bool,
+    pub waiting_for_input: bool,
+    pub attention_coords: Vec<Vec<usize>>,
+    pub attention_message: String,
+    pub turn_message: String,
+    pub error_message: String,
+    pub error_until: f64,
+    pub hover_message: String,
+    pub selected_cell: Option<Vec<usize>>,
+    pub production_status: String,
+    pub destination: Option<Vec<usize>>,
+    pub map_to_display: String,
+    pub debug_message: String,
+    pub load_menu: Option<LoadMenuMsg>,
+    pub tutorial: Option<TutorialMsg>,
+    pub tutorial_menu: Option<TutorialMenuMsg>,
+    pub tips: Option<TipsMsg>,
+}
+
+/// Changes since the previous broadcast. Scalar fields are present only
+/// when they changed; nullable ones use `Some(None)` for an explicit null.
+#[derive(Deserialize, Debug, Default)]
+pub struct PatchMsg {
+    pub seq: u64,
+    #[serde(default)]
+    pub cells: Vec<(usize, usize, Option<CellMsg>)>,
+    pub round: Option<u32>,
+    pub paused: Option<bool>,
+    pub pause_requested: Option<bool>,
+    pub waiting_for_input: Option<bool>,
+    pub attention_coords: Option<Vec<Vec<usize>>>,
+    pub attention_message: Option<String>,
+    pub turn_message: Option<String>,
+    pub error_message: Option<String>,
+    pub error_until: Option<f64>,
+    pub hover_message: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub selected_cell: Option<Option<Vec<usize>>>,
+    pub production_status: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub destination: Option<Option<Vec<usize>>>,
+    pub map_to_display: Option<String>,
+    pub debug_message: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub load_menu: Option<Option<LoadMenuMsg>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub tutorial: Option<Option<TutorialMsg>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub tutorial_menu: Option<Option<TutorialMenuMsg>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub tips: Option<Option<TipsMsg>>,
+}
+
+/// Distinguishes a field that is present but null from one that is absent.
+fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TipsMsg {
+    pub id: String,
+    pub title: String,
+    pub text: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CellMsg {
+    pub t: Terrain,
+    pub cs: Option<CityStatus>,
+    pub u: Option<UnitMsg>,
+    pub wp: Option<bool>,
+    pub fc: Option<u8>,
+    pub ac: Option<u8>,
+    pub af: Option<u8>,
+    pub aa: Option<u8>,
+    pub cid: Option<u32>,
+    pub prod: Option<ProductionMsg>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct UnitMsg {
+    pub t: UnitKind,
+    pub o: Owner,
+    pub m: UnitMode,
+    pub h: Option<u32>,
+    pub fuel: Option<u32>,
+    #[serde(rename = "marching-orders")]
+    pub marching_orders: Option<Orders>,
+    #[serde(rename = "flight-path")]
+    pub flight_path: Option<Orders>,
+    #[serde(rename = "transport-mission")]
+    pub transport_mission: Option<TransportMission>,
+}
+
+/// Where a unit has been sent (`UnitMsg.marching-orders`,
+/// `UnitMsg.flight-path`): a destination cell as `[col, row]`, or
+/// `"lookaround"` for an army told to look around where it stands. Any
+/// other shape lands in `Unknown` rather than failing the frame.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(from = "serde_json::Value")]
+pub enum Orders {
+    Target(usize, usize),
+    Lookaround,
+    Unknown(serde_json::Value),
+}
+
+impl Orders {
+    /// The destination cell, if the orders name one.
+    pub fn target(&self) -> Option<(usize, usize)> {
+        match *self {
+            Orders::Target(col, row) => Some((col, row)),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Value> for Orders {
+    fn from(value: serde_json::Value) -> Self {
+        let cell = |v: &serde_json::Value| v.as_u64().map(|n| n as usize);
+        match value.as_array().map(Vec::as_slice) {
+            Some([col, row]) => {
+                if let (Some(col), Some(row)) = (cell(col), cell(row)) {
+                    return Orders::Target(col, row);
+                }
+            }
+            _ if value == "lookaround" => return Orders::Lookaround,
+            _ => {}
+        }
+        Orders::Unknown(value)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ProductionMsg {
+    pub item: UnitKind,
+    pub remaining: u32,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LoadMenuMsg {
+    pub files: Vec<String>,
+    pub hovered: Option<usize>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TutorialMsg {
+    pub page_text: String,
+    pub page_index: u32,
+    pub page_count: u32,
+    pub scenario_name: String,
+    pub overlay_visible: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TutorialMenuMsg {
+    pub scenarios: Vec<TutorialScenarioMsg>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TutorialScenarioMsg {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(msg: ClientMessage, expected: &str) {
+        let json = serde_json::to_string(&msg).unwrap();
+        assert_eq!(json, expected);
+        let back: ClientMessage = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, msg);
+    }
+
+    #[test]
+    fn hello_round_trip() {
+        round_trip(
+            ClientMessage::Hello { protocol_version: 1, features: CLIENT_FEATURES.to_vec() },
+            r#"{"type":"hello","protocol_version":1,"features":["patches","overlays"]}"#,
+        );
+    }
+
+    #[test]
+    fn welcome_compatibility() {
+        let decode = |json: &str| match serde_json::from_str(json).unwrap() {
+            ServerMessage::Welcome(w) => w,
+            other => panic!("expected welcome, got {:?}", other),
+        };
+        let ok = decode(r#"{"type":"welcome","protocol_version":1,"min_protocol_version":1,"compatible":true,"features":["patches","telepathy"]}"#);
+        assert!(ok.incompatibility().is_none());
+        assert_eq!(ok.features, vec![Feature::Patches, Feature::Unknown("telepathy".to_string())]);
+
+        let too_old = decode(r#"{"type":"welcome","protocol_version":3,"min_protocol_version":2,"compatible":false}"#);
+        assert!(too_old.incompatibility().unwrap().contains("Client protocol v1"));
+
+        let old_server = decode(r#"{"type":"welcome","protocol_version":0,"min_protocol_version":0,"compatible":true}"#);
+        assert!(old_server.incompatibility().unwrap().contains("Server protocol v0"));
+    }
+
+    #[test]
+    fn key_round_trip() {
+        round_trip(
+            ClientMessage::Key { key: "Q".to_string(), shift: true, mouse_x: 3, mouse_y: 7 },
+            r#"{"type":"key","key":"Q","shift":true,"mouse_x":3,"mouse_y":7}"#,
+        );
+    }
+
+    #[test]
+    fn key_up_round_trip() {
+        round_trip(ClientMessage::KeyUp, r#"{"type":"key_up"}"#);
+    }
+
+    #[test]
+    fn click_round_trip() {
+        round_trip(
+            ClientMessage::Click { col: 12, row: 4, button: MouseButton::Right },
+            r#"{"type":"click","col":12,"row":4,"button":"right"}"#,
+        );
+    }
+
+    #[test]
+    fn hover_round_trip() {
+        round_trip(
+            ClientMessage::Hover { col: -1, row: 0 },
+            r#"{"type":"hover","col":-1,"row":0}"#,
+        );
+    }
+
+    #[test]
+    fn tutorial_select_round_trip() {
+        round_trip(
+            ClientMessage::TutorialSelect { id: "basics".to_string() },
+            r#"{"type":"tutorial_select","id":"basics"}"#,
+        );
+    }
+
+    #[test]
+    fn load_select_round_trip() {
+        round_trip(
+            ClientMessage::LoadSelect { file: "autosave.edn".to_string() },
+            r#"{"type":"load_select","file":"autosave.edn"}"#,
+        );
+    }
+
+    #[test]
+    fn resync_round_trip() {
+        round_trip(ClientMessage::Resync, r#"{"type":"resync"}"#);
+    }
+
+    #[test]
+    fn patch_distinguishes_null_from_absent() {
+        let json = r#"{"type":"patch","seq":5,"cells":[[1,2,{"t":"sea"}],[0,0,null]],"load_menu":null,"round":9}"#;
+        let ServerMessage::Patch(p) = serde_json::from_str(json).unwrap() else {
+            panic!("expected patch");
+        };
+        assert_eq!(p.seq, 5);
+        assert_eq!(p.cells.len(), 2);
+        assert_eq!(p.cells[0].2.as_ref().map(|c| c.t.clone()), Some(Terrain::Sea));
+        assert!(p.cells[1].2.is_none());
+        assert_eq!(p.round, Some(9));
+        assert!(matches!(p.load_menu, Some(None)));
+        assert!(p.tutorial.is_none());
+    }
+
+    #[test]
+    fn unit_orders_decode_to_targets() {
+        let json = r#"{"t":"fighter","o":"player","m":"moving","flight-path":[12,4],"marching-orders":"lookaround"}"#;
+        let unit: UnitMsg = serde_json::from_str(json).unwrap();
+        assert_eq!(unit.flight_path, Some(Orders::Target(12, 4)));
+        assert_eq!(unit.marching_orders, Some(Orders::Lookaround));
+        assert_eq!(unit.flight_path.and_then(|o| o.target()), Some((12, 4)));
+
+        // Null is absent; an odd shape is kept but has no target
+        let json = r#"{"t":"army","o":"player","m":"moving","flight-path":null,"marching-orders":[-1,"x"]}"#;
+        let unit: UnitMsg = serde_json::from_str(json).unwrap();
+        assert!(unit.flight_path.is_none());
+        assert!(matches!(unit.marching_orders, Some(Orders::Unknown(_))));
+        assert_eq!(unit.marching_orders.unwrap().target(), None);
+    }
+
+    #[test]
+    fn unknown_message_type_decodes_to_unknown() {
+        let msg: ServerMessage = serde_json::from_str(r#"{"type":"chat","text":"hi"}"#).unwrap();
+        assert!(matches!(msg, ServerMessage::Unknown));
+    }
+
+    #[test]
+    fn tutorial_select_escapes_id() {
+        let msg = ClientMessage::TutorialSelect { id: "a\"b\\c".to_string() };
+        let json = serde_json::to_string(&msg).unwrap();
+        assert_eq!(json, r#"{"type":"tutorial_select","id":"a\"b\\c"}"#);
+        let back: ClientMessage = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, msg);
+    }
+}
@@ -0,0 +1,1758 @@
+use std::ops::Range;
+
+use crate::camera::Camera;
+use crate::draw::DrawTarget;
+use crate::effects::{EffectKind, Effects};
+use crate::fuel::FuelRange;
+use crate::icons::{draw_unit_icon, UnitStyle};
+use crate::layout::{
+    BannerLayout, Button, Control, HelpLayout, InspectorLayout, Layout, MenuLayout, MinimapLayout, Rect,
+    ReplayBarLayout,
+    TutorialOverlayLayout,
+};
+use crate::motion::{Motion, Move};
+use crate::protocol::{CellMsg, CityStatus, Owner, Terrain, TransportMission, UnitKind, UnitMode};
+use crate::replay::ReplayStatus;
+use crate::state::GameState;
+use crate::theme::{Color, Theme};
+use crate::units::{damage, item_cost, unit_char};
+
+// --- Cell dimensions (14x20 for better readability) ---
+pub const CELL_W: f64 = 14.0;
+pub const CELL_H: f64 = 20.0;
+
+// Message area layout
+pub(crate) const TEXT_AREA_ROWS: u32 = 3;
+pub(crate) const TEXT_AREA_GAP: f64 = 8.0;
+const MSG_LEFT_PADDING: f64 = 12.0;
+const MSG_LINE_1_Y: f64 = 14.0;
+const MSG_LINE_2_Y: f64 = 32.0;
+const MSG_LINE_3_Y: f64 = 50.0;
+const MSG_SEPARATOR_OFFSET: f64 = 4.0;
+
+// Display area width fractions
+const GAME_INFO_WIDTH_FRACTION: f64 = 0.375;
+const DEBUG_WIDTH_FRACTION: f64 = 0.25;
+
+// Minimum canvas width so UI elements (tutorial overlay, message area) have room
+pub(crate) const MIN_CANVAS_W: f64 = 700.0;
+
+// Cell character offsets within cells
+const CELL_CHAR_X_OFFSET: f64 = 3.0;
+const CELL_CHAR_Y_OFFSET: f64 = 15.0;
+
+/// How far a damaged unit's glyph is blended towards the theme's error colour.
+const DAMAGE_TINT: f64 = 0.5;
+
+// --- Font strings ---
+// Unit and production glyphs at the default zoom
+const CELL_FONT_PX: f64 = 14.0;
+const BADGE_FONT_PX: f64 = 9.0;
+/// Below this zoom cells are too small for cargo badges.
+const BADGE_MIN_ZOOM: f64 = 1.0;
+const FONT_MSG: &str = "500 15px 'JetBrains Mono', 'Fira Code', 'Courier New', monospace";
+const FONT_MENU_TITLE: &str = "700 18px 'JetBrains Mono', 'Fira Code', 'Courier New', monospace";
+pub(crate) const FONT_MENU_ITEM: &str = "400 15px 'JetBrains Mono', 'Fira Code', 'Courier New', monospace";
+pub(crate) const FONT_MENU_HINT: &str = "400 12px 'JetBrains Mono', 'Fira Code', 'Courier New', monospace";
+
+/// Terrain kept on an offscreen layer, so a frame only repaints the cells
+/// whose terrain changed.
+struct TerrainLayer<T> {
+    target: T,
+    /// Layer pixels per cell.
+    cell_w: f64,
+    cell_h: f64,
+    /// Fog texture and depth shading, or flat colours for the minimap.
+    shaded: bool,
+    /// Colour each cell was last painted with; `None` for fog.
+    painted: Vec<Vec<Option<Color>>>,
+}
+
+impl<T: DrawTarget> TerrainLayer<T> {
+    /// The map at the default zoom.
+    fn map(target: T) -> Self {
+        Self { target, cell_w: CELL_W, cell_h: CELL_H, shaded: true, painted: Vec::new() }
+    }
+
+    /// One flat pixel a cell, scaled up for the minimap.
+    fn minimap(target: T) -> Self {
+        Self { target, cell_w: 1.0, cell_h: 1.0, shaded: false, painted: Vec::new() }
+    }
+
+    /// Repaint the cells that differ from what the layer shows, or all of
+    /// them when the map size changed. Returns how many were painted.
+    fn update(&mut self, state: &GameState, cols: usize, rows: usize) -> usize {
+        let size = ((cols as f64 * self.cell_w) as u32, (rows as f64 * self.cell_h) as u32);
+        let resized = self.target.size() != size || self.painted.len() != cols;
+        if resized {
+            self.target.set_size(size.0, size.1);
+            self.painted = vec![vec![None; rows]; cols];
+        }
+        let mut painted = 0;
+        for col in 0..cols {
+            for row in 0..rows {
+                let color = terrain_color(state, col, row);
+                if resized || self.painted[col][row] != color {
+                    let cell = Rect::new(col as f64 * self.cell_w, row as f64 * self.cell_h, self.cell_w, self.cell_h);
+                    if self.shaded {
+                        paint_terrain_cell(&mut self.target, &state.theme, cell, (col + row).is_multiple_of(2), color);
+                    } else {
+                        self.target.set_fill_style(&rgb(color.unwrap_or(state.theme.unexplored)));
+                        self.target.fill_rect(cell.x, cell.y, cell.w, cell.h);
+                    }
+                    self.painted[col][row] = color;
+                    painted += 1;
+                }
+            }
+        }
+        painted
+    }
+}
+
+/// Draws a `GameState` onto any [`DrawTarget`].
+pub struct Renderer<T: DrawTarget> {
+    ctx: T,
+    layout: Layout,
+    terrain: Option<TerrainLayer<T>>,
+    /// Cells repainted on the terrain layer by the last frame.
+    terrain_painted: usize,
+    minimap: Option<TerrainLayer<T>>,
+    /// Colours of the last frame; the layers are repainted when it changes.
+    theme: Theme,
+    /// Epoch of the last snapshot drawn; snapshots are only diffed within one.
+    snapshot_epoch: u64,
+    /// Units sliding between the cells of the last two snapshots.
+    motion: Motion,
+    /// Battle effects found between snapshots, still running.
+    effects: Effects,
+}
+
+impl<T: DrawTarget> Renderer<T> {
+    pub fn new(target: T) -> Self {
+        let terrain = target.create_layer().map(TerrainLayer::map);
+        let minimap = target.create_layer().map(TerrainLayer::minimap);
+        Self {
+            ctx: target,
+            layout: Layout::default(),
+            terrain,
+            terrain_painted: 0,
+            minimap,
+            theme: Theme::default(),
+            snapshot_epoch: 0,
+            motion: Motion::default(),
+            effects: Effects::default(),
+        }
+    }
+
+    /// Layout of the last rendered frame, for hit-testing pointer input.
+    pub fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    pub fn target(&self) -> &T {
+        &self.ctx
+    }
+
+    pub fn target_mut(&mut self) -> &mut T {
+        &mut self.ctx
+    }
+
+    pub fn into_target(self) -> T {
+        self.ctx
+    }
+
+    /// The cached terrain layer, when the target supports layers.
+    pub fn terrain_layer(&self) -> Option<&T> {
+        self.terrain.as_ref().map(|t| &t.target)
+    }
+
+    /// How many cells the last frame repainted on the terrain layer.
+    pub fn terrain_painted(&self) -> usize {
+        self.terrain_painted
+    }
+
+    /// Draw one frame. `now` is a monotonic animation clock in ms;
+    /// `wall_ms` is the Unix epoch time in ms, compared against server
+    /// deadlines such as `error_until`.
+    pub fn render(&mut self, state: &GameState, now: f64, wall_ms: f64) {
+        if self.theme != state.theme {
+            self.theme = state.theme.clone();
+            for layer in self.terrain.iter_mut().chain(self.minimap.iter_mut()) {
+                layer.painted.clear();
+            }
+        }
+
+        // Diff against the last snapshot only when this one follows on from it
+        let continuous = state.snapshot_epoch == self.snapshot_epoch;
+        self.snapshot_epoch = state.snapshot_epoch;
+        self.motion.update(state, continuous, now);
+        self.effects.update(state, continuous, self.motion.moves(), now);
+
+        let layout = Layout::compute(state, &mut self.ctx);
+        let (total_w, total_h) = (layout.width as u32, layout.height as u32);
+        let (cols, rows) = (layout.cols, layout.rows);
+        if cols == 0 || rows == 0 {
+            // Nothing to draw except a handshake failure
+            if let Some(ref banner) = layout.protocol_banner {
+                if self.ctx.size() != (total_w, total_h) {
+                    self.ctx.set_size(total_w, total_h);
+                }
+                self.ctx.set_fill_style(&rgb(self.theme.canvas_bg));
+                self.ctx.fill_rect(0.0, 0.0, layout.width, layout.height);
+                self.draw_protocol_banner(banner);
+            }
+            self.layout = layout;
+            return;
+        }
+
+        let grid_w = layout.map.w;
+        let map_h = layout.map.h;
+        let cam = layout.camera;
+        let (vis_cols, vis_rows) = cam.visible(cols, rows, grid_w, map_h);
+        let canvas_w = layout.message_area.w;
+        let text_h = layout.height - map_h;
+
+        // Resize canvas if needed
+        if self.ctx.size() != (total_w, total_h) {
+            self.ctx.set_size(total_w, total_h);
+        }
+
+        // Clear with canvas background
+        self.ctx.set_fill_style(&rgb(self.theme.canvas_bg));
+        self.ctx.fill_rect(0.0, 0.0, total_w as f64, total_h as f64);
+
+        // Smooth animation values
+        let pulse_attention = pulse(now, 1500.0);
+        let pulse_completed = pulse(now, 2000.0);
+        let pulse_unit = pulse(now, 800.0);
+        let pulse_marker = pulse(now, 900.0);
+
+        // Draw fog, terrain and depth shading from the cached layer
+        self.draw_terrain(state, &layout);
+
+        // Draw pulsing cells (attention target, finished production) over it
+        self.draw_cell_pulses(state, &cam, &vis_cols, &vis_rows, pulse_attention, pulse_completed);
+
+        // Draw grid lines (soft)
+        self.draw_grid(&cam, &vis_cols, &vis_rows, grid_w, map_h);
+
+        // Draw hover cell highlight
+        self.draw_hover_highlight(state, &cam, grid_w, map_h);
+
+        // Draw selected cell outline
+        self.draw_selected_outline(state, &cam, grid_w, map_h);
+
+        // Draw the selected fighter's fuel range and refuel points
+        let fuel_range = FuelRange::of_selected(state);
+        if let Some(ref range) = fuel_range {
+            self.draw_fuel_range(state, range, &cam, grid_w, map_h);
+        }
+
+        // Draw where units are headed, under the units themselves
+        self.draw_orders(state, &cam, grid_w, map_h);
+
+        // Draw production indicators, units, and waypoints
+        self.ctx.set_font(&cell_font(cam.zoom()));
+        let slide = self.motion.progress(now, state.move_animation_ms);
+        let sliding = match slide {
+            Some(_) => self.motion.moves().to_vec(),
+            None => Vec::new(),
+        };
+        self.draw_cell_contents(state, &cam, &vis_cols, &vis_rows, pulse_unit, &sliding);
+        if let Some(t) = slide {
+            self.draw_sliding_units(state, &cam, &sliding, t);
+        }
+
+        // Draw fighter and army counts aboard ships and in cities
+        if cam.zoom() >= BADGE_MIN_ZOOM {
+            self.draw_cargo_badges(state, &cam, &vis_cols, &vis_rows);
+        }
+
+        // Draw explosions, captured cities and lost hits
+        self.draw_effects(&cam, grid_w, map_h, now);
+
+        // Draw an in-cell marker for the active "needs attention" target
+        self.draw_attention_marker(state, &cam, grid_w, map_h, pulse_marker);
+
+        // Warn when the selected fighter can't reach a city or carrier
+        if let Some(ref range) = fuel_range {
+            if range.stranded() {
+                self.draw_fuel_warning(range, grid_w);
+            }
+        }
+
+        // Cells cut by the bottom of a scrolled view spill into the gap
+        // above the message area
+        if map_h < rows as f64 * cam.cell_h() {
+            self.ctx.set_fill_style(&rgb(self.theme.canvas_bg));
+            self.ctx.fill_rect(0.0, map_h, canvas_w, TEXT_AREA_GAP);
+        }
+
+        // Draw the overview of a map larger than the view
+        if let Some(ref minimap) = layout.minimap {
+            self.draw_minimap(state, minimap, pulse_marker);
+        }
+
+        // Draw message area panel
+        self.draw_message_area(state, wall_ms, canvas_w, map_h, text_h);
+
+        // Draw decoder diagnostics line (toggled with F3)
+        if state.show_diagnostics {
+            self.draw_diagnostics(state, canvas_w, map_h);
+        }
+
+        // Draw inspector panel (selected cell details)
+        if let Some(ref inspector) = layout.inspector {
+            self.draw_inspector_panel(inspector, state.hovered_control);
+        }
+
+        // Draw tutorial overlay if active and visible
+        if let (Some(tut), Some(l)) = (&state.tutorial, &layout.tutorial_overlay) {
+            self.draw_tutorial_overlay(tut, l, state.hovered_control);
+        }
+
+        // Draw contextual help overlay (tips + cheat sheet)
+        if let Some(ref help) = layout.help {
+            self.draw_help_overlay(help, state.hovered_control);
+        }
+
+        // Draw tutorial menu if open
+        if let (Some(menu), Some(l)) = (&state.tutorial_menu, &layout.tutorial_menu) {
+            self.draw_tutorial_menu(menu, l, state.hovered_control, total_w as f64, total_h as f64);
+        }
+
+        // Draw load menu if open
+        if let (Some(menu), Some(l)) = (&state.load_menu, &layout.load_menu) {
+            self.draw_load_menu(menu, l, state.hovered_control, total_w as f64, total_h as f64);
+        }
+
+        // Version mismatch reported by the handshake
+        if let Some(ref banner) = layout.protocol_banner {
+            self.draw_protocol_banner(banner);
+        }
+
+        // Replay controls, above any recorded menu
+        if let (Some(replay), Some(l)) = (&state.replay, &layout.replay_bar) {
+            self.draw_replay_bar(replay, l, state.hovered_control);
+        }
+
+        // Dim the last known state while the connection is down
+        self.draw_connection_overlay(state, wall_ms, total_w as f64, total_h as f64);
+
+        self.layout = layout;
+    }
+
+    fn draw_protocol_banner(&mut self, l: &BannerLayout) {
+        let Rect { x: left, y: top, w: banner_w, h: banner_h } = l.panel;
+        let padding = l.padding;
+        let line_h = 20.0;
+
+        self.ctx.set_fill_style(&rgb(self.theme.panel_bg));
+        self.ctx.fill_rect(left, top, banner_w, banner_h);
+        self.ctx.set_fill_style(&rgb(self.theme.error));
+        self.ctx.fill_rect(left, top, 3.0, banner_h);
+        self.ctx.set_stroke_style(&rgb(self.theme.error));
+        self.ctx.set_line_width(1.0);
+        self.ctx.stroke_rect(left, top, banner_w, banner_h);
+
+        self.ctx.set_text_baseline("top");
+        self.ctx.set_font(FONT_MENU_TITLE);
+        self.ctx.set_fill_style(&rgb(self.theme.error));
+        self.ctx.fill_text("Incompatible server", left + padding + 4.0, top + padding);
+
+        self.ctx.set_font(FONT_MENU_ITEM);
+        self.ctx.set_fill_style(&rgb(self.theme.text_primary));
+        for (i, line) in l.lines.iter().enumerate() {
+            self.ctx.fill_text(line, left + padding + 4.0, l.text_top + i as f64 * line_h);
+        }
+        self.ctx.set_text_baseline("alphabetic");
+    }
+
+    fn draw_connection_overlay(&mut self, state: &GameState, wall_ms: f64, screen_w: f64, screen_h: f64) {
+        let dropped = wall_ms < state.input_dropped_until;
+        if !state.stale && !dropped {
+            return;
+        }
+
+        if state.stale {
+            self.ctx.set_global_alpha(0.55);
+            self.ctx.set_fill_style(&rgb(self.theme.canvas_bg));
+            self.ctx.fill_rect(0.0, 0.0, screen_w, screen_h);
+            self.ctx.set_global_alpha(1.0);
+        }
+
+        let text = match (state.connected, dropped) {
+            (false, true) => "Not connected - input ignored",
+            (false, false) => "Connection lost - reconnecting...",
+            (true, true) => "Waiting for server state - input ignored",
+            (true, false) => "Waiting for server state...",
+        };
+
+        self.ctx.set_font(FONT_MSG);
+        let text_w = self.ctx.measure_text(text);
+        let banner_w = text_w + 32.0;
+        let banner_h = 32.0;
+        let left = (screen_w - banner_w) / 2.0;
+        let top = (screen_h - banner_h) / 2.0;
+
+        self.ctx.set_fill_style(&rgb(self.theme.panel_bg));
+        self.ctx.fill_rect(left, top, banner_w, banner_h);
+        self.ctx.set_fill_style(&rgb(self.theme.error));
+        self.ctx.fill_rect(left, top, 3.0, banner_h);
+        self.ctx.set_stroke_style(&rgb(self.theme.panel_border));
+        self.ctx.set_line_width(1.0);
+        self.ctx.stroke_rect(left, top, banner_w, banner_h);
+
+        self.ctx.set_text_baseline("middle");
+        self.ctx.set_fill_style(&rgb(if dropped { self.theme.error } else { self.theme.text_primary }));
+        self.ctx.fill_text(text, left + 16.0, top + banner_h / 2.0);
+        self.ctx.set_text_baseline("alphabetic");
+    }
+
+    fn draw_terrain(&mut self, state: &GameState, layout: &Layout) {
+        let (cols, rows, cam, view) = (layout.cols, layout.rows, layout.camera, layout.map);
+        match self.terrain.as_mut() {
+            Some(layer) => {
+                self.terrain_painted = layer.update(state, cols, rows);
+                // The layer is kept at the default zoom; scale the part in view
+                let src = Rect::new(cam.left * CELL_W, cam.top * CELL_H, view.w / cam.zoom(), view.h / cam.zoom());
+                self.ctx.draw_layer(&layer.target, src, view);
+            }
+            None => {
+                let (vis_cols, vis_rows) = cam.visible(cols, rows, view.w, view.h);
+                for col in vis_cols {
+                    for row in vis_rows.clone() {
+                        let color = terrain_color(state, col, row);
+                        paint_terrain_cell(&mut self.ctx, &self.theme, cam.cell_rect(col, row), (col + row).is_multiple_of(2), color);
+                    }
+                }
+            }
+        }
+    }
+
+    fn draw_cell_pulses(
+        &mut self,
+        state: &GameState,
+        cam: &Camera,
+        cols: &Range<usize>,
+        rows: &Range<usize>,
+        pulse_attention: f64,
+        pulse_completed: f64,
+    ) {
+        let attention_cell = state.attention_coords.first();
+
+        for col in cols.clone() {
+            for row in rows.clone() {
+                let cell = match state.cells.get(col).and_then(|c| c.get(row)) {
+                    Some(Some(cell)) => cell,
+                    _ => continue,
+                };
+
+                if cell.t == Terrain::Unexplored {
+                    continue;
+                }
+
+                let base_color = cell_color(&self.theme, cell);
+                let is_attention = attention_cell.is_some_and(|ac| ac.0 == col && ac.1 == row);
+
+                let is_completed = cell.t == Terrain::City
+                    && cell.cs != Some(CityStatus::Free)
+                    && cell.prod.as_ref().is_some_and(|p| p.remaining == 0);
+
+                let color = if is_attention {
+                    // Smooth pulse between base color and bright highlight
+                    let highlight = brighten(base_color, 1.6);
+                    lerp_color(base_color, highlight, pulse_attention * 0.7)
+                } else if is_completed {
+                    // Smooth pulse between city color and white
+                    lerp_color(base_color, self.theme.text_primary, pulse_completed * 0.5)
+                } else {
+                    continue;
+                };
+
+                let r = cam.cell_rect(col, row);
+                self.ctx.set_fill_style(&rgb(color));
+                self.ctx.fill_rect(r.x, r.y, r.w, r.h);
+                draw_cell_depth(&mut self.ctx, r, base_color);
+            }
+        }
+    }
+
+    fn draw_grid(&mut self, cam: &Camera, cols: &Range<usize>, rows: &Range<usize>, map_w: f64, map_h: f64) {
+        if cols.is_empty() || rows.is_empty() {
+            return;
+        }
+        let first = cam.cell_rect(cols.start, rows.start);
+        let last = cam.cell_rect(cols.end - 1, rows.end - 1);
+        let (right, bottom) = (last.right().min(map_w), last.bottom().min(map_h));
+        self.ctx.set_stroke_style(&rgba(self.theme.grid, 0.3));
+        self.ctx.set_line_width(1.0);
+        self.ctx.begin_path();
+        for col in cols.start..=cols.end {
+            let x = cam.cell_rect(col, 0).x;
+            self.ctx.move_to(x, first.y.max(0.0));
+            self.ctx.line_to(x, bottom);
+        }
+        for row in rows.start..=rows.end {
+            let y = cam.cell_rect(0, row).y;
+            self.ctx.move_to(first.x.max(0.0), y);
+            self.ctx.line_to(right, y);
+        }
+        self.ctx.stroke();
+    }
+
+    fn draw_hover_highlight(&mut self, state: &GameState, cam: &Camera, map_w: f64, map_h: f64) {
+        if let (Some(col), Some(row)) = (state.hover_col, state.hover_row) {
+            let (cols, rows) = state.map_size;
+            if col < cols && row < rows {
+                let r = cam.cell_rect(col, row);
+                if in_view(r, map_w, map_h) {
+                    self.ctx.set_stroke_style(&rgba(self.theme.accent, 0.8));
+                    self.ctx.set_line_width(2.0);
+                    self.ctx.stroke_rect(r.x + 0.5, r.y + 0.5, r.w - 1.0, r.h - 1.0);
+                    self.ctx.set_line_width(1.0);
+                }
+            }
+        }
+    }
+
+    fn draw_selected_outline(&mut self, state: &GameState, cam: &Camera, map_w: f64, map_h: f64) {
+        if let (Some(col), Some(row)) = (state.selected_col, state.selected_row) {
+            let (cols, rows) = state.map_size;
+            if col < cols && row < rows {
+                let r = cam.cell_rect(col, row);
+                if in_view(r, map_w, map_h) {
+                    // Bold outline with subtle shadow for high contrast.
+                    self.ctx.set_stroke_style(&rgba(self.theme.shadow, 0.55));
+                    self.ctx.set_line_width(4.0);
+                    self.ctx.stroke_rect(r.x + 1.5, r.y + 1.5, r.w - 3.0, r.h - 3.0);
+
+                    self.ctx.set_stroke_style(&rgba(self.theme.selection, 0.95));
+                    self.ctx.set_line_width(2.0);
+                    self.ctx.stroke_rect(r.x + 1.5, r.y + 1.5, r.w - 3.0, r.h - 3.0);
+                    self.ctx.set_line_width(1.0);
+                }
+            }
+        }
+    }
+
+    fn draw_attention_marker(&mut self, state: &GameState, cam: &Camera, map_w: f64, map_h: f64, pulse: f64) {
+        if !state.waiting_for_input {
+            return;
+        }
+
+        let Some(&(col, row)) = state.attention_coords.first() else {
+            return;
+        };
+
+        let (cols, rows) = state.map_size;
+        if col >= cols || row >= rows {
+            return;
+        }
+
+        let r = cam.cell_rect(col, row);
+        if !in_view(r, map_w, map_h) {
+            return;
+        }
+
+        // Subtle pulsing corner chevrons so it's clear the *attention target* is here.
+        let alpha = 0.55 + 0.35 * pulse;
+        self.ctx.set_stroke_style(&rgba(self.theme.sentry, alpha));
+        self.ctx.set_line_width(2.0);
+
+        let inset = 2.5 * cam.zoom();
+        let len = 5.5 * cam.zoom();
+        let left = r.x + inset;
+        let right = r.right() - inset;
+        let top = r.y + inset;
+        let bottom = r.bottom() - inset;
+
+        self.ctx.begin_path();
+        // top-left
+        self.ctx.move_to(left + len, top);
+        self.ctx.line_to(left, top);
+        self.ctx.line_to(left, top + len);
+        // top-right
+        self.ctx.move_to(right - len, top);
+        self.ctx.line_to(right, top);
+        self.ctx.line_to(right, top + len);
+        // bottom-left
+        self.ctx.move_to(left + len, bottom);
+        self.ctx.line_to(left, bottom);
+        self.ctx.line_to(left, bottom - len);
+        // bottom-right
+        self.ctx.move_to(right - len, bottom);
+        self.ctx.line_to(right, bottom);
+        self.ctx.line_to(right, bottom - len);
+        self.ctx.stroke();
+
+        self.ctx.set_line_width(1.0);
+    }
+
+    /// Shade the cells the fighter can still reach, ring the part it can
+    /// reach this round, and outline the refuel points among them. The
+    /// range turns red when none are in reach.
+    fn draw_fuel_range(&mut self, state: &GameState, range: &FuelRange, cam: &Camera, map_w: f64, map_h: f64) {
+        let color = if range.stranded() { self.theme.error } else { self.theme.accent };
+        let area = |(cols, rows): (Range<usize>, Range<usize>)| {
+            let first = cam.cell_rect(cols.start, rows.start);
+            let last = cam.cell_rect(cols.end - 1, rows.end - 1);
+            // Clipped to the view, with a pixel to spare so off-view edges stay hidden
+            let left = first.x.max(-1.0);
+            let top = first.y.max(-1.0);
+            Rect::new(left, top, last.right().min(map_w + 1.0) - left, last.bottom().min(map_h + 1.0) - top)
+        };
+        let full = area(range.cells(state.map_size));
+        let round = area(range.this_round(state.map_size));
+        if full.w <= 0.0 || full.h <= 0.0 {
+            return;
+        }
+
+        self.ctx.set_fill_style(&rgba(color, 0.08));
+        self.ctx.fill_rect(full.x, full.y, full.w, full.h);
+        self.ctx.set_line_width(1.0);
+        self.ctx.set_stroke_style(&rgba(color, 0.7));
+        self.ctx.stroke_rect(full.x + 0.5, full.y + 0.5, full.w - 1.0, full.h - 1.0);
+        if round != full && round.w > 0.0 && round.h > 0.0 {
+            self.ctx.set_stroke_style(&rgba(color, 0.35));
+            self.ctx.stroke_rect(round.x + 0.5, round.y + 0.5, round.w - 1.0, round.h - 1.0);
+        }
+
+        self.ctx.set_stroke_style(&rgba(self.theme.explore, 0.9));
+        self.ctx.set_line_width(2.0);
+        for &(col, row) in &range.refuel {
+            let r = cam.cell_rect(col, row);
+            if in_view(r, map_w, map_h) {
+                self.ctx.stroke_rect(r.x + 1.5, r.y + 1.5, r.w - 3.0, r.h - 3.0);
+            }
+        }
+        self.ctx.set_line_width(1.0);
+    }
+
+    fn draw_fuel_warning(&mut self, range: &FuelRange, map_w: f64) {
+        let text = format!("Fighter has {} fuel: no city or carrier in range", range.fuel);
+        self.ctx.set_font(FONT_MENU_ITEM);
+        let w = self.ctx.measure_text(&text) + 24.0;
+        let h = 28.0;
+        let left = ((map_w - w) / 2.0).max(4.0);
+        let top = 8.0;
+
+        self.ctx.set_fill_style(&rgb(self.theme.panel_bg));
+        self.ctx.fill_rect(left, top, w, h);
+        self.ctx.set_stroke_style(&rgb(self.theme.error));
+        self.ctx.stroke_rect(left + 0.5, top + 0.5, w - 1.0, h - 1.0);
+
+        self.ctx.set_text_baseline("middle");
+        self.ctx.set_fill_style(&rgb(self.theme.error));
+        self.ctx.fill_text(&text, left + 12.0, top + h / 2.0);
+        self.ctx.set_text_baseline("alphabetic");
+    }
+
+    /// Marching orders and flight paths as arrows from unit to destination:
+    /// the selected unit's always, everyone's while `show_all_orders` is on.
+    fn draw_orders(&mut self, state: &GameState, cam: &Camera, map_w: f64, map_h: f64) {
+        let selected = state.selected_col.zip(state.selected_row);
+        let (cols, rows) = state.map_size;
+        let units: Vec<(usize, usize)> = if state.show_all_orders {
+            (0..cols).flat_map(|col| (0..rows).map(move |row| (col, row))).collect()
+        } else {
+            selected.into_iter().collect()
+        };
+
+        for (col, row) in units {
+            let cell = state.cells.get(col).and_then(|c| c.get(row)).and_then(|c| c.as_ref());
+            let Some(unit) = cell.and_then(|c| c.u.as_ref()) else {
+                continue;
+            };
+            let alpha = if selected == Some((col, row)) { 0.9 } else { 0.5 };
+            let orders = [
+                (&unit.marching_orders, self.theme.marching_orders),
+                (&unit.flight_path, self.theme.flight_path),
+            ];
+            for (orders, color) in orders {
+                let Some((to_col, to_row)) = orders.as_ref().and_then(|o| o.target()) else {
+                    continue;
+                };
+                if to_col >= cols || to_row >= rows || (to_col, to_row) == (col, row) {
+                    continue;
+                }
+                let from = cam.cell_rect(col, row);
+                let to = cam.cell_rect(to_col, to_row);
+                let (x0, y0) = (from.x + from.w / 2.0, from.y + from.h / 2.0);
+                let (x1, y1) = (to.x + to.w / 2.0, to.y + to.h / 2.0);
+                // Skip arrows wholly off the view
+                if x0.max(x1) < 0.0 || x0.min(x1) > map_w || y0.max(y1) < 0.0 || y0.min(y1) > map_h {
+                    continue;
+                }
+                draw_arrow(&mut self.ctx, (x0, y0), (x1, y1), 5.0 * cam.zoom(), &rgba(color, alpha));
+            }
+        }
+    }
+
+    fn draw_cell_contents(
+        &mut self,
+        state: &GameState,
+        cam: &Camera,
+        cols: &Range<usize>,
+        rows: &Range<usize>,
+        pulse_unit: f64,
+        sliding: &[Move],
+    ) {
+        let attention_cell = state.attention_coords.first();
+
+        for col in cols.clone() {
+            for row in rows.clone() {
+                let cell = match state.cells.get(col).and_then(|c| c.get(row)) {
+                    Some(Some(cell)) => cell,
+                    _ => continue,
+                };
+
+                let r = cam.cell_rect(col, row);
+                let cx = r.x + CELL_CHAR_X_OFFSET * cam.zoom();
+                let cy = r.y + CELL_CHAR_Y_OFFSET * cam.zoom();
+
+                // Draw production indicator (thermometer bar + character)
+                if cell.t == Terrain::City {
+                    if let Some(ref prod) = cell.prod {
+                        let total = item_cost(&prod.item) as f64;
+                        let remaining = prod.remaining as f64;
+                        let progress = (total - remaining) / total;
+
+                        if progress > 0.0 && remaining > 0.0 {
+                            let base = cell_color(&self.theme, cell);
+                            let dark = darken(base, 0.5);
+                            self.ctx.set_global_alpha(0.5);
+                            self.ctx.set_fill_style(&rgb(dark));
+                            let bar_height = r.h * progress;
+                            self.ctx.fill_rect(r.x, r.bottom() - bar_height, r.w, bar_height);
+                            self.ctx.set_global_alpha(1.0);
+                        }
+
+                        self.draw_unit(state.unit_style, &prod.item, false, r, cam.zoom(), self.theme.production);
+                    }
+                }
+
+                // Draw unit, unless it is still sliding in from another cell
+                if let Some(ref unit) = cell.u {
+                    if sliding.iter().any(|m| m.to == (col, row)) {
+                        continue;
+                    }
+                    let is_attention = attention_cell.is_some_and(|ac| ac.0 == col && ac.1 == row);
+
+                    let has_awake_airport = cell.af.unwrap_or(0) > 0;
+                    let has_awake_carrier = unit.t == UnitKind::Carrier
+                        && cell.af.unwrap_or(0) > 0;
+                    let has_awake_army = unit.t == UnitKind::Transport
+                        && cell.aa.unwrap_or(0) > 0;
+                    let has_contained = has_awake_airport || has_awake_carrier || has_awake_army;
+                    let computer = unit.o == Owner::Computer;
+
+                    if is_attention && has_contained {
+                        // Smooth crossfade between container and contained unit
+                        let contained = if has_awake_airport || has_awake_carrier {
+                            UnitKind::Fighter
+                        } else {
+                            UnitKind::Army
+                        };
+
+                        let alpha_contained = pulse_unit;
+                        let alpha_container = 1.0 - pulse_unit;
+
+                        if alpha_container > 0.05 {
+                            self.ctx.set_global_alpha(alpha_container);
+                            self.draw_unit(state.unit_style, &unit.t, computer, r, cam.zoom(), unit_color(&self.theme, unit));
+                        }
+                        if alpha_contained > 0.05 {
+                            self.ctx.set_global_alpha(alpha_contained);
+                            self.draw_unit(state.unit_style, &contained, false, r, cam.zoom(), self.theme.awake);
+                        }
+                        self.ctx.set_global_alpha(1.0);
+                    } else {
+                        self.draw_unit(state.unit_style, &unit.t, computer, r, cam.zoom(), unit_color(&self.theme, unit));
+                    }
+
+                    if let Some((hits, max)) = damage(unit) {
+                        draw_hit_bar(&mut self.ctx, &self.theme, r, cam.zoom(), hits, max);
+                    }
+                } else if cell.wp == Some(true) {
+                    draw_text_shadow(&mut self.ctx, &self.theme, "*", cx, cy, self.theme.waypoint);
+                }
+            }
+        }
+    }
+
+    /// Units `t` of the way from their old cell to their new one.
+    fn draw_sliding_units(&mut self, state: &GameState, cam: &Camera, moves: &[Move], t: f64) {
+        for m in moves {
+            let unit = match state.cells.get(m.to.0).and_then(|c| c.get(m.to.1)) {
+                Some(Some(CellMsg { u: Some(unit), .. })) => unit,
+                _ => continue,
+            };
+            let from = cam.cell_rect(m.from.0, m.from.1);
+            let to = cam.cell_rect(m.to.0, m.to.1);
+            let r = Rect::new(from.x + (to.x - from.x) * t, from.y + (to.y - from.y) * t, to.w, to.h);
+            let computer = unit.o == Owner::Computer;
+            self.draw_unit(state.unit_style, &unit.t, computer, r, cam.zoom(), unit_color(&self.theme, unit));
+            if let Some((hits, max)) = damage(unit) {
+                draw_hit_bar(&mut self.ctx, &self.theme, r, cam.zoom(), hits, max);
+            }
+        }
+    }
+
+    /// A flash and shockwave where a unit was destroyed, a ring in the new
+    /// owner's colour round a captured city, and lost hits floating up off
+    /// a damaged unit.
+    fn draw_effects(&mut self, cam: &Camera, map_w: f64, map_h: f64, now: f64) {
+        if self.effects.active().is_empty() {
+            return;
+        }
+        let zoom = cam.zoom();
+        for effect in self.effects.active().to_vec() {
+            let Some(age) = effect.age(now) else { continue };
+            let r = cam.cell_rect(effect.col, effect.row);
+            if !in_view(r, map_w, map_h) {
+                continue;
+            }
+            let (cx, cy) = (r.x + r.w / 2.0, r.y + r.h / 2.0);
+            let size = r.w.max(r.h);
+            let fade = 1.0 - age;
+            match effect.kind {
+                EffectKind::Destroyed => {
+                    self.ctx.set_fill_style(&rgba(self.theme.sentry, 0.8 * fade * fade));
+                    self.ctx.fill_rect(r.x, r.y, r.w, r.h);
+                    self.ctx.set_stroke_style(&rgba(self.theme.error, fade));
+                    self.ctx.set_line_width(2.0 * zoom);
+                    draw_ring(&mut self.ctx, cx, cy, size * (0.3 + 0.9 * age));
+                }
+                EffectKind::Captured(ref owner) => {
+                    let color = match owner {
+                        CityStatus::Player => self.theme.player_city,
+                        CityStatus::Computer => self.theme.computer_city,
+                        _ => self.theme.free_city,
+                    };
+                    self.ctx.set_stroke_style(&rgba(color, fade));
+                    self.ctx.set_line_width(2.0 * zoom);
+                    draw_ring(&mut self.ctx, cx, cy, size * (0.5 + 1.5 * age));
+                }
+                EffectKind::Damaged(lost) => {
+                    let x = r.x + CELL_CHAR_X_OFFSET * zoom;
+                    let y = r.y + CELL_CHAR_Y_OFFSET * zoom - 0.8 * r.h * age;
+                    self.ctx.set_font(&cell_font(zoom));
+                    self.ctx.set_global_alpha(fade);
+                    draw_text_shadow(&mut self.ctx, &self.theme, &format!("-{}", lost), x, y, self.theme.error);
+                    self.ctx.set_global_alpha(1.0);
+                }
+            }
+        }
+        self.ctx.set_line_width(1.0);
+    }
+
+    /// A unit, or a city's production item, in `cell`. Letters mark the
+    /// computer's units by case; icons by a body in the theme's computer
+    /// city colour, outlined in `color`.
+    fn draw_unit(&mut self, style: UnitStyle, kind: &UnitKind, computer: bool, cell: Rect, zoom: f64, color: Color) {
+        match style {
+            UnitStyle::Letters => {
+                let ch = unit_char(kind);
+                let text = if computer { ch.to_lowercase() } else { ch.to_string() };
+                let x = cell.x + CELL_CHAR_X_OFFSET * zoom;
+                let y = cell.y + CELL_CHAR_Y_OFFSET * zoom;
+                draw_text_shadow(&mut self.ctx, &self.theme, &text, x, y, color);
+            }
+            UnitStyle::Icons => {
+                let (fill, outline, width) = if computer {
+                    (rgb(self.theme.computer_city), rgb(color), zoom)
+                } else {
+                    (rgb(color), rgba(self.theme.shadow, 0.8), zoom)
+                };
+                draw_unit_icon(&mut self.ctx, kind, cell, &fill, &outline, width);
+            }
+        }
+    }
+
+    /// Count badges along the top of cells holding fighters or armies:
+    /// fighters in the right corner, armies to their left. A badge is
+    /// accented while any of its units are awake.
+    fn draw_cargo_badges(&mut self, state: &GameState, cam: &Camera, cols: &Range<usize>, rows: &Range<usize>) {
+        let mut badges = Vec::new();
+        for col in cols.clone() {
+            for row in rows.clone() {
+                if let Some(Some(cell)) = state.cells.get(col).and_then(|c| c.get(row)) {
+                    let cargo = [(cell.fc, cell.af), (cell.ac, cell.aa)];
+                    for (count, awake) in cargo {
+                        if let Some(count) = count.filter(|&n| n > 0) {
+                            badges.push((col, row, count, awake.unwrap_or(0) > 0));
+                        }
+                    }
+                }
+            }
+        }
+        if badges.is_empty() {
+            return;
+        }
+
+        let zoom = cam.zoom();
+        self.ctx.set_font(&badge_font(zoom));
+        self.ctx.set_text_baseline("top");
+        self.ctx.set_text_align("center");
+        let mut right = 0.0;
+        let mut last_cell = None;
+        for (col, row, count, awake) in badges {
+            let r = cam.cell_rect(col, row);
+            if last_cell != Some((col, row)) {
+                right = r.right() - zoom;
+                last_cell = Some((col, row));
+            }
+            let text = count.to_string();
+            let w = self.ctx.measure_text(&text) + 2.0 * zoom;
+            let h = BADGE_FONT_PX * zoom + zoom;
+            let (bg, fg) = if awake {
+                (rgb(self.theme.sentry), rgb(self.theme.canvas_bg))
+            } else {
+                (rgba(self.theme.shadow, 0.7), rgb(self.theme.text_primary))
+            };
+            self.ctx.set_fill_style(&bg);
+            self.ctx.fill_rect(right - w, r.y + zoom, w, h);
+            self.ctx.set_fill_style(&fg);
+            self.ctx.fill_text(&text, right - w / 2.0, r.y + 1.5 * zoom);
+            right -= w + zoom;
+        }
+        self.ctx.set_text_align("left");
+        self.ctx.set_text_baseline("alphabetic");
+    }
+
+    fn draw_minimap(&mut self, state: &GameState, l: &MinimapLayout, pulse: f64) {
+        let (cols, rows) = state.map_size;
+        let Rect { x: left, y: top, w: panel_w, h: panel_h } = l.panel;
+        self.ctx.set_global_alpha(0.92);
+        self.ctx.set_fill_style(&rgb(self.theme.panel_bg));
+        self.ctx.fill_rect(left, top, panel_w, panel_h);
+        self.ctx.set_global_alpha(1.0);
+        self.ctx.set_stroke_style(&rgb(self.theme.panel_border));
+        self.ctx.set_line_width(1.0);
+        self.ctx.stroke_rect(left + 0.5, top + 0.5, panel_w - 1.0, panel_h - 1.0);
+
+        // Terrain, one block a cell
+        match self.minimap.as_mut() {
+            Some(layer) => {
+                layer.update(state, cols, rows);
+                self.ctx.draw_layer(&layer.target, Rect::new(0.0, 0.0, cols as f64, rows as f64), l.map);
+            }
+            None => {
+                for col in 0..cols {
+                    for row in 0..rows {
+                        let r = l.cell_rect(col, row);
+                        self.ctx.set_fill_style(&rgb(terrain_color(state, col, row).unwrap_or(self.theme.unexplored)));
+                        self.ctx.fill_rect(r.x, r.y, r.w, r.h);
+                    }
+                }
+            }
+        }
+
+        // Cities and units stand out as dots at least a few pixels wide
+        let dot = |l: &MinimapLayout, col: usize, row: usize, size: f64| {
+            let r = l.cell_rect(col, row);
+            let size = size.max(l.scale);
+            Rect::new(r.x + (r.w - size) / 2.0, r.y + (r.h - size) / 2.0, size, size)
+        };
+        for (col, column) in state.cells.iter().enumerate().take(cols) {
+            for (row, cell) in column.iter().enumerate().take(rows) {
+                let Some(cell) = cell else { continue };
+                let marker = if let Some(ref unit) = cell.u {
+                    let color = if unit.o == Owner::Player { self.theme.explore } else { self.theme.error };
+                    Some((color, 2.0))
+                } else if cell.t == Terrain::City && cell.cs != Some(CityStatus::Free) {
+                    Some((cell_color(&self.theme, cell), 3.0))
+                } else {
+                    None
+                };
+                if let Some((color, size)) = marker {
+                    let r = dot(l, col, row, size);
+                    self.ctx.set_fill_style(&rgb(color));
+                    self.ctx.fill_rect(r.x, r.y, r.w, r.h);
+                }
+            }
+        }
+
+        // Cells waiting for orders
+        self.ctx.set_stroke_style(&rgba(self.theme.sentry, 0.55 + 0.35 * pulse));
+        self.ctx.set_line_width(1.5);
+        for &(col, row) in state.attention_coords.iter().filter(|(c, r)| *c < cols && *r < rows) {
+            let r = dot(l, col, row, 6.0);
+            self.ctx.stroke_rect(r.x, r.y, r.w, r.h);
+        }
+
+        // What the main view shows
+        let v = l.viewport;
+        self.ctx.set_stroke_style(&rgba(self.theme.viewport, 0.9));
+        self.ctx.set_line_width(1.0);
+        self.ctx.stroke_rect(v.x + 0.5, v.y + 0.5, (v.w - 1.0).max(1.0), (v.h - 1.0).max(1.0));
+    }
+
+    fn draw_message_area(
+        &mut self,
+        state: &GameState,
+        wall_ms: f64,
+        map_w: f64,
+        map_h: f64,
+        _text_h: f64,
+    ) {
+        let text_x = 0.0;
+        let text_y = map_h + TEXT_AREA_GAP;
+        let text_w = map_w;
+        let info_w = text_w * GAME_INFO_WIDTH_FRACTION;
+        let debug_w = text_w * DEBUG_WIDTH_FRACTION;
+        let debug_x = text_x + info_w;
+        let right_edge = text_x + text_w;
+        let panel_h = TEXT_AREA_ROWS as f64 * CELL_H;
+
+        // Panel background
+        self.ctx.set_fill_style(&rgb(self.theme.panel_bg));
+        self.ctx.fill_rect(text_x, text_y - MSG_SEPARATOR_OFFSET, text_w, panel_h + MSG_SEPARATOR_OFFSET);
+
+        // Top separator line
+        self.ctx.set_stroke_style(&rgb(self.theme.panel_border));
+        self.ctx.begin_path();
+        self.ctx.move_to(text_x, text_y - MSG_SEPARATOR_OFFSET);
+        self.ctx.line_to(text_x + text_w, text_y - MSG_SEPARATOR_OFFSET);
+        self.ctx.stroke();
+
+        // Section dividers
+        self.ctx.begin_path();
+        self.ctx.move_to(debug_x, text_y - MSG_SEPARATOR_OFFSET);
+        self.ctx.line_to(debug_x, text_y + panel_h);
+        self.ctx.move_to(debug_x + debug_w, text_y - MSG_SEPARATOR_OFFSET);
+        self.ctx.line_to(debug_x + debug_w, text_y + panel_h);
+        self.ctx.stroke();
+
+        self.ctx.set_font(FONT_MSG);
+        self.ctx.set_fill_style(&rgb(self.theme.text_primary));
+        self.ctx.set_text_baseline("top");
+
+        // --- Game Info region (left) ---
+        if !state.attention_message.is_empty() {
+            self.ctx.fill_text(
+                &state.attention_message,
+                text_x + MSG_LEFT_PADDING,
+                text_y + MSG_LINE_1_Y,
+            );
+        }
+
+        if !state.turn_message.is_empty() {
+            self.ctx.set_fill_style(&rgb(self.theme.text_secondary));
+            self.ctx.fill_text(
+                &state.turn_message,
+                text_x + MSG_LEFT_PADDING,
+                text_y + MSG_LINE_2_Y,
+            );
+            self.ctx.set_fill_style(&rgb(self.theme.text_primary));
+        } else if let Some(dest) = state.destination {
+            self.ctx.set_fill_style(&rgb(self.theme.text_secondary));
+            self.ctx.fill_text(
+                &format!("Dest: {},{}", dest.0, dest.1),
+                text_x + MSG_LEFT_PADDING,
+                text_y + MSG_LINE_2_Y,
+            );
+            self.ctx.set_fill_style(&rgb(self.theme.text_primary));
+        }
+
+        // Error notification banner with fade
+        if !state.error_message.is_empty() && wall_ms < state.error_until {
+            let remaining = state.error_until - wall_ms;
+            let total_duration = 10000.0;
+            let elapsed_frac = 1.0 - (remaining / total_duration);
+
+            let alpha = if elapsed_frac > 0.8 {
+                1.0 - (elapsed_frac - 0.8) / 0.2
+            } else {
+                1.0
+            };
+
+            if alpha > 0.01 {
+                let err_y = text_y + MSG_LINE_3_Y - 4.0;
+                let err_h = 22.0;
+
+                self.ctx.set_global_alpha(alpha * 0.1);
+                self.ctx.set_fill_style(&rgb(self.theme.error));
+                self.ctx.fill_rect(text_x, err_y, info_w, err_h);
+
+                self.ctx.set_global_alpha(alpha);
+                self.ctx.set_fill_style(&rgb(self.theme.error));
+                self.ctx.fill_rect(text_x, err_y, 3.0, err_h);
+
+                self.ctx.fill_text(
+                    &state.error_message,
+                    text_x + MSG_LEFT_PADDING,
+                    text_y + MSG_LINE_3_Y,
+                );
+                self.ctx.set_global_alpha(1.0);
+            }
+        }
+
+        // --- Debug region (center) ---
+        if !state.debug_message.is_empty() {
+            self.ctx.set_fill_style(&rgb(self.theme.debug));
+            let lines: Vec<&str> = state.debug_message.split('\n').collect();
+            let y_offsets = [MSG_LINE_1_Y, MSG_LINE_2_Y, MSG_LINE_3_Y];
+            let center_x = debug_x + debug_w / 2.0;
+            for (line, y_off) in lines.iter().take(3).zip(y_offsets.iter()) {
+                let msg_x = center_x - self.ctx.measure_text(line) / 2.0;
+                self.ctx.fill_text(line, msg_x, text_y + y_off);
+            }
+            self.ctx.set_fill_style(&rgb(self.theme.text_primary));
+        }
+
+        // --- Game Status region (right) ---
+        self.ctx.set_text_align("right");
+
+        let round_str = format!("Round: {}", state.round);
+        if state.paused || state.pause_requested {
+            let full_str = format!("PAUSED  {}", round_str);
+            let full_width = self.ctx.measure_text(&full_str);
+            let x = right_edge - full_width - MSG_LEFT_PADDING;
+            self.ctx.set_text_align("left");
+            self.ctx.set_fill_style(&rgb(self.theme.error));
+            self.ctx.fill_text("PAUSED  ", x, text_y + MSG_LINE_1_Y);
+            let paused_width = self.ctx.measure_text("PAUSED  ");
+            self.ctx.set_fill_style(&rgb(self.theme.text_primary));
+            self.ctx.fill_text(&round_str, x + paused_width, text_y + MSG_LINE_1_Y);
+            self.ctx.set_text_align("right");
+        } else {
+            self.ctx.fill_text(&round_str, right_edge - MSG_LEFT_PADDING, text_y + MSG_LINE_1_Y);
+        }
+
+        if !state.hover_message.is_empty() {
+            self.ctx.set_fill_style(&rgb(self.theme.text_secondary));
+            self.ctx.fill_text(&state.hover_message, right_edge - MSG_LEFT_PADDING, text_y + MSG_LINE_2_Y);
+            self.ctx.set_fill_style(&rgb(self.theme.text_primary));
+        }
+
+        if !state.production_status.is_empty() {
+            self.ctx.set_fill_style(&rgb(self.theme.text_secondary));
+            self.ctx.fill_text(&state.production_status, right_edge - MSG_LEFT_PADDING, text_y + MSG_LINE_3_Y);
+        }
+
+        self.ctx.set_text_align("left");
+        self.ctx.set_text_baseline("alphabetic");
+    }
+
+    fn draw_diagnostics(&mut self, state: &GameState, canvas_w: f64, map_h: f64) {
+        let seq = state.seq.map_or("-".to_string(), |s| s.to_string());
+        let mut line = format!(
+            "seq {}  unknown {}  malformed {}",
+            seq, state.unknown_messages, state.malformed_messages
+        );
+        if !state.last_decode_error.is_empty() {
+            line.push_str(&format!("  last: {}", state.last_decode_error));
+        }
+
+        self.ctx.set_font(FONT_MENU_HINT);
+        let line_h = 18.0;
+        let top = map_h - line_h;
+        self.ctx.set_global_alpha(0.85);
+        self.ctx.set_fill_style(&rgb(self.theme.panel_bg));
+        self.ctx.fill_rect(0.0, top, canvas_w, line_h);
+        self.ctx.set_global_alpha(1.0);
+
+        let color = if state.unknown_messages + state.malformed_messages > 0 {
+            self.theme.sentry
+        } else {
+            self.theme.debug
+        };
+        self.ctx.set_fill_style(&rgb(color));
+        self.ctx.set_text_baseline("middle");
+        self.ctx.fill_text(&line, MSG_LEFT_PADDING, top + line_h / 2.0);
+        self.ctx.set_text_baseline("alphabetic");
+    }
+
+    fn draw_tutorial_overlay(
+        &mut self,
+        tut: &crate::protocol::TutorialMsg,
+        l: &TutorialOverlayLayout,
+        hovered: Option<Control>,
+    ) {
+        let Rect { x: left, y: top, w: panel_w, h: panel_h } = l.panel;
+        let padding = l.padding;
+
+        // Panel background with transparency
+        self.ctx.set_global_alpha(0.92);
+        self.ctx.set_fill_style(&rgb(self.theme.panel_bg));
+        self.ctx.fill_rect(left, top, panel_w, panel_h);
+        self.ctx.set_global_alpha(1.0);
+
+        // Accent border (left edge)
+        self.ctx.set_fill_style(&rgb(self.theme.accent));
+        self.ctx.fill_rect(left, top, 3.0, panel_h);
+
+        // Border
+        self.ctx.set_stroke_style(&rgb(self.theme.panel_border));
+        self.ctx.set_line_width(1.0);
+        self.ctx.stroke_rect(left, top, panel_w, panel_h);
+
+        // Title
+        self.ctx.set_font(FONT_MENU_TITLE);
+        self.ctx.set_fill_style(&rgb(self.theme.accent));
+        self.ctx.set_text_baseline("top");
+        self.ctx.fill_text(
+            &tut.scenario_name,
+            left + padding + 4.0,
+            top + padding,
+        );
+
+        // Separator under title
+        self.ctx.set_stroke_style(&rgb(self.theme.panel_border));
+        self.ctx.begin_path();
+        self.ctx.move_to(left + padding, l.separator_y);
+        self.ctx.line_to(left + panel_w - padding, l.separator_y);
+        self.ctx.stroke();
+
+        // Page text (word-wrapped, 15px font)
+        let line_h = 20.0;
+        self.ctx.set_font(FONT_MENU_ITEM);
+        self.ctx.set_fill_style(&rgb(self.theme.text_primary));
+        for (i, line) in l.lines.iter().enumerate() {
+            self.ctx.fill_text(
+                line,
+                left + padding + 4.0,
+                l.text_top + i as f64 * line_h,
+            );
+        }
+
+        // Page number and navigation buttons
+        self.ctx.set_font(FONT_MENU_HINT);
+        self.ctx.set_fill_style(&rgb(self.theme.text_secondary));
+        self.ctx.fill_text(&l.page_label, left + padding + 4.0, l.nav_y);
+        self.draw_buttons(&l.buttons, hovered);
+
+        self.ctx.set_text_baseline("alphabetic");
+    }
+
+    fn draw_help_overlay(&mut self, l: &HelpLayout, hovered: Option<Control>) {
+        let Rect { x: left, y: top, w: panel_w, h: panel_h } = l.panel;
+        let padding = l.padding;
+        let line_h = 18.0;
+
+        // Panel background
+        self.ctx.set_global_alpha(0.90);
+        self.ctx.set_fill_style(&rgb(self.theme.panel_bg));
+        self.ctx.fill_rect(left, top, panel_w, panel_h);
+        self.ctx.set_global_alpha(1.0);
+
+        // Accent
+        self.ctx.set_fill_style(&rgb(self.theme.accent));
+        self.ctx.fill_rect(left, top, 3.0, panel_h);
+
+        // Border
+        self.ctx.set_stroke_style(&rgb(self.theme.panel_border));
+        self.ctx.set_line_width(1.0);
+        self.ctx.stroke_rect(left, top, panel_w, panel_h);
+
+        // Title
+        self.ctx.set_font(FONT_MENU_TITLE);
+        self.ctx.set_fill_style(&rgb(self.theme.accent));
+        self.ctx.set_text_baseline("top");
+        self.ctx.fill_text(&l.title, left + padding + 4.0, top + padding);
+
+        // Separator
+        self.ctx.set_stroke_style(&rgb(self.theme.panel_border));
+        self.ctx.begin_path();
+        self.ctx.move_to(left + padding, l.separator_y);
+        self.ctx.line_to(left + panel_w - padding, l.separator_y);
+        self.ctx.stroke();
+
+        // Tip text
+        self.ctx.set_font(FONT_MENU_ITEM);
+        self.ctx.set_fill_style(&rgb(self.theme.text_primary));
+        for (i, line) in l.tip_lines.iter().enumerate() {
+            self.ctx
+                .fill_text(line, left + padding + 4.0, l.tip_top + i as f64 * line_h);
+        }
+
+        // Selected section (only when a cell is selected)
+        if let Some(sel_top) = l.selected_top {
+            self.ctx.set_font(FONT_MENU_HINT);
+            self.ctx.set_fill_style(&rgb(self.theme.text_secondary));
+            self.ctx
+                .fill_text("Selected", left + padding + 4.0, sel_top);
+
+            self.ctx.set_font(FONT_MENU_ITEM);
+            self.ctx.set_fill_style(&rgb(self.theme.text_primary));
+            for (i, line) in l.selected_lines.iter().enumerate() {
+                self.ctx
+                    .fill_text(line, left + padding + 4.0, l.selected_lines_top + i as f64 * line_h);
+            }
+        }
+
+        // Controls section
+        self.ctx.set_fill_style(&rgb(self.theme.text_secondary));
+        for (i, line) in l.controls_lines.iter().enumerate() {
+            self.ctx
+                .fill_text(line, left + padding + 4.0, l.controls_top + i as f64 * line_h);
+        }
+
+        // Hide / inspector buttons
+        self.ctx.set_font(FONT_MENU_HINT);
+        self.draw_buttons(&l.buttons, hovered);
+
+        self.ctx.set_text_baseline("alphabetic");
+    }
+
+    fn draw_tutorial_menu(
+        &mut self,
+        menu: &crate::protocol::TutorialMenuMsg,
+        l: &MenuLayout,
+        hovered: Option<Control>,
+        screen_w: f64,
+        screen_h: f64,
+    ) {
+        let Rect { x: left, y: top, w: menu_w, h: menu_h } = l.panel;
+        let padding = l.padding;
+        let content_top = l.content_top;
+
+        // Dark overlay
+        self.ctx.set_global_alpha(0.85);
+        self.ctx.set_fill_style(&rgb(self.theme.canvas_bg));
+        self.ctx.fill_rect(0.0, 0.0, screen_w, screen_h);
+        self.ctx.set_global_alpha(1.0);
+
+        // Dialog shadow
+        self.ctx.set_fill_style(&rgba(self.theme.shadow, 0.3));
+        self.ctx.fill_rect(left + 4.0, top + 4.0, menu_w, menu_h);
+
+        // Dialog background
+        self.ctx.set_fill_style(&rgb(self.theme.panel_bg));
+        self.ctx.set_stroke_style(&rgb(self.theme.panel_border));
+        self.ctx.set_line_width(1.0);
+        self.ctx.fill_rect(left, top, menu_w, menu_h);
+        self.ctx.stroke_rect(left, top, menu_w, menu_h);
+
+        // Title
+        self.ctx.set_font(FONT_MENU_TITLE);
+        self.ctx.set_fill_style(&rgb(self.theme.accent));
+        self.ctx.set_text_baseline("top");
+        self.ctx.fill_text("Tutorial Scenarios", left + padding, top + padding);
+
+        // Title separator
+        self.ctx.set_stroke_style(&rgb(self.theme.panel_border));
+        self.ctx.begin_path();
+        self.ctx.move_to(left + padding, content_top - 4.0);
+        self.ctx.line_to(left + menu_w - padding, content_top - 4.0);
+        self.ctx.stroke();
+
+        // Scenario list
+        for ((idx, scenario), item) in menu.scenarios.iter().enumerate().zip(&l.items) {
+            let is_hovered = hovered == Some(Control::TutorialMenuItem(idx));
+            if is_hovered {
+                self.ctx.set_fill_style(&rgb(self.theme.hover_bg));
+                self.ctx.fill_rect(item.x + 1.0, item.y, item.w - 2.0, item.h);
+            }
+
+            // Scenario number + name
+            let name_color = if is_hovered { self.theme.hover_text } else { self.theme.text_primary };
+            self.ctx.set_font(FONT_MENU_ITEM);
+            self.ctx.set_fill_style(&rgb(name_color));
+            let label = format!("{}. {}", idx + 1, scenario.name);
+            self.ctx.fill_text(&label, left + padding, item.y + 8.0);
+
+            // Description
+            let desc_color = if is_hovered { self.theme.hover_text_secondary } else { self.theme.text_secondary };
+            self.ctx.set_font(FONT_MENU_HINT);
+            self.ctx.set_fill_style(&rgb(desc_color));
+            self.ctx.fill_text(&scenario.description, left + padding + 20.0, item.y + 28.0);
+        }
+
+        // Hint
+        self.ctx.set_font(FONT_MENU_HINT);
+        self.ctx.set_fill_style(&rgb(self.theme.text_secondary));
+        self.ctx.fill_text(
+            "Click to start  |  Press ESC to close",
+            left + padding,
+            top + menu_h - padding,
+        );
+
+        self.ctx.set_text_baseline("alphabetic");
+    }
+
+    fn draw_inspector_panel(&mut self, l: &InspectorLayout, hovered: Option<Control>) {
+        let Rect { x: left, y: top, w: panel_w, h: panel_h } = l.panel;
+        let padding = l.padding;
+        let line_h = 18.0;
+
+        // Panel background
+        self.ctx.set_global_alpha(0.92);
+        self.ctx.set_fill_style(&rgb(self.theme.panel_bg));
+        self.ctx.fill_rect(left, top, panel_w, panel_h);
+        self.ctx.set_global_alpha(1.0);
+
+        // Accent
+        self.ctx.set_fill_style(&rgb(self.theme.accent));
+        self.ctx.fill_rect(left, top, 3.0, panel_h);
+
+        // Border
+        self.ctx.set_stroke_style(&rgb(self.theme.panel_border));
+        self.ctx.set_line_width(1.0);
+        self.ctx.stroke_rect(left, top, panel_w, panel_h);
+
+        // Title
+        self.ctx.set_font(FONT_MENU_TITLE);
+        self.ctx.set_fill_style(&rgb(self.theme.accent));
+        self.ctx.set_text_baseline("top");
+        self.ctx.fill_text("Inspector", left + padding + 4.0, top + padding);
+
+        // Body
+        self.ctx.set_font(FONT_MENU_ITEM);
+        self.ctx.set_fill_style(&rgb(self.theme.text_primary));
+        for (i, line) in l.lines.iter().enumerate() {
+            self.ctx
+                .fill_text(line, left + padding + 4.0, l.text_top + i as f64 * line_h);
+        }
+
+        // Hide button
+        self.ctx.set_font(FONT_MENU_HINT);
+        self.draw_buttons(&l.buttons, hovered);
+
+        self.ctx.set_text_baseline("alphabetic");
+    }
+
+    fn draw_load_menu(
+        &mut self,
+        menu: &crate::protocol::LoadMenuMsg,
+        l: &MenuLayout,
+        hovered: Option<Control>,
+        screen_w: f64,
+        screen_h: f64,
+    ) {
+        let Rect { x: left, y: top, w: menu_w, h: menu_h } = l.panel;
+        let padding = l.padding;
+        let content_top = l.content_top;
+        // Local pointer hover wins over the server's idea of it
+        let hovered = match hovered {
+            Some(Control::LoadMenuItem(idx)) => Some(idx),
+            _ => menu.hovered,
+        };
+
+        // Dark overlay
+        self.ctx.set_global_alpha(0.85);
+        self.ctx.set_fill_style(&rgb(self.theme.canvas_bg));
+        self.ctx.fill_rect(0.0, 0.0, screen_w, screen_h);
+        self.ctx.set_global_alpha(1.0);
+
+        // Dialog shadow
+        self.ctx.set_fill_style(&rgba(self.theme.shadow, 0.3));
+        self.ctx.fill_rect(left + 4.0, top + 4.0, menu_w, menu_h);
+
+        // Dialog background
+        self.ctx.set_fill_style(&rgb(self.theme.panel_bg));
+        self.ctx.set_stroke_style(&rgb(self.theme.panel_border));
+        self.ctx.set_line_width(1.0);
+        self.ctx.fill_rect(left, top, menu_w, menu_h);
+        self.ctx.stroke_rect(left, top, menu_w, menu_h);
+
+        // Title
+        self.ctx.set_font(FONT_MENU_TITLE);
+        self.ctx.set_fill_style(&rgb(self.theme.text_primary));
+        self.ctx.set_text_baseline("top");
+        self.ctx.fill_text("Load Game", left + padding, top + padding);
+
+        // Title separator
+        self.ctx.set_stroke_style(&rgb(self.theme.panel_border));
+        self.ctx.begin_path();
+        self.ctx.move_to(left + padding, content_top - 4.0);
+        self.ctx.line_to(left + menu_w - padding, content_top - 4.0);
+        self.ctx.stroke();
+
+        // File list
+        self.ctx.set_font(FONT_MENU_ITEM);
+        if menu.files.is_empty() {
+            self.ctx.set_fill_style(&rgb(self.theme.text_secondary));
+            self.ctx.fill_text("No saved games found", left + padding, content_top + 10.0);
+        } else {
+            for ((idx, filename), item) in menu.files.iter().enumerate().zip(&l.items) {
+                if hovered == Some(idx) {
+                    self.ctx.set_fill_style(&rgb(self.theme.hover_bg));
+                    self.ctx.fill_rect(item.x + 1.0, item.y, item.w - 2.0, item.h);
+                    self.ctx.set_fill_style(&rgb(self.theme.hover_text));
+                    self.ctx.fill_text(filename, left + padding, item.y + 10.0);
+                } else {
+                    self.ctx.set_fill_style(&rgb(self.theme.text_primary));
+                    self.ctx.fill_text(filename, left + padding, item.y + 10.0);
+                }
+            }
+        }
+
+        // ESC hint
+        self.ctx.set_font(FONT_MENU_HINT);
+        self.ctx.set_fill_style(&rgb(self.theme.text_secondary));
+        self.ctx.fill_text("Press ESC to close", left + padding, top + menu_h - padding);
+        self.ctx.set_text_baseline("alphabetic");
+    }
+
+    /// Draw the replay controls along the bottom: buttons, a seek track
+    /// with a tick per recorded input, and the playback time.
+    fn draw_replay_bar(&mut self, replay: &ReplayStatus, l: &ReplayBarLayout, hovered: Option<Control>) {
+        let Rect { x: left, y: top, w: bar_w, h: bar_h } = l.panel;
+        self.ctx.set_fill_style(&rgb(self.theme.panel_bg));
+        self.ctx.fill_rect(left, top, bar_w, bar_h);
+        self.ctx.set_stroke_style(&rgb(self.theme.panel_border));
+        self.ctx.set_line_width(1.0);
+        self.ctx.begin_path();
+        self.ctx.move_to(left, top);
+        self.ctx.line_to(left + bar_w, top);
+        self.ctx.stroke();
+
+        self.ctx.set_font(FONT_MENU_ITEM);
+        self.ctx.set_text_baseline("top");
+        self.draw_buttons(&l.buttons, hovered);
+
+        // Track, played portion, then a tick per recorded input
+        let track = l.track;
+        let mid = track.y + track.h / 2.0;
+        let head_x = l.x_at(replay.time_ms, replay.duration_ms);
+        let track_bg = if hovered == Some(Control::ReplaySeek) { self.theme.text_secondary } else { self.theme.panel_border };
+        self.ctx.set_fill_style(&rgb(track_bg));
+        self.ctx.fill_rect(track.x, mid - 2.0, track.w, 4.0);
+        self.ctx.set_fill_style(&rgb(self.theme.accent));
+        self.ctx.fill_rect(track.x, mid - 2.0, head_x - track.x, 4.0);
+        self.ctx.set_fill_style(&rgba(self.theme.sentry, 0.7));
+        let mut last_x = f64::NAN;
+        for &t in &replay.inputs {
+            let x = l.x_at(t, replay.duration_ms).floor();
+            if x != last_x {
+                self.ctx.fill_rect(x, track.y, 1.0, 4.0);
+                last_x = x;
+            }
+        }
+        self.ctx.set_fill_style(&rgb(self.theme.text_primary));
+        self.ctx.fill_rect(head_x - 1.5, track.y + 2.0, 3.0, track.h - 4.0);
+
+        self.ctx.set_fill_style(&rgb(self.theme.text_secondary));
+        self.ctx.fill_text(&l.time_label, l.label_x, l.text_y);
+        self.ctx.set_text_baseline("alphabetic");
+    }
+
+    /// Draw text buttons in the current font with a top baseline; the
+    /// hovered one gets a highlight.
+    fn draw_buttons(&mut self, buttons: &[Button], hovered: Option<Control>) {
+        for button in buttons {
+            if hovered == Some(button.control) {
+                let r = button.rect;
+                self.ctx.set_fill_style(&rgba(self.theme.hover_bg, 0.35));
+                self.ctx.fill_rect(r.x, r.y, r.w, r.h);
+                self.ctx.set_fill_style(&rgb(self.theme.text_primary));
+            } else {
+                self.ctx.set_fill_style(&rgb(self.theme.text_secondary));
+            }
+            self.ctx.fill_text(&button.label, button.x, button.y);
+        }
+    }
+}
+
+// --- Helper functions ---
+
+fn pulse(now: f64, period_ms: f64) -> f64 {
+    0.5 + 0.5 * (now * 2.0 * std::f64::consts::PI / period_ms).sin()
+}
+
+fn lerp_color(a: Color, b: Color, t: f64) -> Color {
+    [
+        (a[0] as f64 + (b[0] as f64 - a[0] as f64) * t) as u8,
+        (a[1] as f64 + (b[1] as f64 - a[1] as f64) * t) as u8,
+        (a[2] as f64 + (b[2] as f64 - a[2] as f64) * t) as u8,
+    ]
+}
+
+fn brighten(color: Color, factor: f64) -> Color {
+    [
+        (color[0] as f64 * factor).min(255.0) as u8,
+        (color[1] as f64 * factor).min(255.0) as u8,
+        (color[2] as f64 * factor).min(255.0) as u8,
+    ]
+}
+
+fn darken(color: Color, factor: f64) -> Color {
+    [
+        (color[0] as f64 * factor) as u8,
+        (color[1] as f64 * factor) as u8,
+        (color[2] as f64 * factor) as u8,
+    ]
+}
+
+fn rgb(color: Color) -> String {
+    format!("rgb({},{},{})", color[0], color[1], color[2])
+}
+
+fn rgba(color: Color, alpha: f64) -> String {
+    format!("rgba({},{},{},{})", color[0], color[1], color[2], alpha)
+}
+
+/// Colour the terrain layer paints a cell with, or `None` for fog.
+fn terrain_color(state: &GameState, col: usize, row: usize) -> Option<Color> {
+    match state.cells.get(col).and_then(|c| c.get(row)) {
+        Some(Some(cell)) if cell.t != Terrain::Unexplored => Some(cell_color(&state.theme, cell)),
+        _ => None,
+    }
+}
+
+/// One cell of terrain: the fog checkerboard (`light` picks its square),
+/// or the terrain colour with its depth shading.
+fn paint_terrain_cell(ctx: &mut impl DrawTarget, theme: &Theme, cell: Rect, light: bool, color: Option<Color>) {
+    match color {
+        None => {
+            ctx.set_fill_style(&rgb(if light { theme.fog_light } else { theme.fog_dark }));
+            ctx.fill_rect(cell.x, cell.y, cell.w, cell.h);
+        }
+        Some(color) => {
+            ctx.set_fill_style(&rgb(color));
+            ctx.fill_rect(cell.x, cell.y, cell.w, cell.h);
+            draw_cell_depth(ctx, cell, color);
+        }
+    }
+}
+
+/// Top highlight and bottom shadow lines that give a cell some depth.
+fn draw_cell_depth(ctx: &mut impl DrawTarget, cell: Rect, base: Color) {
+    let highlight = brighten(base, 1.25);
+    ctx.set_stroke_style(&rgba(highlight, 0.4));
+    ctx.begin_path();
+    ctx.move_to(cell.x + 0.5, cell.y + 0.5);
+    ctx.line_to(cell.right() - 0.5, cell.y + 0.5);
+    ctx.stroke();
+
+    let shadow = darken(base, 0.6);
+    ctx.set_stroke_style(&rgba(shadow, 0.4));
+    ctx.begin_path();
+    ctx.move_to(cell.x + 0.5, cell.bottom() - 0.5);
+    ctx.line_to(cell.right() - 0.5, cell.bottom() - 0.5);
+    ctx.stroke();
+}
+
+/// A line from `from` ending in a filled arrowhead `head` px long at `to`.
+fn draw_arrow(ctx: &mut impl DrawTarget, from: (f64, f64), to: (f64, f64), head: f64, style: &str) {
+    let ((x0, y0), (x1, y1)) = (from, to);
+    let len = (x1 - x0).hypot(y1 - y0);
+    if len <= head {
+        return;
+    }
+    let (ux, uy) = ((x1 - x0) / len, (y1 - y0) / len);
+    let (bx, by) = (x1 - ux * head, y1 - uy * head);
+    let half = head * 0.6;
+
+    ctx.set_stroke_style(style);
+    ctx.set_line_width(2.0);
+    ctx.begin_path();
+    ctx.move_to(x0, y0);
+    ctx.line_to(bx, by);
+    ctx.stroke();
+    ctx.set_line_width(1.0);
+
+    ctx.set_fill_style(style);
+    ctx.begin_path();
+    ctx.move_to(x1, y1);
+    ctx.line_to(bx - uy * half, by + ux * half);
+    ctx.line_to(bx + uy * half, by - ux * half);
+    ctx.close_path();
+    ctx.fill();
+}
+
+/// A circle of `radius` round `(cx, cy)`, stroked as a polygon since draw
+/// targets have no arcs.
+fn draw_ring(ctx: &mut impl DrawTarget, cx: f64, cy: f64, radius: f64) {
+    const SEGMENTS: usize = 24;
+    ctx.begin_path();
+    for i in 0..SEGMENTS {
+        let a = i as f64 * std::f64::consts::TAU / SEGMENTS as f64;
+        let (x, y) = (cx + radius * a.cos(), cy + radius * a.sin());
+        if i == 0 {
+            ctx.move_to(x, y);
+        } else {
+            ctx.line_to(x, y);
+        }
+    }
+    ctx.close_path();
+    ctx.stroke();
+}
+
+/// Whether a cell lies wholly inside the map view.
+fn in_view(cell: Rect, map_w: f64, map_h: f64) -> bool {
+    cell.x >= 0.0 && cell.y >= 0.0 && cell.right() <= map_w && cell.bottom() <= map_h
+}
+
+fn cell_font(zoom: f64) -> String {
+    format!("bold {}px 'JetBrains Mono', 'Fira Code', 'Courier New', monospace", CELL_FONT_PX * zoom)
+}
+
+fn badge_font(zoom: f64) -> String {
+    format!("bold {}px 'JetBrains Mono', 'Fira Code', 'Courier New', monospace", BADGE_FONT_PX * zoom)
+}
+
+/// Background colour of a map cell by terrain, city owner and continent.
+pub fn cell_color(theme: &Theme, cell: &CellMsg) -> Color {
+    match cell.t {
+        Terrain::City => match cell.cs {
+            Some(CityStatus::Player) => theme.player_city,
+            Some(CityStatus::Computer) => theme.computer_city,
+            Some(CityStatus::Free) => theme.free_city,
+            _ => theme.free_city,
+        },
+        Terrain::Land => {
+            if let Some(cid) = cell.cid {
+                theme.land_shades[cid as usize % theme.land_shades.len()]
+            } else {
+                theme.land
+            }
+        }
+        Terrain::Sea => theme.sea,
+        _ => theme.unexplored,
+    }
+}
+
+/// Glyph colour of a unit, by its mode.
+pub fn unit_color(theme: &Theme, unit: &crate::protocol::UnitMsg) -> Color {
+    let color = if unit.o == Owner::Computer && unit.t == UnitKind::Army {
+        theme.awake
+    } else if unit.transport_mission == Some(TransportMission::Loading) {
+        theme.sleeping
+    } else {
+        match unit.m {
+            UnitMode::Awake => theme.awake,
+            UnitMode::Sentry => theme.sentry,
+            UnitMode::Explore | UnitMode::CoastlineFollow => theme.explore,
+            _ => theme.sleeping,
+        }
+    };
+    // Damaged units are tinted red so they stand out for repair
+    if damage(unit).is_some() {
+        lerp_color(color, theme.error, DAMAGE_TINT)
+    } else {
+        color
+    }
+}
+
+/// Remaining hits along the bottom of a damaged unit's cell: one pip per
+/// hit when there is room, otherwise a continuous bar.
+fn draw_hit_bar(ctx: &mut impl DrawTarget, theme: &Theme, cell: Rect, zoom: f64, hits: u32, max: u32) {
+    let x = cell.x + 2.0 * zoom;
+    let y = cell.bottom() - 3.0 * zoom;
+    let w = cell.w - 4.0 * zoom;
+    let h = 2.0 * zoom;
+
+    ctx.set_fill_style(&rgba(theme.shadow, 0.6));
+    ctx.fill_rect(x - 0.5, y - 0.5, w + 1.0, h + 1.0);
+    ctx.set_fill_style(&rgb(lerp_color(theme.error, theme.explore, hits as f64 / max as f64)));
+    let gap = if w / max as f64 >= 3.0 { 1.0 } else { 0.0 };
+    if gap > 0.0 {
+        let pip_w = (w - gap * (max - 1) as f64) / max as f64;
+        for i in 0..hits {
+            ctx.fill_rect(x + i as f64 * (pip_w + gap), y, pip_w, h);
+        }
+    } else {
+        ctx.fill_rect(x, y, w * hits as f64 / max as f64, h);
+    }
+}
+
+fn draw_text_shadow(ctx: &mut impl DrawTarget, theme: &Theme, text: &str, x: f64, y: f64, color: Color) {
+    ctx.set_fill_style(&rgba(theme.shadow, 0.6));
+    ctx.fill_text(text, x + 1.0, y + 1.0);
+    ctx.set_fill_style(&rgb(color));
+    ctx.fill_text(text, x, y);
+}
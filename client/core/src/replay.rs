@@ -0,0 +1,416 @@
+//! Recording of a session's traffic and playback through `GameState`
+//! without a socket.
+//!
+//! A recording is JSON lines: a header, then one line per frame with its
+//! time in ms since recording started and its direction:
+//!
+//! ```text
+//! {"type":"recording","version":1,"started":1760000000000}
+//! {"t":0,"dir":"in","frame":{"type":"state",...}}
+//! {"t":412.5,"dir":"out","frame":{"type":"key",...}}
+//! ```
+//!
+//! Received frames are stored as sent, so a recording stays replayable
+//! when it contains messages this client doesn't know.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::protocol::{ClientMessage, ServerMessage};
+use crate::state::GameState;
+
+pub const RECORDING_VERSION: u32 = 1;
+
+/// Playback speeds, cycled by the speed control.
+pub const SPEEDS: [f64; 6] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
+const NORMAL_SPEED: usize = 2;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    In,
+    Out,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Header {
+    #[serde(rename = "type")]
+    kind: String,
+    version: u32,
+    started: f64,
+}
+
+/// Collects frames while recording; `to_jsonl` produces the file.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    started_wall_ms: f64,
+    started_ms: f64,
+    lines: Vec<String>,
+}
+
+impl Recorder {
+    /// `wall_ms` is the Unix epoch time and `now_ms` the monotonic clock
+    /// that later frames are stamped with.
+    pub fn new(wall_ms: f64, now_ms: f64) -> Self {
+        Self { started_wall_ms: wall_ms, started_ms: now_ms, lines: Vec::new() }
+    }
+
+    /// Record a frame from the server, exactly as received. Frames that
+    /// aren't JSON at all are dropped, as they could not be replayed.
+    pub fn record_received(&mut self, now_ms: f64, text: &str) {
+        if !text.contains('\n') && serde_json::from_str::<serde::de::IgnoredAny>(text).is_ok() {
+            self.push(now_ms, Direction::In, text.trim());
+        } else if let Ok(frame) = serde_json::from_str::<Value>(text) {
+            // Keep one frame per line
+            self.push(now_ms, Direction::In, &frame.to_string());
+        }
+    }
+
+    pub fn record_sent(&mut self, now_ms: f64, msg: &ClientMessage) {
+        if let Ok(text) = serde_json::to_string(msg) {
+            self.push(now_ms, Direction::Out, &text);
+        }
+    }
+
+    fn push(&mut self, now_ms: f64, dir: Direction, frame: &str) {
+        let t = (now_ms - self.started_ms).max(0.0);
+        let dir = if dir == Direction::In { "in" } else { "out" };
+        self.lines.push(format!(r#"{{"t":{},"dir":"{}","frame":{}}}"#, t, dir, frame));
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn to_jsonl(&self) -> String {
+        let header = Header { kind: "recording".to_string(), version: RECORDING_VERSION, started: self.started_wall_ms };
+        let mut out = serde_json::to_string(&header).unwrap_or_default();
+        out.push('\n');
+        for line in &self.lines {
+            out.push_str(line);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// One recorded frame.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct RecordedFrame {
+    pub t: f64,
+    pub dir: Direction,
+    pub frame: Value,
+}
+
+impl RecordedFrame {
+    fn is_full_state(&self) -> bool {
+        self.dir == Direction::In && self.frame.get("type").and_then(Value::as_str) == Some("state")
+    }
+}
+
+/// A parsed recording, frames in time order.
+#[derive(Debug, Clone, Default)]
+pub struct Recording {
+    pub started_wall_ms: f64,
+    pub frames: Vec<RecordedFrame>,
+}
+
+impl Recording {
+    /// Parse a recording. Errors carry the 1-based line number.
+    pub fn parse(text: &str) -> Result<Recording, String> {
+        let mut recording = Recording::default();
+        for (i, line) in text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            let err = |err: serde_json::Error| format!("line {}: {}", i + 1, err);
+            let header = serde_json::from_str::<Header>(line).ok().filter(|h| h.kind == "recording");
+            if let Some(header) = header.filter(|_| recording.frames.is_empty()) {
+                if header.version > RECORDING_VERSION {
+                    return Err(format!("recording version {} is newer than this client", header.version));
+                }
+                recording.started_wall_ms = header.started;
+                continue;
+            }
+            recording.frames.push(serde_json::from_str(line).map_err(err)?);
+        }
+        if !recording.frames.iter().any(|f| f.dir == Direction::In) {
+            return Err("recording has no server frames".to_string());
+        }
+        recording.frames.sort_by(|a, b| a.t.total_cmp(&b.t));
+        Ok(recording)
+    }
+
+    pub fn duration_ms(&self) -> f64 {
+        self.frames.last().map_or(0.0, |f| f.t)
+    }
+}
+
+/// Playback position, kept on `GameState` so layout and rendering can
+/// draw the replay controls.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ReplayStatus {
+    pub time_ms: f64,
+    pub duration_ms: f64,
+    pub playing: bool,
+    pub speed: f64,
+    /// Times of the messages the recorded client sent.
+    pub inputs: Vec<f64>,
+}
+
+/// Feeds a recording into a `GameState` as if it came off the socket.
+#[derive(Debug, Clone)]
+pub struct Player {
+    recording: Recording,
+    /// Index of the next frame to apply.
+    next: usize,
+    time_ms: f64,
+    playing: bool,
+    speed: usize,
+}
+
+impl Player {
+    /// Start playback paused at the first server frame, and mark `state`
+    /// as replaying.
+    pub fn new(recording: Recording, state: &mut GameState) -> Self {
+        let first = recording.frames.iter().find(|f| f.dir == Direction::In).map_or(0.0, |f| f.t);
+        state.connected = true;
+        state.stale = false;
+        state.replay = Some(ReplayStatus {
+            duration_ms: recording.duration_ms(),
+            inputs: recording.frames.iter().filter(|f| f.dir == Direction::Out).map(|f| f.t).collect(),
+            ..ReplayStatus::default()
+        });
+        let mut player = Self { recording, next: 0, time_ms: 0.0, playing: false, speed: NORMAL_SPEED };
+        player.seek(first, state);
+        player
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn time_ms(&self) -> f64 {
+        self.time_ms
+    }
+
+    /// Wall-clock time at the playhead, for comparing against server
+    /// deadlines such as `error_until`.
+    pub fn wall_ms(&self) -> f64 {
+        self.recording.started_wall_ms + self.time_ms
+    }
+
+    /// Move the playhead on by `dt_ms` of real time while playing.
+    pub fn advance(&mut self, dt_ms: f64, state: &mut GameState) {
+        if !self.playing {
+            return;
+        }
+        let end = self.recording.duration_ms();
+        self.time_ms = (self.time_ms + dt_ms * SPEEDS[self.speed]).min(end);
+        self.apply_until(self.time_ms, state);
+        if self.time_ms >= end {
+            self.playing = false;
+        }
+        self.sync(state);
+    }
+
+    pub fn toggle_playing(&mut self, state: &mut GameState) {
+        if !self.playing && self.time_ms >= self.recording.duration_ms() {
+            // Play again from the start
+            self.seek(0.0, state);
+        }
+        self.playing = !self.playing;
+        self.sync(state);
+    }
+
+    /// Pause and apply the next server frame.
+    pub fn step(&mut self, state: &mut GameState) {
+        self.playing = false;
+        let next_in = self.recording.frames[self.next..].iter().position(|f| f.dir == Direction::In);
+        if let Some(offset) = next_in {
+            self.time_ms = self.recording.frames[self.next + offset].t;
+            self.apply_until(self.time_ms, state);
+        }
+        self.sync(state);
+    }
+
+    pub fn cycle_speed(&mut self, state: &mut GameState) {
+        self.speed = (self.speed + 1) % SPEEDS.len();
+        self.sync(state);
+    }
+
+    /// Jump to `t_ms`. Going backwards replays from the last full state
+    /// at or before the target, since patches only apply in order.
+    pub fn seek(&mut self, t_ms: f64, state: &mut GameState) {
+        let t_ms = t_ms.clamp(0.0, self.recording.duration_ms());
+        // Seeking skips over whatever happened in between
+        state.break_continuity();
+        if t_ms < self.time_ms || self.next == 0 {
+            let end = self.recording.frames.partition_point(|f| f.t <= t_ms);
+            self.next = self.recording.frames[..end].iter().rposition(RecordedFrame::is_full_state).unwrap_or(0);
+        }
+        self.time_ms = t_ms;
+        self.apply_until(t_ms, state);
+        self.sync(state);
+    }
+
+    fn apply_until(&mut self, t_ms: f64, state: &mut GameState) {
+        while let Some(frame) = self.recording.frames.get(self.next).filter(|f| f.t <= t_ms) {
+            if frame.dir == Direction::In {
+                match serde_json::from_value::<ServerMessage>(frame.frame.clone()) {
+                    Ok(msg) => state.apply_message(msg),
+                    Err(err) => {
+                        state.malformed_messages += 1;
+                        state.last_decode_error = err.to_string();
+                    }
+                }
+            }
+            self.next += 1;
+        }
+        // Nothing to resync from but the recording itself
+        state.take_resync_request();
+    }
+
+    fn sync(&self, state: &mut GameState) {
+        if let Some(status) = state.replay.as_mut() {
+            status.time_ms = self.time_ms;
+            status.playing = self.playing;
+            status.speed = SPEEDS[self.speed];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::icons::UnitStyle;
+    use crate::theme::Theme;
+
+    fn state_frame(round: u32, seq: u64) -> String {
+        serde_json::json!({
+            "type": "state", "seq": seq, "map_size": [1, 1], "cells": [[{"t": "sea"}]], "round": round,
+            "paused": false, "pause_requested": false, "waiting_for_input": false,
+            "attention_coords": [], "attention_message": "", "turn_message": "",
+            "error_message": "", "error_until": 0, "hover_message": "",
+            "selected_cell": null, "production_status": "", "destination": null,
+            "map_to_display": "player-map", "debug_message": "", "load_menu": null,
+            "tutorial": null, "tutorial_menu": null, "tips": null
+        })
+        .to_string()
+    }
+
+    fn patch_frame(round: u32, seq: u64) -> String {
+        format!(r#"{{"type":"patch","seq":{},"cells":[],"round":{}}}"#, seq, round)
+    }
+
+    /// A state at 0 ms, patches at 100 and 200, a key at 250, a state at 300.
+    fn recording() -> Recording {
+        let mut rec = Recorder::new(1_700_000_000_000.0, 5000.0);
+        rec.record_received(5000.0, &state_frame(1, 1));
+        rec.record_received(5100.0, &patch_frame(2, 2));
+        rec.record_received(5200.0, &patch_frame(3, 3));
+        rec.record_sent(5250.0, &ClientMessage::KeyUp);
+        rec.record_received(5300.0, &state_frame(4, 4));
+        Recording::parse(&rec.to_jsonl()).unwrap()
+    }
+
+    #[test]
+    fn recorder_round_trips() {
+        let rec = recording();
+        assert_eq!(rec.started_wall_ms, 1_700_000_000_000.0);
+        assert_eq!(rec.frames.len(), 5);
+        assert_eq!(rec.duration_ms(), 300.0);
+        assert_eq!(rec.frames[3].dir, Direction::Out);
+        assert_eq!(rec.frames[3].frame, serde_json::json!({"type": "key_up"}));
+    }
+
+    #[test]
+    fn recorder_drops_frames_that_are_not_json() {
+        let mut rec = Recorder::new(0.0, 0.0);
+        rec.record_received(1.0, "not json");
+        rec.record_received(2.0, r#"{"type":"from_the_future"}"#);
+        rec.record_received(3.0, "{\n  \"type\": \"welcome\"\n}");
+        assert_eq!(rec.frame_count(), 2);
+        assert_eq!(rec.to_jsonl().lines().count(), 3);
+    }
+
+    #[test]
+    fn rejects_bad_recordings() {
+        assert_eq!(Recording::parse("").unwrap_err(), "recording has no server frames");
+        assert!(Recording::parse("{\"t\":0}\n").unwrap_err().starts_with("line 1:"));
+        assert_eq!(
+            Recording::parse(r#"{"type":"recording","version":99,"started":0}"#).unwrap_err(),
+            "recording version 99 is newer than this client"
+        );
+    }
+
+    #[test]
+    fn plays_at_speed_and_stops_at_the_end() {
+        let mut state = GameState::new();
+        let mut player = Player::new(recording(), &mut state);
+        assert_eq!((state.round, player.is_playing()), (1, false));
+        assert!(state.connected && !state.stale);
+        assert_eq!(state.replay.as_ref().unwrap().inputs, vec![250.0]);
+
+        player.advance(1000.0, &mut state);
+        assert_eq!(state.round, 1, "paused players don't move");
+
+        player.toggle_playing(&mut state);
+        player.advance(150.0, &mut state);
+        assert_eq!(state.round, 2);
+        player.cycle_speed(&mut state);
+        player.advance(50.0, &mut state);
+        assert_eq!((state.round, player.time_ms()), (3, 250.0));
+        assert_eq!(state.replay.as_ref().unwrap().speed, 2.0);
+
+        player.advance(1000.0, &mut state);
+        assert_eq!((state.round, player.time_ms(), player.is_playing()), (4, 300.0, false));
+        assert_eq!(player.wall_ms(), 1_700_000_000_300.0);
+    }
+
+    #[test]
+    fn steps_one_server_frame_at_a_time() {
+        let mut state = GameState::new();
+        let mut player = Player::new(recording(), &mut state);
+        player.toggle_playing(&mut state);
+        player.step(&mut state);
+        assert_eq!((state.round, player.time_ms(), player.is_playing()), (2, 100.0, false));
+        player.step(&mut state);
+        player.step(&mut state);
+        assert_eq!((state.round, player.time_ms()), (4, 300.0), "steps over sent frames");
+        player.step(&mut state);
+        assert_eq!(state.round, 4);
+    }
+
+    #[test]
+    fn loading_a_recording_keeps_view_settings() {
+        let mut state = GameState::new();
+        state.apply_frame(&state_frame(9, 40)).unwrap();
+        state.theme = Theme::light();
+        state.unit_style = UnitStyle::Icons;
+        state.camera.zoom_at(2, 0.0, 0.0);
+        let zoom = state.camera.zoom();
+
+        state.reset_game();
+        Player::new(recording(), &mut state);
+        assert_eq!((state.round, state.seq), (1, Some(1)));
+        assert_eq!(state.theme, Theme::light());
+        assert_eq!(state.unit_style, UnitStyle::Icons);
+        assert_eq!(state.camera.zoom(), zoom);
+    }
+
+    #[test]
+    fn seeking_back_replays_from_the_last_full_state() {
+        let mut state = GameState::new();
+        let mut player = Player::new(recording(), &mut state);
+        player.seek(300.0, &mut state);
+        assert_eq!(state.round, 4);
+        player.seek(150.0, &mut state);
+        assert_eq!((state.round, state.seq), (2, Some(2)));
+        assert!(!state.awaiting_resync);
+        player.seek(-5.0, &mut state);
+        assert_eq!((state.round, player.time_ms()), (1, 0.0));
+
+        // Playing again after the end starts over
+        player.seek(300.0, &mut state);
+        player.toggle_playing(&mut state);
+        assert_eq!((state.round, player.time_ms(), player.is_playing()), (1, 0.0, true));
+    }
+}
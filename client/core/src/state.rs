@@ -0,0 +1,459 @@
+use crate::camera::Camera;
+use crate::icons::UnitStyle;
+use crate::theme::Theme;
+use crate::layout::Control;
+use crate::motion::MOVE_ANIMATION_MS;
+use crate::replay::ReplayStatus;
+use crate::protocol::{ServerMessage, CellMsg, Feature, LoadMenuMsg, PatchMsg, TutorialMsg, TutorialMenuMsg, TipsMsg};
+
+pub struct GameState {
+    pub map_size: (usize, usize), // (cols, rows)
+    pub cells: Vec<Vec<Option<CellMsg>>>,
+    pub round: u32,
+    pub paused: bool,
+    pub pause_requested: bool,
+    pub waiting_for_input: bool,
+    pub attention_coords: Vec<(usize, usize)>,
+    pub attention_message: String,
+    pub turn_message: String,
+    pub error_message: String,
+    pub error_until: f64,
+    pub hover_message: String,
+    pub production_status: String,
+    pub destination: Option<(usize, usize)>,
+    pub debug_message: String,
+    pub load_menu: Option<LoadMenuMsg>,
+    pub tutorial: Option<TutorialMsg>,
+    pub tutorial_menu: Option<TutorialMenuMsg>,
+    pub tips: Option<TipsMsg>,
+    pub selected_col: Option<usize>,
+    pub selected_row: Option<usize>,
+    // Sequence number of the last applied state or patch
+    pub seq: Option<u64>,
+    // Which map the server shows: player-map, computer-map or actual-map
+    pub map_to_display: String,
+    // Bumped whenever a snapshot doesn't follow on from the one before (a
+    // sequence jump, a view toggle, a load, a replay seek), so nothing
+    // diffs the two as if one game had moved on
+    pub snapshot_epoch: u64,
+    // Set on a patch sequence gap; patches are ignored until a full state arrives
+    pub awaiting_resync: bool,
+    resync_requested: bool,
+    // Connection status: the map is kept but dimmed while stale
+    pub connected: bool,
+    pub stale: bool,
+    pub input_dropped_until: f64,
+    // Handshake result: features the server agreed to, or why it can't be used
+    pub server_features: Vec<Feature>,
+    pub protocol_error: Option<String>,
+    // Decoder diagnostics: frames with an unknown type, and frames that failed to decode
+    pub unknown_messages: u32,
+    pub malformed_messages: u32,
+    pub last_decode_error: String,
+    // Client-side hover tracking (not from server)
+    pub hover_col: Option<usize>,
+    pub hover_row: Option<usize>,
+    // Client-side overlay control under the pointer (not from server)
+    pub hovered_control: Option<Control>,
+    // Playback position when fed from a recording instead of the socket
+    pub replay: Option<ReplayStatus>,
+    // Zoom and scroll position of the map view (not from server)
+    pub camera: Camera,
+    // Canvas pixels the front end has room for; None sizes the canvas to
+    // the whole map at the current zoom
+    pub screen_size: Option<(f64, f64)>,
+
+    // --- Client-side onboarding / UI state ---
+    pub show_help_overlay: bool,
+    pub show_inspector: bool,
+    pub show_diagnostics: bool,
+    pub show_minimap: bool,
+    pub show_all_orders: bool,
+    pub unit_style: UnitStyle,
+    pub theme: Theme,
+    // How long units slide between cells, in ms; 0 makes them jump
+    pub move_animation_ms: f64,
+    pub used_pause: bool,
+    pub used_tutorial_menu: bool,
+    pub used_destination: bool,
+    pub used_waypoint: bool,
+    pub used_save: bool,
+    pub used_load_menu: bool,
+    pub used_map_cycle: bool,
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameState {
+    pub fn new() -> Self {
+        Self {
+            map_size: (0, 0),
+            cells: vec![],
+            round: 0,
+            paused: false,
+            pause_requested: false,
+            waiting_for_input: false,
+            attention_coords: vec![],
+            attention_message: String::new(),
+            turn_message: String::new(),
+            error_message: String::new(),
+            error_until: 0.0,
+            hover_message: String::new(),
+            production_status: String::new(),
+            destination: None,
+            debug_message: String::new(),
+            load_menu: None,
+            tutorial: None,
+            tutorial_menu: None,
+            tips: None,
+            selected_col: None,
+            selected_row: None,
+            seq: None,
+            map_to_display: String::new(),
+            snapshot_epoch: 0,
+            awaiting_resync: false,
+            resync_requested: false,
+            connected: false,
+            stale: false,
+            input_dropped_until: 0.0,
+            server_features: vec![],
+            protocol_error: None,
+            unknown_messages: 0,
+            malformed_messages: 0,
+            last_decode_error: String::new(),
+            hover_col: None,
+            hover_row: None,
+            hovered_control: None,
+            replay: None,
+            camera: Camera::default(),
+            screen_size: None,
+
+            show_help_overlay: true,
+            show_inspector: true,
+            show_diagnostics: false,
+            show_minimap: true,
+            show_all_orders: false,
+            unit_style: UnitStyle::Letters,
+            theme: Theme::default(),
+            move_animation_ms: MOVE_ANIMATION_MS,
+            used_pause: false,
+            used_tutorial_menu: false,
+            used_destination: false,
+            used_waypoint: false,
+            used_save: false,
+            used_load_menu: false,
+            used_map_cycle: false,
+        }
+    }
+
+    /// Forget everything the server and the connection supplied, as when
+    /// a recording replaces the live game. The player's own view settings
+    /// (camera, toggles, unit style, theme, animation speed) and onboarding
+    /// progress are kept.
+    pub fn reset_game(&mut self) {
+        let old = std::mem::take(self);
+        *self = Self {
+            snapshot_epoch: old.snapshot_epoch + 1,
+            camera: old.camera,
+            screen_size: old.screen_size,
+            show_help_overlay: old.show_help_overlay,
+            show_inspector: old.show_inspector,
+            show_diagnostics: old.show_diagnostics,
+            show_minimap: old.show_minimap,
+            show_all_orders: old.show_all_orders,
+            unit_style: old.unit_style,
+            theme: old.theme,
+            move_animation_ms: old.move_animation_ms,
+            used_pause: old.used_pause,
+            used_tutorial_menu: old.used_tutorial_menu,
+            used_destination: old.used_destination,
+            used_waypoint: old.used_waypoint,
+            used_save: old.used_save,
+            used_load_menu: old.used_load_menu,
+            used_map_cycle: old.used_map_cycle,
+            ..Self::new()
+        };
+    }
+
+    /// Decode one text frame from the server and apply it. Frames that
+    /// fail to decode are counted and returned as errors; they never
+    /// disturb the current state.
+    pub fn apply_frame(&mut self, text: &str) -> Result<(), serde_json::Error> {
+        match serde_json::from_str::<ServerMessage>(text) {
+            Ok(msg) => {
+                self.apply_message(msg);
+                Ok(())
+            }
+            Err(err) => {
+                self.malformed_messages += 1;
+                self.last_decode_error = err.to_string();
+                Err(err)
+            }
+        }
+    }
+
+    pub fn apply_message(&mut self, msg: ServerMessage) {
+        match msg {
+            ServerMessage::State(s) => {
+                self.check_continuity(s.seq, Some(s.round), Some(&s.map_to_display));
+                self.seq = s.seq;
+                self.map_to_display = s.map_to_display;
+                self.awaiting_resync = false;
+                self.stale = false;
+                self.map_size = s.map_size;
+                self.cells = s.cells;
+                self.round = s.round;
+                self.paused = s.paused;
+                self.pause_requested = s.pause_requested;
+                self.waiting_for_input = s.waiting_for_input;
+                self.attention_coords = s.attention_coords.iter().filter_map(|c| coord(c)).collect();
+                self.attention_message = s.attention_message;
+                self.turn_message = s.turn_message;
+                self.error_message = s.error_message;
+                self.error_until = s.error_until;
+                self.hover_message = s.hover_message;
+                self.set_selected(s.selected_cell.as_deref());
+                self.production_status = s.production_status;
+                self.destination = s.destination.as_deref().and_then(coord);
+                self.debug_message = s.debug_message;
+                self.load_menu = s.load_menu;
+                self.tutorial = s.tutorial;
+                self.tutorial_menu = s.tutorial_menu;
+                self.tips = s.tips;
+            }
+            ServerMessage::Welcome(w) => {
+                self.protocol_error = w.incompatibility();
+                self.server_features = w.features;
+            }
+            ServerMessage::Unknown => {
+                self.unknown_messages += 1;
+            }
+            ServerMessage::Patch(p) => {
+                if self.awaiting_resync {
+                    // Ask again in case the last request never arrived
+                    self.resync_requested = true;
+                    return;
+                }
+                match self.seq {
+                    Some(seq) if p.seq <= seq => {} // stale, already covered by a snapshot
+                    Some(seq) if p.seq == seq + 1 => self.apply_patch(p),
+                    _ => {
+                        self.awaiting_resync = true;
+                        self.resync_requested = true;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Record that user input was discarded because the socket is down,
+    /// so the renderer can show a notice until `now_ms + 2.5s`.
+    pub fn note_dropped_input(&mut self, now_ms: f64) {
+        self.input_dropped_until = now_ms + 2500.0;
+    }
+
+    /// Returns true once after a sequence gap, when a resync should be
+    /// requested, and again after each patch that arrives before the full
+    /// state does.
+    pub fn take_resync_request(&mut self) -> bool {
+        std::mem::take(&mut self.resync_requested)
+    }
+
+    /// Start a new snapshot epoch, for a jump the server can't announce,
+    /// such as seeking in a replay.
+    pub fn break_continuity(&mut self) {
+        self.snapshot_epoch += 1;
+    }
+
+    /// Break continuity unless the snapshot numbered `seq` follows on from
+    /// the current one: the next number, the same view, and the same or
+    /// the next round.
+    fn check_continuity(&mut self, seq: Option<u64>, round: Option<u32>, view: Option<&str>) {
+        let next = matches!((self.seq, seq), (Some(prev), Some(seq)) if seq == prev + 1);
+        let same_view = view.is_none_or(|v| v == self.map_to_display);
+        let same_game = round.is_none_or(|r| r == self.round || r == self.round + 1);
+        if !(next && same_view && same_game) {
+            self.break_continuity();
+        }
+    }
+
+    fn apply_patch(&mut self, p: PatchMsg) {
+        self.check_continuity(Some(p.seq), p.round, p.map_to_display.as_deref());
+        self.seq = Some(p.seq);
+        if let Some(v) = p.map_to_display { self.map_to_display = v; }
+        for (col, row, cell) in p.cells {
+            if let Some(slot) = self.cells.get_mut(col).and_then(|c| c.get_mut(row)) {
+                *slot = cell;
+            }
+        }
+        if let Some(v) = p.round { self.round = v; }
+        if let Some(v) = p.paused { self.paused = v; }
+        if let Some(v) = p.pause_requested { self.pause_requested = v; }
+        if let Some(v) = p.waiting_for_input { self.waiting_for_input = v; }
+        if let Some(v) = p.attention_coords {
+            self.attention_coords = v.iter().filter_map(|c| coord(c)).collect();
+        }
+        if let Some(v) = p.attention_message { self.attention_message = v; }
+        if let Some(v) = p.turn_message { self.turn_message = v; }
+        if let Some(v) = p.error_message { self.error_message = v; }
+        if let Some(v) = p.error_until { self.error_until = v; }
+        if let Some(v) = p.hover_message { self.hover_message = v; }
+        if let Some(v) = p.selected_cell { self.set_selected(v.as_deref()); }
+        if let Some(v) = p.production_status { self.production_status = v; }
+        if let Some(v) = p.destination { self.destination = v.as_deref().and_then(coord); }
+        if let Some(v) = p.debug_message { self.debug_message = v; }
+        if let Some(v) = p.load_menu { self.load_menu = v; }
+        if let Some(v) = p.tutorial { self.tutorial = v; }
+        if let Some(v) = p.tutorial_menu { self.tutorial_menu = v; }
+        if let Some(v) = p.tips { self.tips = v; }
+    }
+
+    fn set_selected(&mut self, cell: Option<&[usize]>) {
+        (self.selected_col, self.selected_row) = cell
+            .and_then(coord)
+            .map(|(c, r)| (Some(c), Some(r)))
+            .unwrap_or((None, None));
+    }
+}
+
+fn coord(c: &[usize]) -> Option<(usize, usize)> {
+    if c.len() == 2 { Some((c[0], c[1])) } else { None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(json: &str) -> ServerMessage {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn state_with_seq(seq: u64) -> ServerMessage {
+        decode(&format!(
+            r#"{{"type":"state","seq":{},"map_size":[2,1],"cells":[[{{"t":"land"}}],[null]],
+                "round":1,"paused":false,"pause_requested":false,"waiting_for_input":false,
+                "attention_coords":[],"attention_message":"","turn_message":"","error_message":"",
+                "error_until":0,"hover_message":"","selected_cell":null,"production_status":"",
+                "destination":null,"map_to_display":"player-map","debug_message":"",
+                "load_menu":null,"tutorial":null,"tutorial_menu":null,"tips":null}}"#,
+            seq
+        ))
+    }
+
+    #[test]
+    fn resync_is_requested_again_until_a_full_state_arrives() {
+        let mut st = GameState::new();
+        st.apply_message(state_with_seq(3));
+        st.apply_message(decode(r#"{"type":"patch","seq":5,"round":7}"#));
+        assert!(st.take_resync_request());
+
+        // The request was lost; the next patch asks again
+        st.apply_message(decode(r#"{"type":"patch","seq":6,"round":8}"#));
+        assert!(st.take_resync_request());
+        assert!(!st.take_resync_request());
+
+        st.apply_message(state_with_seq(6));
+        st.apply_message(decode(r#"{"type":"patch","seq":7,"round":9}"#));
+        assert!(!st.take_resync_request());
+    }
+
+    #[test]
+    fn only_following_snapshots_share_an_epoch() {
+        let mut st = GameState::new();
+        st.apply_message(state_with_seq(3));
+        let epoch = st.snapshot_epoch;
+        st.apply_message(decode(r#"{"type":"patch","seq":4,"cells":[[1,0,{"t":"sea"}]]}"#));
+        st.apply_message(state_with_seq(5));
+        assert_eq!(st.snapshot_epoch, epoch, "the next patch or state follows on");
+
+        // Toggling the view shows another map, not a game that moved on
+        st.apply_message(decode(r#"{"type":"patch","seq":6,"map_to_display":"computer-map"}"#));
+        assert_eq!((st.map_to_display.as_str(), st.snapshot_epoch), ("computer-map", epoch + 1));
+
+        // A loaded game jumps rounds; a replay seeking back goes back in seq
+        st.apply_message(decode(r#"{"type":"patch","seq":7,"map_to_display":"player-map"}"#));
+        st.apply_message(decode(r#"{"type":"patch","seq":8,"round":40}"#));
+        assert_eq!(st.snapshot_epoch, epoch + 3);
+        st.apply_message(state_with_seq(2));
+        assert_eq!(st.snapshot_epoch, epoch + 4);
+    }
+
+    #[test]
+    fn patch_applies_on_top_of_state() {
+        let mut st = GameState::new();
+        st.apply_message(state_with_seq(3));
+        st.apply_message(decode(
+            r#"{"type":"patch","seq":4,"cells":[[1,0,{"t":"sea"}]],"round":2,"selected_cell":[1,0]}"#,
+        ));
+        assert_eq!(st.seq, Some(4));
+        assert_eq!(st.round, 2);
+        assert!(st.cells[1][0].is_some());
+        assert_eq!((st.selected_col, st.selected_row), (Some(1), Some(0)));
+        assert!(!st.take_resync_request());
+    }
+
+    #[test]
+    fn sequence_gap_requests_resync_once() {
+        let mut st = GameState::new();
+        st.apply_message(state_with_seq(3));
+        st.apply_message(decode(r#"{"type":"patch","seq":5,"round":7}"#));
+        st.apply_message(decode(r#"{"type":"patch","seq":6,"round":8}"#));
+        assert_eq!(st.round, 1);
+        assert!(st.awaiting_resync);
+        assert!(st.take_resync_request());
+        assert!(!st.take_resync_request());
+
+        st.apply_message(state_with_seq(6));
+        assert!(!st.awaiting_resync);
+        st.apply_message(decode(r#"{"type":"patch","seq":7,"round":9}"#));
+        assert_eq!(st.round, 9);
+    }
+
+    #[test]
+    fn stale_patch_is_ignored() {
+        let mut st = GameState::new();
+        st.apply_message(state_with_seq(3));
+        st.apply_message(decode(r#"{"type":"patch","seq":3,"round":7}"#));
+        assert_eq!(st.round, 1);
+        assert!(!st.take_resync_request());
+    }
+
+    #[test]
+    fn incompatible_welcome_sets_protocol_error() {
+        let mut st = GameState::new();
+        st.apply_message(decode(
+            r#"{"type":"welcome","protocol_version":2,"min_protocol_version":2,"compatible":false}"#,
+        ));
+        assert!(st.protocol_error.is_some());
+
+        st.apply_message(decode(
+            r#"{"type":"welcome","protocol_version":1,"min_protocol_version":1,"compatible":true,"features":["patches"]}"#,
+        ));
+        assert!(st.protocol_error.is_none());
+        assert_eq!(st.server_features, vec![Feature::Patches]);
+    }
+
+    #[test]
+    fn unknown_and_malformed_frames_are_counted() {
+        let mut st = GameState::new();
+        st.apply_message(state_with_seq(1));
+        assert!(st.apply_frame(r#"{"type":"chat","text":"hi"}"#).is_ok());
+        assert!(st.apply_frame(r#"{"type":"patch"}"#).is_err());
+        assert!(st.apply_frame("not json").is_err());
+        assert_eq!(st.unknown_messages, 1);
+        assert_eq!(st.malformed_messages, 2);
+        assert!(!st.last_decode_error.is_empty());
+        assert_eq!(st.round, 1);
+    }
+
+    #[test]
+    fn patch_before_any_state_requests_resync() {
+        let mut st = GameState::new();
+        st.apply_message(decode(r#"{"type":"patch","seq":1}"#));
+        assert!(st.take_resync_request());
+    }
+}
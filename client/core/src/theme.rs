@@ -0,0 +1,339 @@
+//! Every colour the renderer draws with, gathered into swappable themes.
+//! The built-in themes are chosen by name; a custom theme is JSON with the
+//! same field names, colours as `[r, g, b]`, and any entry left out taken
+//! from the default dark theme.
+
+use serde::{Deserialize, Serialize};
+
+pub type Color = [u8; 3];
+
+/// Names of the built-in themes, in the order the theme key cycles them.
+pub const BUILTIN_THEMES: [&str; 4] = ["dark", "deuteranopia", "high-contrast", "light"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    #[serde(default = "custom_name")]
+    pub name: String,
+
+    // Terrain
+    pub player_city: Color,
+    pub computer_city: Color,
+    pub free_city: Color,
+    pub unexplored: Color,
+    /// Land with no continent id.
+    pub land: Color,
+    /// Land, picked by continent id so neighbouring continents differ.
+    pub land_shades: [Color; 8],
+    pub sea: Color,
+    /// Checkerboard squares drawn over unexplored cells.
+    pub fog_light: Color,
+    pub fog_dark: Color,
+    /// Lines between cells.
+    pub grid: Color,
+    /// Outline of the selected cell.
+    pub selection: Color,
+    /// Frame on the minimap round the part of the map in view.
+    pub viewport: Color,
+
+    // Units, by mode
+    pub awake: Color,
+    pub sleeping: Color,
+    pub sentry: Color,
+    pub explore: Color,
+    pub production: Color,
+    pub waypoint: Color,
+    pub marching_orders: Color,
+    pub flight_path: Color,
+
+    // UI chrome
+    pub canvas_bg: Color,
+    pub panel_bg: Color,
+    pub panel_border: Color,
+    pub text_primary: Color,
+    pub text_secondary: Color,
+    pub error: Color,
+    pub debug: Color,
+    pub accent: Color,
+    /// Behind the hovered menu entry, which is drawn in `hover_text` and
+    /// `hover_text_secondary`.
+    pub hover_bg: Color,
+    pub hover_text: Color,
+    pub hover_text_secondary: Color,
+    /// Backing for map text, badges, hit bars and icon outlines, and the
+    /// drop shadow under dialogs.
+    pub shadow: Color,
+}
+
+fn custom_name() -> String {
+    "custom".to_string()
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    /// Dark cartographic palette: the default.
+    pub fn dark() -> Self {
+        Self {
+            name: "dark".to_string(),
+            player_city: [46, 160, 67],
+            computer_city: [218, 54, 51],
+            free_city: [201, 209, 217],
+            unexplored: [13, 17, 23],
+            land: [61, 43, 31],
+            land_shades: [
+                [61, 43, 31], // dark earth
+                [74, 55, 40], // warm earth
+                [51, 36, 25], // deep soil
+                [82, 62, 45], // clay
+                [43, 30, 20], // dark wood
+                [92, 74, 54], // sandstone
+                [72, 53, 32], // bark
+                [58, 47, 38], // taupe
+            ],
+            sea: [26, 58, 92],
+            fog_light: [15, 19, 25],
+            fog_dark: [11, 15, 21],
+            grid: [0, 0, 0],
+            selection: [255, 255, 255],
+            viewport: [255, 255, 255],
+            awake: [240, 246, 252],
+            sleeping: [72, 79, 88],
+            sentry: [240, 136, 62],
+            explore: [86, 211, 100],
+            production: [110, 118, 129],
+            waypoint: [63, 185, 80],
+            marching_orders: [210, 168, 255],
+            flight_path: [57, 197, 207],
+            canvas_bg: [13, 17, 23],
+            panel_bg: [22, 27, 34],
+            panel_border: [48, 54, 61],
+            text_primary: [230, 237, 243],
+            text_secondary: [139, 148, 158],
+            error: [248, 81, 73],
+            debug: [121, 192, 255],
+            accent: [88, 166, 255],
+            hover_bg: [31, 111, 235],
+            hover_text: [255, 255, 255],
+            hover_text_secondary: [200, 210, 220],
+            shadow: [0, 0, 0],
+        }
+    }
+
+    /// The dark theme with no red/green pairs: player blue against
+    /// computer vermilion, and errors in reddish purple so they never
+    /// read as computer ownership. Colours from the Okabe-Ito palette.
+    pub fn deuteranopia() -> Self {
+        Self {
+            name: "deuteranopia".to_string(),
+            player_city: [0, 114, 178],
+            computer_city: [213, 94, 0],
+            sentry: [230, 159, 0],
+            explore: [86, 180, 233],
+            waypoint: [0, 158, 115],
+            marching_orders: [240, 228, 66],
+            flight_path: [86, 180, 233],
+            error: [204, 121, 167],
+            accent: [86, 180, 233],
+            hover_bg: [0, 114, 178],
+            ..Self::dark()
+        }
+    }
+
+    /// Black background, white text and saturated, well-separated hues.
+    pub fn high_contrast() -> Self {
+        Self {
+            name: "high-contrast".to_string(),
+            player_city: [0, 160, 255],
+            computer_city: [255, 140, 0],
+            free_city: [255, 255, 255],
+            unexplored: [0, 0, 0],
+            land: [110, 80, 40],
+            land_shades: [
+                [110, 80, 40],
+                [125, 92, 48],
+                [100, 72, 36],
+                [135, 100, 55],
+                [95, 68, 34],
+                [140, 108, 62],
+                [118, 86, 44],
+                [105, 78, 40],
+            ],
+            sea: [0, 50, 140],
+            fog_light: [12, 12, 12],
+            fog_dark: [0, 0, 0],
+            grid: [0, 0, 0],
+            selection: [255, 255, 255],
+            viewport: [255, 255, 255],
+            awake: [255, 255, 255],
+            sleeping: [150, 150, 150],
+            sentry: [255, 200, 0],
+            explore: [0, 255, 140],
+            production: [200, 200, 200],
+            waypoint: [0, 255, 140],
+            marching_orders: [230, 150, 255],
+            flight_path: [0, 240, 255],
+            canvas_bg: [0, 0, 0],
+            panel_bg: [0, 0, 0],
+            panel_border: [255, 255, 255],
+            text_primary: [255, 255, 255],
+            text_secondary: [230, 230, 230],
+            error: [255, 80, 80],
+            debug: [120, 200, 255],
+            accent: [255, 214, 0],
+            hover_bg: [0, 70, 190],
+            hover_text: [255, 255, 255],
+            hover_text_secondary: [230, 230, 230],
+            shadow: [0, 0, 0],
+        }
+    }
+
+    /// Light panels and a pale map, for bright rooms.
+    pub fn light() -> Self {
+        Self {
+            name: "light".to_string(),
+            player_city: [26, 127, 55],
+            computer_city: [207, 34, 46],
+            free_city: [110, 119, 129],
+            unexplored: [216, 222, 228],
+            land: [196, 164, 120],
+            land_shades: [
+                [196, 164, 120],
+                [210, 180, 136],
+                [186, 152, 108],
+                [218, 190, 146],
+                [178, 144, 100],
+                [226, 200, 156],
+                [204, 170, 124],
+                [190, 162, 126],
+            ],
+            sea: [130, 180, 225],
+            fog_light: [222, 227, 232],
+            fog_dark: [210, 216, 222],
+            grid: [87, 96, 106],
+            selection: [31, 35, 40],
+            viewport: [31, 35, 40],
+            awake: [31, 35, 40],
+            sleeping: [110, 118, 129],
+            sentry: [188, 76, 0],
+            explore: [26, 127, 55],
+            production: [87, 96, 106],
+            waypoint: [26, 127, 55],
+            marching_orders: [130, 80, 223],
+            flight_path: [0, 130, 150],
+            canvas_bg: [246, 248, 250],
+            panel_bg: [255, 255, 255],
+            panel_border: [208, 215, 222],
+            text_primary: [31, 35, 40],
+            text_secondary: [87, 96, 106],
+            error: [191, 57, 138],
+            debug: [9, 105, 218],
+            accent: [9, 105, 218],
+            hover_bg: [9, 105, 218],
+            hover_text: [255, 255, 255],
+            hover_text_secondary: [220, 230, 245],
+            shadow: [255, 255, 255],
+        }
+    }
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "deuteranopia" => Some(Self::deuteranopia()),
+            "high-contrast" => Some(Self::high_contrast()),
+            "light" => Some(Self::light()),
+            _ => None,
+        }
+    }
+
+    /// The built-in theme after this one, wrapping around; a custom theme
+    /// is followed by the first.
+    pub fn next_builtin(&self) -> Self {
+        let next = BUILTIN_THEMES
+            .iter()
+            .position(|name| *name == self.name)
+            .map_or(0, |i| (i + 1) % BUILTIN_THEMES.len());
+        Self::builtin(BUILTIN_THEMES[next]).unwrap()
+    }
+
+    /// A custom theme from JSON.
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        serde_json::from_str(text).map_err(|e| format!("theme: {}", e))
+    }
+
+    /// What to store to bring this theme back with [`Theme::restore`]: the
+    /// name of an unmodified built-in, otherwise the whole theme as JSON.
+    pub fn saved(&self) -> String {
+        if Self::builtin(&self.name).as_ref() == Some(self) {
+            self.name.clone()
+        } else {
+            serde_json::to_string(self).unwrap()
+        }
+    }
+
+    pub fn restore(saved: &str) -> Result<Self, String> {
+        Self::builtin(saved).map_or_else(|| Self::from_json(saved), Ok)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtins_cycle_by_name() {
+        let mut theme = Theme::default();
+        for name in BUILTIN_THEMES.iter().skip(1) {
+            theme = theme.next_builtin();
+            assert_eq!(theme.name, *name);
+        }
+        assert_eq!(theme.next_builtin(), Theme::dark());
+    }
+
+    #[test]
+    fn ownership_and_errors_never_share_a_colour() {
+        for name in BUILTIN_THEMES {
+            let theme = Theme::builtin(name).unwrap();
+            let colors = [theme.player_city, theme.computer_city, theme.error];
+            for (i, a) in colors.iter().enumerate() {
+                assert!(!colors[i + 1..].contains(a), "{} reuses {:?}", name, a);
+            }
+        }
+    }
+
+    #[test]
+    fn awake_units_stand_out_on_land_and_sea() {
+        let luma = |c: Color| 0.299 * c[0] as f64 + 0.587 * c[1] as f64 + 0.114 * c[2] as f64;
+        for name in BUILTIN_THEMES {
+            let theme = Theme::builtin(name).unwrap();
+            for ground in theme.land_shades.iter().chain([&theme.land, &theme.sea]) {
+                assert!((luma(theme.awake) - luma(*ground)).abs() > 100.0, "{}: awake on {:?}", name, ground);
+            }
+        }
+    }
+
+    #[test]
+    fn custom_json_fills_gaps_from_the_default() {
+        let theme = Theme::from_json(r#"{"player_city": [0, 0, 255], "sea": [0, 0, 80]}"#).unwrap();
+        assert_eq!(theme.name, "custom");
+        assert_eq!(theme.player_city, [0, 0, 255]);
+        assert_eq!(theme.computer_city, Theme::dark().computer_city);
+        assert!(Theme::from_json(r#"{"sea": "blue"}"#).is_err());
+    }
+
+    #[test]
+    fn saved_themes_restore() {
+        assert_eq!(Theme::light().saved(), "light");
+        assert_eq!(Theme::restore("light").unwrap(), Theme::light());
+
+        let custom = Theme { sea: [1, 2, 3], ..Theme::high_contrast() };
+        let saved = custom.saved();
+        assert!(saved.starts_with('{'));
+        assert_eq!(Theme::restore(&saved).unwrap(), custom);
+        assert!(Theme::restore("sepia").is_err());
+    }
+}
@@ -0,0 +1,120 @@
+use crate::protocol::{UnitKind, UnitMsg};
+
+/// Squares a fighter flies per round.
+pub const FIGHTER_SPEED: u32 = 8;
+/// Squares a fighter can fly on a full tank.
+pub const FIGHTER_FUEL: u32 = 32;
+
+/// Production cost (rounds) per unit type.
+pub fn item_cost(unit_type: &UnitKind) -> u32 {
+    match unit_type {
+        UnitKind::Army => 5,
+        UnitKind::Fighter => 10,
+        UnitKind::Transport => 30,
+        UnitKind::Carrier => 30,
+        UnitKind::PatrolBoat => 15,
+        UnitKind::Destroyer => 20,
+        UnitKind::Submarine => 20,
+        UnitKind::Battleship => 40,
+        UnitKind::Satellite => 50,
+        UnitKind::Unknown(_) => 10,
+    }
+}
+
+/// Squares a unit type moves per round.
+pub fn speed(unit_type: &UnitKind) -> u32 {
+    match unit_type {
+        UnitKind::Army => 1,
+        UnitKind::Fighter => FIGHTER_SPEED,
+        UnitKind::PatrolBoat => 4,
+        UnitKind::Destroyer
+        | UnitKind::Submarine
+        | UnitKind::Transport
+        | UnitKind::Carrier
+        | UnitKind::Battleship => 2,
+        UnitKind::Satellite => 10,
+        UnitKind::Unknown(_) => 1,
+    }
+}
+
+/// Hits a unit type can take before it is destroyed; satellites can't
+/// be damaged.
+pub fn max_hits(unit_type: &UnitKind) -> Option<u32> {
+    match unit_type {
+        UnitKind::Army | UnitKind::Fighter | UnitKind::PatrolBoat | UnitKind::Transport => Some(1),
+        UnitKind::Destroyer => Some(3),
+        UnitKind::Submarine => Some(2),
+        UnitKind::Carrier => Some(8),
+        UnitKind::Battleship => Some(10),
+        UnitKind::Satellite | UnitKind::Unknown(_) => None,
+    }
+}
+
+/// `(hits, max)` for a unit below its maximum hits.
+pub fn damage(unit: &UnitMsg) -> Option<(u32, u32)> {
+    let max = max_hits(&unit.t)?;
+    unit.h.filter(|&h| h < max).map(|h| (h, max))
+}
+
+/// Map glyph for a unit type; computer units are shown in lowercase.
+pub fn unit_char(unit_type: &UnitKind) -> &'static str {
+    match unit_type {
+        UnitKind::Army => "A",
+        UnitKind::Fighter => "F",
+        UnitKind::Transport => "T",
+        UnitKind::Carrier => "C",
+        UnitKind::Destroyer => "D",
+        UnitKind::Submarine => "S",
+        UnitKind::PatrolBoat => "P",
+        UnitKind::Battleship => "B",
+        UnitKind::Satellite => "Z",
+        UnitKind::Unknown(_) => "?",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn costs_match_readme() {
+        assert_eq!(item_cost(&UnitKind::Army), 5);
+        assert_eq!(item_cost(&UnitKind::PatrolBoat), 15);
+        assert_eq!(item_cost(&UnitKind::Battleship), 40);
+        assert_eq!(item_cost(&UnitKind::Satellite), 50);
+    }
+
+    #[test]
+    fn speeds_match_readme() {
+        assert_eq!(speed(&UnitKind::Army), 1);
+        assert_eq!(speed(&UnitKind::Fighter), 8);
+        assert_eq!(speed(&UnitKind::PatrolBoat), 4);
+        assert_eq!(speed(&UnitKind::Battleship), 2);
+        assert_eq!(speed(&UnitKind::Satellite), 10);
+    }
+
+    #[test]
+    fn hits_match_readme() {
+        assert_eq!(max_hits(&UnitKind::Army), Some(1));
+        assert_eq!(max_hits(&UnitKind::Submarine), Some(2));
+        assert_eq!(max_hits(&UnitKind::Carrier), Some(8));
+        assert_eq!(max_hits(&UnitKind::Battleship), Some(10));
+        assert_eq!(max_hits(&UnitKind::Satellite), None);
+    }
+
+    #[test]
+    fn only_units_below_max_hits_are_damaged() {
+        let unit = |json: &str| serde_json::from_str::<UnitMsg>(json).unwrap();
+        assert_eq!(damage(&unit(r#"{"t":"battleship","o":"player","m":"awake","h":4}"#)), Some((4, 10)));
+        assert_eq!(damage(&unit(r#"{"t":"battleship","o":"player","m":"awake","h":10}"#)), None);
+        assert_eq!(damage(&unit(r#"{"t":"destroyer","o":"computer","m":"awake"}"#)), None);
+        assert_eq!(damage(&unit(r#"{"t":"satellite","o":"player","m":"awake","h":1}"#)), None);
+    }
+
+    #[test]
+    fn glyphs() {
+        assert_eq!(unit_char(&UnitKind::Satellite), "Z");
+        assert_eq!(unit_char(&UnitKind::PatrolBoat), "P");
+        assert_eq!(unit_char(&UnitKind::from("zeppelin")), "?");
+    }
+}
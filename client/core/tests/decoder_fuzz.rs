@@ -0,0 +1,241 @@
+//! Property tests feeding random and mutated JSON into the server message
+//! decoder. Whatever arrives on the socket, decoding and applying it to a
+//! `GameState` must never panic.
+
+use empire_client_core::protocol::ServerMessage;
+use empire_client_core::state::GameState;
+use proptest::prelude::*;
+use serde_json::{json, Value};
+
+fn state_fixture() -> Value {
+    json!({
+        "type": "state",
+        "seq": 7,
+        "map_size": [3, 2],
+        "cells": [
+            [{"t": "land", "cid": 2}, {"t": "city", "cs": "player", "prod": {"item": "army", "remaining": 3}}],
+            [{"t": "sea", "u": {"t": "transport", "o": "player", "m": "sentry", "h": 1}, "ac": 4, "aa": 1}, null],
+            [{"t": "city", "cs": "computer", "u": {"t": "fighter", "o": "computer", "m": "awake", "fuel": 20}, "fc": 2}, {"t": "unexplored"}]
+        ],
+        "round": 12,
+        "paused": false,
+        "pause_requested": false,
+        "waiting_for_input": true,
+        "attention_coords": [[0, 1]],
+        "attention_message": "City needs production",
+        "turn_message": "",
+        "error_message": "",
+        "error_until": 0,
+        "hover_message": "",
+        "selected_cell": [0, 1],
+        "production_status": "",
+        "destination": null,
+        "map_to_display": "player-map",
+        "debug_message": "",
+        "load_menu": {"files": ["a.edn"], "hovered": 0},
+        "tutorial": null,
+        "tutorial_menu": {"scenarios": [{"id": "basics", "name": "Basics", "description": "Start here"}]},
+        "tips": {"id": "t1", "title": "Tip", "text": "Hello"}
+    })
+}
+
+fn patch_fixture() -> Value {
+    json!({
+        "type": "patch",
+        "seq": 8,
+        "cells": [[1, 1, {"t": "sea"}], [0, 0, null]],
+        "round": 13,
+        "selected_cell": null,
+        "load_menu": null,
+        "attention_coords": [[2, 0], [9]]
+    })
+}
+
+fn fixtures() -> Vec<String> {
+    vec![
+        state_fixture().to_string(),
+        patch_fixture().to_string(),
+        json!({"type": "welcome", "protocol_version": 1, "min_protocol_version": 1, "compatible": true, "features": ["patches"]}).to_string(),
+    ]
+}
+
+/// Arbitrary JSON values, a few levels deep.
+fn arb_json() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(|n| json!(n)),
+        any::<f64>().prop_filter("finite", |f| f.is_finite()).prop_map(|f| json!(f)),
+        ".{0,12}".prop_map(Value::String),
+        prop_oneof![Just("state"), Just("patch"), Just("welcome"), Just("land"), Just("army")]
+            .prop_map(|s| Value::String(s.to_string())),
+    ];
+    leaf.prop_recursive(4, 64, 8, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..6).prop_map(Value::Array),
+            prop::collection::btree_map(".{0,8}", inner, 0..6)
+                .prop_map(|m| Value::Object(m.into_iter().collect())),
+        ]
+    })
+}
+
+/// Replace the value at a pseudo-random path inside `value` with `replacement`.
+fn replace_at(value: &mut Value, mut pick: usize, replacement: Value) {
+    let mut cur = value;
+    loop {
+        let len = match cur {
+            Value::Object(m) => m.len(),
+            Value::Array(a) => a.len(),
+            _ => 0,
+        };
+        if len == 0 || pick.is_multiple_of(3) {
+            *cur = replacement;
+            return;
+        }
+        let idx = pick % len;
+        pick /= 3;
+        cur = match cur {
+            Value::Object(m) => m.values_mut().nth(idx).unwrap(),
+            Value::Array(a) => &mut a[idx],
+            _ => unreachable!(),
+        };
+    }
+}
+
+fn apply_to_fresh_and_loaded(text: &str) {
+    let mut fresh = GameState::new();
+    let _ = fresh.apply_frame(text);
+
+    let mut loaded = GameState::new();
+    loaded.apply_frame(&state_fixture().to_string()).unwrap();
+    let _ = loaded.apply_frame(text);
+}
+
+#[derive(Debug, Clone)]
+enum Edit {
+    Delete(usize, usize),
+    Insert(usize, char),
+    Replace(usize, char),
+    Truncate(usize),
+}
+
+fn arb_edit() -> impl Strategy<Value = Edit> {
+    prop_oneof![
+        (any::<usize>(), 1..8usize).prop_map(|(at, n)| Edit::Delete(at, n)),
+        (any::<usize>(), prop::sample::select(vec!['{', '}', '[', ']', '"', ',', ':', '0', '-', 'e', '\\', 'x'])).prop_map(|(at, c)| Edit::Insert(at, c)),
+        (any::<usize>(), any::<char>()).prop_map(|(at, c)| Edit::Replace(at, c)),
+        any::<usize>().prop_map(Edit::Truncate),
+    ]
+}
+
+fn apply_edit(text: &mut Vec<char>, edit: &Edit) {
+    if text.is_empty() {
+        return;
+    }
+    match *edit {
+        Edit::Delete(at, n) => {
+            let at = at % text.len();
+            let end = (at + n).min(text.len());
+            text.drain(at..end);
+        }
+        Edit::Insert(at, c) => {
+            let at = at % (text.len() + 1);
+            text.insert(at, c);
+        }
+        Edit::Replace(at, c) => {
+            let at = at % text.len();
+            text[at] = c;
+        }
+        Edit::Truncate(at) => {
+            text.truncate(at % text.len());
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    #[test]
+    fn arbitrary_text_never_panics(text in ".{0,256}") {
+        apply_to_fresh_and_loaded(&text);
+    }
+
+    #[test]
+    fn arbitrary_json_never_panics(value in arb_json()) {
+        apply_to_fresh_and_loaded(&value.to_string());
+    }
+
+    #[test]
+    fn arbitrary_body_with_known_type_never_panics(
+        ty in prop::sample::select(vec!["state", "patch", "welcome"]),
+        body in arb_json(),
+    ) {
+        let mut value = match body {
+            Value::Object(m) => Value::Object(m),
+            other => json!({ "payload": other }),
+        };
+        value["type"] = json!(ty);
+        apply_to_fresh_and_loaded(&value.to_string());
+    }
+
+    #[test]
+    fn unknown_type_is_tolerated(ty in "[a-z_]{1,12}", body in arb_json()) {
+        prop_assume!(!["state", "patch", "welcome"].contains(&ty.as_str()));
+        let value = json!({ "type": ty, "body": body });
+        let msg: ServerMessage = serde_json::from_str(&value.to_string()).unwrap();
+        prop_assert!(matches!(msg, ServerMessage::Unknown));
+
+        let mut st = GameState::new();
+        st.apply_frame(&value.to_string()).unwrap();
+        prop_assert_eq!(st.unknown_messages, 1);
+        prop_assert_eq!(st.malformed_messages, 0);
+    }
+
+    #[test]
+    fn structurally_mutated_fixtures_never_panic(
+        which in 0..3usize,
+        pick in any::<usize>(),
+        replacement in arb_json(),
+    ) {
+        let mut value: Value = serde_json::from_str(&fixtures()[which]).unwrap();
+        replace_at(&mut value, pick, replacement);
+        apply_to_fresh_and_loaded(&value.to_string());
+    }
+
+    #[test]
+    fn textually_mutated_fixtures_never_panic(
+        which in 0..3usize,
+        edits in prop::collection::vec(arb_edit(), 1..6),
+    ) {
+        let mut chars: Vec<char> = fixtures()[which].chars().collect();
+        for edit in &edits {
+            apply_edit(&mut chars, edit);
+        }
+        let text: String = chars.into_iter().collect();
+        apply_to_fresh_and_loaded(&text);
+    }
+
+    #[test]
+    fn patches_with_any_coordinates_never_panic(
+        cells in prop::collection::vec((any::<u32>(), any::<u32>()), 0..8),
+        seq in 0..16u64,
+    ) {
+        let cells: Vec<Value> = cells
+            .into_iter()
+            .map(|(c, r)| json!([c, r, {"t": "land"}]))
+            .collect();
+        let patch = json!({ "type": "patch", "seq": seq, "cells": cells, "attention_coords": [[1, 2, 3]] });
+        apply_to_fresh_and_loaded(&patch.to_string());
+    }
+}
+
+#[test]
+fn fixtures_decode_cleanly() {
+    let mut st = GameState::new();
+    for text in fixtures() {
+        st.apply_frame(&text).unwrap();
+    }
+    assert_eq!(st.malformed_messages, 0);
+    assert_eq!(st.round, 13);
+    assert_eq!(st.seq, Some(8));
+}
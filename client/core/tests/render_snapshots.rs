@@ -0,0 +1,547 @@
+//! Snapshot tests of the draw commands `Renderer::render` issues for
+//! fixture game states. Run with `UPDATE_SNAPSHOTS=1` to accept changes,
+//! then review the diff under `tests/snapshots/`.
+
+use std::path::PathBuf;
+
+use empire_client_core::draw::{DrawCommand, RecordingTarget};
+use empire_client_core::effects::DAMAGED_MS;
+use empire_client_core::icons::UnitStyle;
+use empire_client_core::layout::{Control, Rect};
+use empire_client_core::renderer::Renderer;
+use empire_client_core::replay::ReplayStatus;
+use empire_client_core::state::GameState;
+use serde_json::{json, Value};
+
+const WALL_MS: f64 = 1_700_000_000_000.0;
+
+/// A 4x12 map: land and sea columns, a player city with production, and a
+/// player army on land.
+fn base_state() -> Value {
+    let land = json!({"t": "land", "cid": 1});
+    let sea = json!({"t": "sea"});
+    let mut cells = vec![
+        vec![land.clone(); 12],
+        vec![land.clone(); 12],
+        vec![sea.clone(); 12],
+        vec![Value::Null; 12],
+    ];
+    cells[0][0] = json!({"t": "city", "cs": "player", "prod": {"item": "army", "remaining": 2}});
+    cells[1][0] = json!({"t": "land", "cid": 1, "u": {"t": "army", "o": "player", "m": "awake"}});
+    cells[2][1] = json!({"t": "sea", "u": {"t": "destroyer", "o": "computer", "m": "sentry", "h": 3}});
+    json!({
+        "type": "state",
+        "seq": 1,
+        "map_size": [4, 12],
+        "cells": cells,
+        "round": 3,
+        "paused": false,
+        "pause_requested": false,
+        "waiting_for_input": false,
+        "attention_coords": [],
+        "attention_message": "",
+        "turn_message": "",
+        "error_message": "",
+        "error_until": 0,
+        "hover_message": "",
+        "selected_cell": null,
+        "production_status": "",
+        "destination": null,
+        "map_to_display": "player-map",
+        "debug_message": "",
+        "load_menu": null,
+        "tutorial": null,
+        "tutorial_menu": null,
+        "tips": null
+    })
+}
+
+fn state_from(value: Value) -> GameState {
+    let mut st = GameState::new();
+    st.apply_frame(&value.to_string()).unwrap();
+    // Panels are covered by their own fixtures; keep the map ones focused.
+    st.show_help_overlay = false;
+    st.show_inspector = false;
+    st
+}
+
+fn render(state: &GameState) -> RecordingTarget {
+    let mut renderer = Renderer::new(RecordingTarget::new());
+    renderer.render(state, 0.0, WALL_MS);
+    renderer.into_target()
+}
+
+fn assert_snapshot(name: &str, actual: &str) {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "snapshots", &format!("{}.snap", name)]
+        .iter()
+        .collect();
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("missing snapshot {:?}; run with UPDATE_SNAPSHOTS=1", path));
+    if expected != actual {
+        let first_diff = expected
+            .lines()
+            .zip(actual.lines())
+            .position(|(e, a)| e != a)
+            .unwrap_or(expected.lines().count().min(actual.lines().count()));
+        panic!(
+            "snapshot {} differs at line {}:\n  expected: {:?}\n  actual:   {:?}\nrun with UPDATE_SNAPSHOTS=1 to accept",
+            name,
+            first_diff + 1,
+            expected.lines().nth(first_diff),
+            actual.lines().nth(first_diff),
+        );
+    }
+}
+
+fn has(target: &RecordingTarget, cmd: &DrawCommand) -> bool {
+    target.commands().contains(cmd)
+}
+
+#[test]
+fn tutorial_menu() {
+    let mut value = base_state();
+    value["tutorial_menu"] = json!({"scenarios": [
+        {"id": "basics", "name": "Basics", "description": "Move and capture"},
+        {"id": "naval", "name": "Naval", "description": "Ships and transports"}
+    ]});
+    let mut st = state_from(value);
+    st.hovered_control = Some(Control::TutorialMenuItem(1));
+    let target = render(&st);
+
+    let texts = target.texts();
+    assert!(texts.contains(&"Tutorial Scenarios"));
+    assert!(texts.contains(&"1. Basics"));
+    assert!(texts.contains(&"2. Naval"));
+    // Hovered row gets the hover background
+    assert!(has(&target, &DrawCommand::FillStyle("rgb(31,111,235)".to_string())));
+    assert_snapshot("tutorial_menu", &target.dump());
+}
+
+#[test]
+fn load_menu() {
+    let mut value = base_state();
+    value["load_menu"] = json!({"files": ["autosave.edn", "invasion.edn"], "hovered": 0});
+    let target = render(&state_from(value));
+
+    let texts = target.texts();
+    assert!(texts.contains(&"Load Game"));
+    assert!(texts.contains(&"autosave.edn"));
+    assert!(texts.contains(&"Press ESC to close"));
+    assert!(has(&target, &DrawCommand::FillStyle("rgb(255,255,255)".to_string())));
+    assert_snapshot("load_menu", &target.dump());
+}
+
+#[test]
+fn empty_load_menu() {
+    let mut value = base_state();
+    value["load_menu"] = json!({"files": [], "hovered": null});
+    let target = render(&state_from(value));
+    assert!(target.texts().contains(&"No saved games found"));
+}
+
+#[test]
+fn attention_marker() {
+    let mut value = base_state();
+    value["waiting_for_input"] = json!(true);
+    value["attention_coords"] = json!([[1, 0]]);
+    value["attention_message"] = json!("Army needs orders");
+    let target = render(&state_from(value));
+
+    // Chevrons in the sentry colour around cell (1, 0)
+    assert!(has(&target, &DrawCommand::MoveTo(14.0 + 2.5 + 5.5, 2.5)));
+    assert!(target
+        .commands()
+        .iter()
+        .any(|c| matches!(c, DrawCommand::StrokeStyle(s) if s.starts_with("rgba(240,136,62,"))));
+    assert!(target.texts().contains(&"Army needs orders"));
+    assert_snapshot("attention_marker", &target.dump());
+}
+
+#[test]
+fn no_attention_marker_when_not_waiting() {
+    let mut value = base_state();
+    value["attention_coords"] = json!([[1, 0]]);
+    let target = render(&state_from(value));
+    assert!(!target
+        .commands()
+        .iter()
+        .any(|c| matches!(c, DrawCommand::StrokeStyle(s) if s.starts_with("rgba(240,136,62,"))));
+}
+
+#[test]
+fn error_banner() {
+    let mut value = base_state();
+    value["error_message"] = json!("Cannot move there");
+    value["error_until"] = json!(WALL_MS + 5000.0);
+    let target = render(&state_from(value));
+
+    assert!(target.texts().contains(&"Cannot move there"));
+    assert!(has(&target, &DrawCommand::FillStyle("rgb(248,81,73)".to_string())));
+    assert_snapshot("error_banner", &target.dump());
+}
+
+#[test]
+fn expired_error_banner_is_hidden() {
+    let mut value = base_state();
+    value["error_message"] = json!("Cannot move there");
+    value["error_until"] = json!(WALL_MS - 1.0);
+    let target = render(&state_from(value));
+    assert!(!target.texts().contains(&"Cannot move there"));
+}
+
+#[test]
+fn help_and_inspector_panels() {
+    let mut st = state_from(base_state());
+    st.show_help_overlay = true;
+    st.show_inspector = true;
+    st.selected_col = Some(0);
+    st.selected_row = Some(0);
+    let target = render(&st);
+
+    let texts = target.texts();
+    assert!(texts.contains(&"Inspector"));
+    assert!(texts.contains(&"Selected"));
+    assert!(texts.contains(&"[I] hide inspector"));
+    assert_snapshot("help_and_inspector", &target.dump());
+}
+
+#[test]
+fn resizes_target_to_map() {
+    let target = render(&state_from(base_state()));
+    // 4 cols * 14 is below the minimum width; 12 rows * 20 + message area
+    assert_eq!(target.commands()[0], DrawCommand::SetSize(700, 240 + 8 + 60));
+}
+
+#[test]
+fn hovered_panel_button_is_highlighted() {
+    let mut st = state_from(base_state());
+    st.show_inspector = true;
+    let plain = render(&st);
+    st.hovered_control = Some(Control::HideInspector);
+    let hovered = render(&st);
+
+    let highlight = DrawCommand::FillStyle("rgba(31,111,235,0.35)".to_string());
+    assert!(!has(&plain, &highlight));
+    assert!(has(&hovered, &highlight));
+    assert!(hovered.texts().contains(&"[I] hide inspector"));
+}
+
+#[test]
+fn renderer_exposes_layout_of_last_frame() {
+    let mut st = state_from(base_state());
+    st.show_help_overlay = true;
+    let mut renderer = Renderer::new(RecordingTarget::new());
+    renderer.render(&st, 0.0, WALL_MS);
+    let help = renderer.layout().help.as_ref().unwrap();
+    let button = &help.buttons[0];
+    let (x, y) = (button.rect.x + 1.0, button.rect.y + 1.0);
+    assert_eq!(renderer.layout().control_at(x, y), Some(Control::HideHelp));
+}
+
+#[test]
+fn replay_bar() {
+    let mut st = state_from(base_state());
+    st.replay = Some(ReplayStatus {
+        time_ms: 30_000.0,
+        duration_ms: 120_000.0,
+        playing: true,
+        speed: 4.0,
+        inputs: vec![1_000.0, 1_010.0, 60_000.0],
+    });
+    st.hovered_control = Some(Control::ReplayStep);
+    let target = render(&st);
+
+    let texts = target.texts();
+    assert!(texts.contains(&"Pause"));
+    assert!(texts.contains(&"4x"));
+    assert!(texts.contains(&"0:30 / 2:00"));
+    assert!(has(&target, &DrawCommand::SetSize(700, 240 + 8 + 60 + 32)));
+    assert_snapshot("replay_bar", &target.dump());
+}
+
+#[test]
+fn terrain_layer() {
+    let mut renderer = Renderer::new(RecordingTarget::new());
+    renderer.render(&state_from(base_state()), 0.0, WALL_MS);
+
+    // The frame composites the layer instead of painting cells itself
+    let map = Rect::new(0.0, 0.0, 4.0 * 14.0, 12.0 * 20.0);
+    assert!(has(renderer.target(), &DrawCommand::DrawLayer(map, map)));
+    assert_eq!(renderer.terrain_painted(), 4 * 12);
+    let layer = renderer.terrain_layer().unwrap();
+    assert_eq!(layer.commands()[0], DrawCommand::SetSize(4 * 14, 12 * 20));
+    assert_snapshot("terrain_layer", &layer.dump());
+}
+
+#[test]
+fn terrain_layer_repaints_only_changed_cells() {
+    let mut st = state_from(base_state());
+    let mut renderer = Renderer::new(RecordingTarget::new());
+    renderer.render(&st, 0.0, WALL_MS);
+
+    // Units moving and animation time passing leave the terrain alone
+    st.apply_frame(r#"{"type":"patch","seq":2,"cells":[[1,0,{"t":"land","cid":1}],[1,1,{"t":"land","cid":1,"u":{"t":"army","o":"player","m":"awake"}}]]}"#)
+        .unwrap();
+    renderer.render(&st, 500.0, WALL_MS);
+    assert_eq!(renderer.terrain_painted(), 0);
+
+    // An unexplored cell turning into sea is repainted on its own
+    let before = renderer.terrain_layer().unwrap().commands().len();
+    st.apply_frame(r#"{"type":"patch","seq":3,"cells":[[3,5,{"t":"sea"}]]}"#).unwrap();
+    renderer.target_mut().clear();
+    renderer.render(&st, 1000.0, WALL_MS);
+    assert_eq!(renderer.terrain_painted(), 1);
+    let repainted = &renderer.terrain_layer().unwrap().commands()[before..];
+    assert_eq!(repainted[..2], [
+        DrawCommand::FillStyle("rgb(26,58,92)".to_string()),
+        DrawCommand::FillRect(42.0, 100.0, 14.0, 20.0),
+    ]);
+    assert!(!repainted[2..].iter().any(|c| matches!(c, DrawCommand::FillRect(..))));
+    // The frame itself paints no terrain
+    assert!(!has(renderer.target(), &DrawCommand::FillRect(42.0, 100.0, 14.0, 20.0)));
+}
+
+#[test]
+fn scrolled_view_draws_only_visible_cells() {
+    // A wide map of armies, shown through a screen that fits a few columns
+    let mut value = base_state();
+    let army = json!({"t": "land", "u": {"t": "army", "o": "player", "m": "awake"}});
+    value["map_size"] = json!([120, 12]);
+    value["cells"] = json!(vec![vec![army; 12]; 120]);
+    let mut st = state_from(value);
+    st.screen_size = Some((700.0, 1000.0));
+    st.camera.zoom_at(2, 0.0, 0.0);
+    st.camera.pan(28.0 * 10.0, 0.0);
+    let target = render(&st);
+
+    // 700px at 28px a cell is 25 columns; 12 rows of 40px fit
+    let armies = target.texts().iter().filter(|t| **t == "A").count();
+    assert_eq!(armies, 2 * 25 * 12);
+    let src = Rect::new(10.0 * 14.0, 0.0, 700.0 / 2.0, 480.0 / 2.0);
+    assert!(has(&target, &DrawCommand::DrawLayer(src, Rect::new(0.0, 0.0, 700.0, 480.0))));
+    assert!(has(&target, &DrawCommand::Font("bold 28px 'JetBrains Mono', 'Fira Code', 'Courier New', monospace".to_string())));
+}
+
+#[test]
+fn minimap() {
+    // Twice as wide as the screen: a computer city and units on both sides
+    let mut value = base_state();
+    let mut cells = vec![vec![json!({"t": "sea"}); 12]; 100];
+    cells[0][0] = json!({"t": "city", "cs": "player"});
+    cells[90][6] = json!({"t": "city", "cs": "computer"});
+    cells[1][1] = json!({"t": "land", "u": {"t": "army", "o": "player", "m": "awake"}});
+    cells[80][3] = json!({"t": "sea", "u": {"t": "battleship", "o": "computer", "m": "awake"}});
+    value["map_size"] = json!([100, 12]);
+    value["cells"] = json!(cells);
+    value["waiting_for_input"] = json!(true);
+    value["attention_coords"] = json!([[1, 1]]);
+    let mut st = state_from(value);
+    st.screen_size = Some((700.0, 1000.0));
+    let target = render(&st);
+
+    // 2 pixels a cell: the view shows 50 of the 100 columns
+    let viewport = DrawCommand::StrokeRect(486.5, 202.5, 99.0, 23.0);
+    assert!(has(&target, &viewport));
+    assert!(has(&target, &DrawCommand::DrawLayer(Rect::new(0.0, 0.0, 100.0, 12.0), Rect::new(486.0, 202.0, 200.0, 24.0))));
+    assert_snapshot("minimap", &target.dump());
+}
+
+#[test]
+fn marching_orders_and_flight_paths() {
+    let mut value = base_state();
+    value["cells"][1][0]["u"]["marching-orders"] = json!([1, 10]);
+    value["cells"][0][6] = json!({"t": "land", "u": {"t": "fighter", "o": "player", "m": "moving", "flight-path": [3, 2]}});
+    let mut st = state_from(value);
+    let flight = |t: &RecordingTarget| {
+        t.commands().iter().any(|c| matches!(c, DrawCommand::StrokeStyle(s) if s.starts_with("rgba(57,197,207,")))
+    };
+
+    // Nothing selected: no arrows
+    let target = render(&st);
+    assert!(!flight(&target));
+    assert!(!has(&target, &DrawCommand::MoveTo(21.0, 10.0)));
+
+    // The selected army's orders, from its centre down column 1
+    (st.selected_col, st.selected_row) = (Some(1), Some(0));
+    let target = render(&st);
+    assert!(has(&target, &DrawCommand::MoveTo(21.0, 10.0)));
+    assert!(has(&target, &DrawCommand::LineTo(21.0, 205.0)));
+    assert!(has(&target, &DrawCommand::StrokeStyle("rgba(210,168,255,0.9)".to_string())));
+    assert!(!flight(&target));
+
+    // Everyone's with the toggle on, the unselected fighter fainter
+    st.show_all_orders = true;
+    let target = render(&st);
+    assert!(has(&target, &DrawCommand::StrokeStyle("rgba(57,197,207,0.5)".to_string())));
+    assert_snapshot("marching_orders_and_flight_paths", &target.dump());
+}
+
+#[test]
+fn fighter_fuel_range() {
+    let mut value = base_state();
+    value["cells"][1][6] = json!({"t": "land", "u": {"t": "fighter", "o": "player", "m": "awake", "fuel": 6}});
+    let mut st = state_from(value.clone());
+    (st.selected_col, st.selected_row) = (Some(1), Some(6));
+    let target = render(&st);
+
+    // Rows 0-11 are all in range; the home city at (0, 0) is ringed green
+    assert!(has(&target, &DrawCommand::FillStyle("rgba(88,166,255,0.08)".to_string())));
+    assert!(has(&target, &DrawCommand::FillRect(0.0, 0.0, 56.0, 240.0)));
+    assert!(has(&target, &DrawCommand::StrokeRect(1.5, 1.5, 11.0, 17.0)));
+    assert!(!target.texts().iter().any(|t| t.contains("no city or carrier")));
+    assert_snapshot("fighter_fuel_range", &target.dump());
+
+    // Five squares short of the city: red range and a warning
+    value["cells"][1][6]["u"]["fuel"] = json!(5);
+    value["cells"][1][9] = value["cells"][1][6].clone();
+    let mut st = state_from(value);
+    (st.selected_col, st.selected_row) = (Some(1), Some(9));
+    let target = render(&st);
+    assert!(has(&target, &DrawCommand::FillStyle("rgba(248,81,73,0.08)".to_string())));
+    assert!(has(&target, &DrawCommand::FillRect(0.0, 80.0, 56.0, 160.0)));
+    assert!(target.texts().contains(&"Fighter has 5 fuel: no city or carrier in range"));
+}
+
+#[test]
+fn damaged_units_show_hits() {
+    let mut value = base_state();
+    value["cells"][2][1]["u"]["h"] = json!(1);
+    value["cells"][2][3] = json!({"t": "sea", "u": {"t": "battleship", "o": "player", "m": "awake", "h": 4}});
+    let target = render(&state_from(value));
+
+    // One pip of three on the destroyer, a 4/10 bar on the battleship
+    assert!(has(&target, &DrawCommand::FillRect(30.0, 37.0, 8.0 / 3.0, 2.0)));
+    assert!(!has(&target, &DrawCommand::FillRect(30.0 + 8.0 / 3.0 + 1.0, 37.0, 8.0 / 3.0, 2.0)));
+    assert!(has(&target, &DrawCommand::FillRect(30.0, 77.0, 4.0, 2.0)));
+    // The undamaged army keeps its plain colour; the battleship is tinted
+    assert!(has(&target, &DrawCommand::FillStyle("rgb(240,246,252)".to_string())));
+    assert!(has(&target, &DrawCommand::FillStyle("rgb(244,163,162)".to_string())));
+    assert_snapshot("damaged_units", &target.dump());
+}
+
+#[test]
+fn cargo_badges() {
+    let mut value = base_state();
+    value["cells"][0][0]["fc"] = json!(3);
+    value["cells"][2][3] = json!({"t": "sea", "ac": 5, "u": {"t": "transport", "o": "player", "m": "sentry"}});
+    value["cells"][2][5] = json!({"t": "sea", "fc": 6, "af": 2, "u": {"t": "carrier", "o": "player", "m": "sentry"}});
+    let mut st = state_from(value);
+    let target = render(&st);
+
+    let texts = target.texts();
+    assert!(["3", "5", "6"].iter().all(|n| texts.contains(n)));
+    // The carrier has awake fighters: its badge is accented
+    let carrier_badge = target.commands().iter().position(|c| matches!(c, DrawCommand::FillText(t, ..) if t == "6"));
+    let accent = DrawCommand::FillStyle("rgb(240,136,62)".to_string());
+    assert_eq!(target.commands()[carrier_badge.unwrap() - 3], accent);
+    assert_snapshot("cargo_badges", &target.dump());
+
+    // Too small to read when zoomed out
+    st.camera.zoom_at(-1, 0.0, 0.0);
+    assert!(!render(&st).texts().contains(&"6"));
+}
+
+#[test]
+fn icon_units_mark_the_owner_by_colour() {
+    let mut st = state_from(base_state());
+    st.unit_style = UnitStyle::Icons;
+    let target = render(&st);
+
+    // No unit letters, only the production item's icon and unit paths
+    let texts = target.texts();
+    assert!(!texts.contains(&"A") && !texts.contains(&"d"));
+    // The computer destroyer is red, outlined in its mode colour
+    assert!(has(&target, &DrawCommand::FillStyle("rgb(218,54,51)".to_string())));
+    assert!(has(&target, &DrawCommand::StrokeStyle("rgb(240,136,62)".to_string())));
+    assert_snapshot("icon_units", &target.dump());
+}
+
+#[test]
+fn moved_units_slide_to_their_new_cell() {
+    let mut st = state_from(base_state());
+    let mut renderer = Renderer::new(RecordingTarget::new());
+    renderer.render(&st, 0.0, WALL_MS);
+
+    // The army steps down a row; halfway through the slide it sits
+    // between the two cells, not on either
+    st.apply_frame(r#"{"type":"patch","seq":2,"cells":[[1,0,{"t":"land","cid":1}],[1,1,{"t":"land","cid":1,"u":{"t":"army","o":"player","m":"awake"}}]]}"#)
+        .unwrap();
+    renderer.render(&st, 1000.0, WALL_MS);
+    renderer.target_mut().clear();
+    renderer.render(&st, 1000.0 + st.move_animation_ms / 2.0, WALL_MS);
+    let army = |y: f64| DrawCommand::FillText("A".to_string(), 17.0, y);
+    assert!(has(renderer.target(), &army(25.0)));
+    assert!(!has(renderer.target(), &army(15.0)));
+    assert!(!has(renderer.target(), &army(35.0)));
+
+    // Once it's over the army is drawn in its cell
+    renderer.target_mut().clear();
+    renderer.render(&st, 1000.0 + st.move_animation_ms, WALL_MS);
+    assert!(has(renderer.target(), &army(35.0)));
+}
+
+#[test]
+fn battle_effects_follow_snapshot_changes() {
+    let mut st = state_from(base_state());
+    let mut renderer = Renderer::new(RecordingTarget::new());
+    renderer.render(&st, 0.0, WALL_MS);
+
+    // The destroyer loses two hits and sinks the army beside it
+    st.apply_frame(r#"{"type":"patch","seq":2,"cells":[[1,0,{"t":"land","cid":1}],[2,1,{"t":"sea","u":{"t":"destroyer","o":"computer","m":"sentry","h":1}}]]}"#)
+        .unwrap();
+    renderer.render(&st, 1000.0, WALL_MS);
+    assert!(has(renderer.target(), &DrawCommand::FillRect(14.0, 0.0, 14.0, 20.0)));
+    assert!(has(renderer.target(), &DrawCommand::FillText("-2".to_string(), 31.0, 35.0)));
+
+    // The lost hits float up as they fade, then go
+    renderer.target_mut().clear();
+    renderer.render(&st, 1000.0 + DAMAGED_MS / 2.0, WALL_MS);
+    assert!(has(renderer.target(), &DrawCommand::FillText("-2".to_string(), 31.0, 27.0)));
+    assert!(has(renderer.target(), &DrawCommand::GlobalAlpha(0.5)));
+    renderer.target_mut().clear();
+    renderer.render(&st, 1000.0 + DAMAGED_MS, WALL_MS);
+    assert!(!renderer.target().texts().contains(&"-2"));
+}
+
+#[test]
+fn snapshots_that_dont_follow_on_start_no_effects() {
+    // From one snapshot to the next the army is gone and the destroyer
+    // beside it has lost two hits, which would read as a battle
+    let battle_drawn = |first: Value, next: Value| {
+        let mut st = state_from(first);
+        let mut renderer = Renderer::new(RecordingTarget::new());
+        renderer.render(&st, 0.0, WALL_MS);
+        st.apply_frame(&next.to_string()).unwrap();
+        renderer.target_mut().clear();
+        renderer.render(&st, 1000.0, WALL_MS);
+        has(renderer.target(), &DrawCommand::FillRect(14.0, 0.0, 14.0, 20.0)) || renderer.target().texts().contains(&"-2")
+    };
+    let patch = |seq: u64, view: Option<&str>| {
+        let mut p = json!({"type": "patch", "seq": seq, "cells": [
+            [1, 0, {"t": "land", "cid": 1}],
+            [2, 1, {"t": "sea", "u": {"t": "destroyer", "o": "computer", "m": "sentry", "h": 1}}],
+        ]});
+        if let Some(view) = view {
+            p["map_to_display"] = json!(view);
+        }
+        p
+    };
+    assert!(battle_drawn(base_state(), patch(2, None)));
+
+    // Toggling to the computer's map only shows it differently
+    assert!(!battle_drawn(base_state(), patch(2, Some("computer-map"))));
+
+    // Seeking back to an earlier snapshot skips over what happened
+    let mut later = base_state();
+    later["seq"] = json!(5);
+    let mut earlier = base_state();
+    earlier["seq"] = json!(2);
+    earlier["cells"][1][0] = json!({"t": "land", "cid": 1});
+    earlier["cells"][2][1]["u"]["h"] = json!(1);
+    assert!(!battle_drawn(later, earlier));
+}
@@ -0,0 +1,312 @@
+//! Stand-in for the game server when working on the client: serves
+//! `resources/public`, plays state fixtures to every `/ws` client and logs
+//! the messages clients send back.
+//!
+//!     empire-mock-server [--port 8080] [--root resources/public]
+//!                        [--interval MS] [--loop] <fixture.json | timeline>...
+//!
+//! Fixtures given directly are shown `--interval` apart (default 3000 ms);
+//! timeline files (see `timeline.rs`) carry their own offsets. With
+//! `--loop` the whole sequence repeats, otherwise the last state stays up.
+//! `client/mock-server/fixtures` has one fixture per overlay and a tour.
+
+mod timeline;
+
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use empire_client_core::protocol::{ClientMessage, Feature, PROTOCOL_VERSION};
+use serde_json::json;
+use tungstenite::{Message, WebSocket};
+
+use timeline::{load_fixture, load_timeline, stamp, Entry};
+
+const USAGE: &str = "usage: empire-mock-server [--port N] [--root DIR] [--interval MS] [--loop] <fixture.json | timeline>...";
+// How often client threads look for a new frame between reads.
+const POLL: Duration = Duration::from_millis(20);
+
+struct Config {
+    port: u16,
+    root: PathBuf,
+    looping: bool,
+    entries: Vec<Entry>,
+    /// Time from the start of the sequence until it repeats.
+    period: Duration,
+}
+
+fn parse_args(args: &[String]) -> Result<Config, String> {
+    let mut port = 8080;
+    let mut root = PathBuf::from("resources/public");
+    let mut interval = Duration::from_millis(3000);
+    let mut looping = false;
+    let mut inputs = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--port" => port = value()?.parse().map_err(|_| format!("{}: not a port", arg))?,
+            "--root" => root = PathBuf::from(value()?),
+            "--interval" => {
+                interval = Duration::from_millis(value()?.parse().map_err(|_| format!("{}: not a number", arg))?)
+            }
+            "--loop" => looping = true,
+            path if !path.starts_with("--") => inputs.push(PathBuf::from(path)),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    if inputs.is_empty() {
+        return Err(USAGE.to_string());
+    }
+
+    // Lay the inputs end to end, each followed by one interval
+    let mut entries = Vec::new();
+    let mut offset = Duration::ZERO;
+    for path in inputs {
+        let loaded = if path.extension().is_some_and(|e| e == "json") {
+            vec![load_fixture(&path)?]
+        } else {
+            load_timeline(&path)?
+        };
+        let length = loaded.last().map_or(Duration::ZERO, |e| e.at);
+        entries.extend(loaded.into_iter().map(|e| Entry { at: offset + e.at, ..e }));
+        offset += length + interval;
+    }
+    Ok(Config { port, root, looping, entries, period: offset })
+}
+
+/// The frame every client should be showing, numbered by `seq`.
+#[derive(Default)]
+struct Current {
+    seq: u64,
+    frame: String,
+}
+
+type Shared = Arc<Mutex<Current>>;
+
+fn now_ms() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0.0, |d| d.as_millis() as f64)
+}
+
+fn publish(current: &Shared, entry: &Entry) {
+    let mut current = current.lock().unwrap_or_else(|e| e.into_inner());
+    current.seq += 1;
+    current.frame = stamp(&entry.frame, current.seq, now_ms()).to_string();
+    println!("showing {} (seq {})", entry.name, current.seq);
+}
+
+fn play(config: &Config, current: &Shared) {
+    loop {
+        let start = Instant::now();
+        for entry in &config.entries {
+            thread::sleep((start + entry.at).saturating_duration_since(Instant::now()));
+            publish(current, entry);
+        }
+        if !config.looping {
+            return;
+        }
+        thread::sleep((start + config.period).saturating_duration_since(Instant::now()));
+    }
+}
+
+fn welcome(features: &[Feature]) -> String {
+    // Only full states are sent, so patches are never agreed
+    let agreed: Vec<&Feature> = features.iter().filter(|f| **f == Feature::Overlays).collect();
+    json!({
+        "type": "welcome",
+        "protocol_version": PROTOCOL_VERSION,
+        "min_protocol_version": PROTOCOL_VERSION,
+        "compatible": true,
+        "features": agreed,
+    })
+    .to_string()
+}
+
+fn serve_client(id: usize, mut ws: WebSocket<TcpStream>, current: Shared) -> tungstenite::Result<()> {
+    ws.get_mut().set_read_timeout(Some(POLL))?;
+    let mut sent_seq = 0;
+    loop {
+        let (seq, frame) = {
+            let current = current.lock().unwrap_or_else(|e| e.into_inner());
+            (current.seq, (current.seq != sent_seq).then(|| current.frame.clone()))
+        };
+        if let Some(frame) = frame {
+            ws.send(Message::text(frame))?;
+            sent_seq = seq;
+        }
+
+        let text = match ws.read() {
+            Ok(Message::Text(text)) => text,
+            Ok(Message::Close(_)) => return Ok(()),
+            Ok(_) => continue,
+            Err(tungstenite::Error::Io(err))
+                if matches!(err.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) =>
+            {
+                continue
+            }
+            Err(err) => return Err(err),
+        };
+        match serde_json::from_str::<ClientMessage>(text.as_str()) {
+            Ok(msg) => {
+                println!("client {}: {}", id, text);
+                match msg {
+                    ClientMessage::Hello { features, .. } => ws.send(Message::text(welcome(&features)))?,
+                    // Send the current frame again on the next pass
+                    ClientMessage::Resync => sent_seq = 0,
+                    _ => {}
+                }
+            }
+            Err(err) => println!("client {}: {}  (not a ClientMessage: {})", id, text, err),
+        }
+    }
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).unwrap_or("") {
+        "html" => "text/html",
+        "js" => "application/javascript",
+        "wasm" => "application/wasm",
+        "css" => "text/css",
+        "json" => "application/json",
+        _ => "application/octet-stream",
+    }
+}
+
+/// File under `root` for a request path, as the server's `serve-static`
+/// maps it. `None` for paths that try to leave `root`.
+fn static_path(root: &Path, uri: &str) -> Option<PathBuf> {
+    let path = uri.split(['?', '#']).next().unwrap_or("/");
+    let path = if path == "/" { "/index.html" } else { path };
+    if path.split('/').any(|part| part == "..") {
+        return None;
+    }
+    Some(root.join(path.trim_start_matches('/')))
+}
+
+fn serve_static(mut stream: TcpStream, root: &Path, uri: &str) -> io::Result<()> {
+    let file = static_path(root, uri).filter(|p| p.is_file());
+    let (status, kind, body) = match &file {
+        Some(path) => ("200 OK", content_type(path), std::fs::read(path)?),
+        None => ("404 Not Found", "text/plain", b"Not found".to_vec()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        kind,
+        body.len()
+    )?;
+    stream.write_all(&body)
+}
+
+/// Request path from the first line of an HTTP request, without
+/// consuming it, so a WebSocket handshake can still read the request.
+fn peek_uri(stream: &TcpStream) -> io::Result<String> {
+    let mut buf = [0u8; 2048];
+    for _ in 0..50 {
+        let n = stream.peek(&mut buf)?;
+        let head = String::from_utf8_lossy(&buf[..n]);
+        if let Some(line) = head.lines().next().filter(|_| head.contains('\n')) {
+            return Ok(line.split_whitespace().nth(1).unwrap_or("/").to_string());
+        }
+        if n == 0 || n == buf.len() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    Err(io::Error::new(io::ErrorKind::InvalidData, "no request line"))
+}
+
+fn handle_connection(stream: TcpStream, root: &Path, current: Shared, next_id: &AtomicUsize) -> io::Result<()> {
+    let uri = peek_uri(&stream)?;
+    if uri != "/ws" {
+        // Drain the request head before answering
+        let mut stream = stream;
+        stream.set_read_timeout(Some(POLL))?;
+        let _ = stream.read(&mut [0u8; 4096]);
+        return serve_static(stream, root, &uri);
+    }
+    let ws = tungstenite::accept(stream).map_err(|err| io::Error::other(err.to_string()))?;
+    let id = next_id.fetch_add(1, Ordering::Relaxed);
+    println!("client {} connected", id);
+    let result = serve_client(id, ws, current);
+    println!("client {} disconnected", id);
+    result.map_err(|err| io::Error::other(err.to_string()))
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let config = match parse_args(&args) {
+        Ok(config) => Arc::new(config),
+        Err(err) => {
+            eprintln!("{}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+    let listener = match TcpListener::bind(("0.0.0.0", config.port)) {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("port {}: {}", config.port, err);
+            return ExitCode::FAILURE;
+        }
+    };
+    println!("mock server on http://localhost:{} serving {}", config.port, config.root.display());
+
+    let current = Shared::default();
+    {
+        let (config, current) = (Arc::clone(&config), Arc::clone(&current));
+        thread::spawn(move || play(&config, &current));
+    }
+    let next_id = Arc::new(AtomicUsize::new(1));
+    for stream in listener.incoming().flatten() {
+        let (config, current, next_id) = (Arc::clone(&config), Arc::clone(&current), Arc::clone(&next_id));
+        thread::spawn(move || {
+            if let Err(err) = handle_connection(stream, &config.root, current, &next_id) {
+                println!("connection error: {}", err);
+            }
+        });
+    }
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixtures(name: &str) -> String {
+        format!("{}/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    #[test]
+    fn lays_inputs_end_to_end() {
+        let args = vec![fixtures("map.json"), "--interval".into(), "500".into(), fixtures("tour.timeline"), fixtures("paused.json")];
+        let config = parse_args(&args).unwrap();
+        let at: Vec<u64> = config.entries.iter().map(|e| e.at.as_millis() as u64).collect();
+        assert_eq!(at, vec![0, 500, 3500, 6500, 9500, 12500, 15500, 16000]);
+        assert_eq!(config.period, Duration::from_millis(16500));
+        assert!(!config.looping);
+        assert!(parse_args(&[]).is_err());
+    }
+
+    #[test]
+    fn maps_request_paths_like_the_server() {
+        let root = Path::new("public");
+        assert_eq!(static_path(root, "/"), Some(PathBuf::from("public/index.html")));
+        assert_eq!(static_path(root, "/pkg/empire_client_bg.wasm?v=2"), Some(PathBuf::from("public/pkg/empire_client_bg.wasm")));
+        assert_eq!(static_path(root, "/../deps.edn"), None);
+        assert_eq!(content_type(Path::new("a/b.wasm")), "application/wasm");
+        assert_eq!(content_type(Path::new("README")), "application/octet-stream");
+    }
+
+    #[test]
+    fn agrees_on_overlays_only() {
+        let welcome: serde_json::Value = serde_json::from_str(&welcome(&[Feature::Patches, Feature::Overlays])).unwrap();
+        assert_eq!(welcome["features"], json!(["overlays"]));
+        assert_eq!(welcome["compatible"], true);
+    }
+}
@@ -0,0 +1,131 @@
+//! What the mock server plays: state fixtures at fixed offsets.
+//!
+//! A fixture is one `state` frame as the server sends it. A timeline file
+//! lists fixtures with the time they should appear:
+//!
+//!     # <at ms>  <fixture, relative to the timeline file>
+//!     0       map.json
+//!     3000    load_menu.json
+//!
+//! `error_until` below `RELATIVE_LIMIT_MS` is taken as relative to when the
+//! frame is sent, so error banners in fixtures don't start out expired.
+
+use std::path::Path;
+use std::time::Duration;
+
+use empire_client_core::protocol::ServerMessage;
+use serde_json::Value;
+
+// Anything earlier than 2001-09-09 is not a wall-clock timestamp.
+const RELATIVE_LIMIT_MS: f64 = 1e12;
+
+/// A fixture to show from `at` after the timeline starts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub at: Duration,
+    pub name: String,
+    pub frame: Value,
+}
+
+/// Parse a fixture and check that it decodes as a `state` frame.
+pub fn parse_fixture(text: &str) -> Result<Value, String> {
+    let frame: Value = serde_json::from_str(text).map_err(|err| err.to_string())?;
+    match serde_json::from_value::<ServerMessage>(frame.clone()) {
+        Ok(ServerMessage::State(_)) => Ok(frame),
+        Ok(_) => Err("not a state frame".to_string()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+pub fn load_fixture(path: &Path) -> Result<Entry, String> {
+    let text = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    let frame = parse_fixture(&text).map_err(|err| format!("{}: {}", path.display(), err))?;
+    Ok(Entry { at: Duration::ZERO, name: path.display().to_string(), frame })
+}
+
+/// Parse timeline text into (at, fixture path) pairs, sorted by time.
+pub fn parse_timeline(text: &str) -> Result<Vec<(Duration, String)>, String> {
+    let mut steps = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let [at, path] = line.split_whitespace().collect::<Vec<_>>()[..] else {
+            return Err(format!("line {}: expected <at ms> <fixture>", i + 1));
+        };
+        let at: u64 = at.parse().map_err(|_| format!("line {}: not a number: {}", i + 1, at))?;
+        steps.push((Duration::from_millis(at), path.to_string()));
+    }
+    steps.sort_by_key(|(at, _)| *at);
+    Ok(steps)
+}
+
+pub fn load_timeline(path: &Path) -> Result<Vec<Entry>, String> {
+    let text = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    let steps = parse_timeline(&text).map_err(|err| format!("{}: {}", path.display(), err))?;
+    let dir = path.parent().unwrap_or(Path::new("."));
+    steps
+        .into_iter()
+        .map(|(at, fixture)| Ok(Entry { at, ..load_fixture(&dir.join(fixture))? }))
+        .collect()
+}
+
+/// The frame as sent: numbered with `seq`, with a relative `error_until`
+/// made absolute.
+pub fn stamp(frame: &Value, seq: u64, now_ms: f64) -> Value {
+    let mut frame = frame.clone();
+    frame["seq"] = seq.into();
+    if let Some(until) = frame["error_until"].as_f64() {
+        if until > 0.0 && until < RELATIVE_LIMIT_MS {
+            frame["error_until"] = (now_ms + until).into();
+        }
+    }
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_text() -> String {
+        std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/map.json")).unwrap()
+    }
+
+    #[test]
+    fn fixtures_must_be_state_frames() {
+        assert!(parse_fixture(&fixture_text()).is_ok());
+        assert_eq!(parse_fixture(r#"{"type":"welcome","protocol_version":1,"min_protocol_version":1,"compatible":true,"features":[]}"#).unwrap_err(), "not a state frame");
+        assert!(parse_fixture(r#"{"type":"state","round":1}"#).unwrap_err().contains("missing field"));
+    }
+
+    #[test]
+    fn parses_timelines() {
+        let steps = parse_timeline("# intro\n3000 b.json\n0   a.json  # first\n\n").unwrap();
+        assert_eq!(
+            steps,
+            vec![(Duration::ZERO, "a.json".to_string()), (Duration::from_millis(3000), "b.json".to_string())]
+        );
+        assert_eq!(parse_timeline("0 a.json\nsoon b.json").unwrap_err(), "line 2: not a number: soon");
+        assert_eq!(parse_timeline("a.json").unwrap_err(), "line 1: expected <at ms> <fixture>");
+    }
+
+    #[test]
+    fn bundled_timeline_loads() {
+        let entries = load_timeline(Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/tour.timeline"))).unwrap();
+        assert_eq!(entries.len(), 6);
+        assert!(entries.windows(2).all(|w| w[0].at < w[1].at));
+    }
+
+    #[test]
+    fn stamps_seq_and_relative_error_deadline() {
+        let mut frame = parse_fixture(&fixture_text()).unwrap();
+        frame["error_until"] = 5000.into();
+        let sent = stamp(&frame, 9, 1.7e12);
+        assert_eq!(sent["seq"], 9);
+        assert_eq!(sent["error_until"].as_f64(), Some(1.7e12 + 5000.0));
+
+        frame["error_until"] = 1.7e12.into();
+        assert_eq!(stamp(&frame, 10, 1.8e12)["error_until"].as_f64(), Some(1.7e12));
+    }
+}
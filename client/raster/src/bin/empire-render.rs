@@ -0,0 +1,66 @@
+//! Render a saved state snapshot to a PNG, e.g. for bug reports.
+//!
+//!     empire-render <snapshot.json> <out.png> [--panels]
+//!
+//! The snapshot is a `state` frame as sent by the server. A file with one
+//! frame per line (a state followed by patches) is applied in order.
+
+use std::process::ExitCode;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use empire_client_core::state::GameState;
+use empire_client_raster::render_state;
+
+const USAGE: &str = "usage: empire-render <snapshot.json> <out.png> [--panels]";
+
+fn load(text: &str) -> Result<GameState, String> {
+    let mut state = GameState::new();
+    if state.apply_frame(text).is_err() {
+        state = GameState::new();
+        for (i, line) in text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            state
+                .apply_frame(line)
+                .map_err(|err| format!("line {}: {}", i + 1, err))?;
+        }
+    }
+    if state.map_size.0 == 0 {
+        return Err("no state frame found".to_string());
+    }
+    Ok(state)
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let panels = args.iter().any(|a| a == "--panels");
+    let paths: Vec<&String> = args.iter().filter(|a| !a.starts_with("--")).collect();
+    let [input, output] = paths[..] else {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    };
+
+    let text = match std::fs::read_to_string(input) {
+        Ok(text) => text,
+        Err(err) => {
+            eprintln!("{}: {}", input, err);
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut state = match load(&text) {
+        Ok(state) => state,
+        Err(err) => {
+            eprintln!("{}: {}", input, err);
+            return ExitCode::FAILURE;
+        }
+    };
+    state.show_help_overlay = panels;
+    state.show_inspector = panels;
+
+    let wall_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0.0, |d| d.as_millis() as f64);
+    if let Err(err) = render_state(&state, wall_ms).save_png(output) {
+        eprintln!("{}: {}", output, err);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
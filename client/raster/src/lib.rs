@@ -0,0 +1,427 @@
+//! Software rasterizer for the core renderer. `RasterTarget` paints the
+//! renderer's draw calls into an in-memory RGBA image with tiny-skia, so
+//! frames can be rendered to PNG without a browser: golden-image tests,
+//! and map images from saved state snapshots for bug reports.
+//!
+//! Text uses the embedded font8x8 bitmap glyphs, scaled to the monospace
+//! advance the renderer lays text out with.
+
+use std::io;
+use std::path::Path as FsPath;
+
+use empire_client_core::draw::{font_px, DrawTarget, MONO_ADVANCE};
+use empire_client_core::layout::Rect as LayoutRect;
+use empire_client_core::renderer::Renderer;
+use empire_client_core::state::GameState;
+use font8x8::{UnicodeFonts, BASIC_FONTS, BLOCK_FONTS, BOX_FONTS, LATIN_FONTS};
+use tiny_skia::{
+    Color, FillRule, FilterQuality, Paint, Path, PathBuilder, Pattern, Pixmap, Rect, SpreadMode, Stroke,
+    Transform,
+};
+
+/// Parse the CSS colours the renderer emits: `rgb(r,g,b)`, `rgba(r,g,b,a)`,
+/// `#rrggbb` and a few keywords. Returns `None` for anything else, which
+/// (as on a canvas) leaves the current style unchanged.
+pub fn parse_css_color(style: &str) -> Option<Color> {
+    let style = style.trim();
+    match style {
+        "white" => return Some(Color::WHITE),
+        "black" => return Some(Color::BLACK),
+        "transparent" => return Some(Color::TRANSPARENT),
+        _ => {}
+    }
+    if let Some(hex) = style.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        return Some(Color::from_rgba8(channel(0)?, channel(2)?, channel(4)?, 255));
+    }
+    let (args, has_alpha) = if let Some(rest) = style.strip_prefix("rgba(") {
+        (rest.strip_suffix(')')?, true)
+    } else if let Some(rest) = style.strip_prefix("rgb(") {
+        (rest.strip_suffix(')')?, false)
+    } else {
+        return None;
+    };
+    let parts: Vec<&str> = args.split(',').map(str::trim).collect();
+    if parts.len() != if has_alpha { 4 } else { 3 } {
+        return None;
+    }
+    let channel = |s: &str| s.parse::<f32>().ok().map(|v| v.clamp(0.0, 255.0).round() as u8);
+    let alpha = if has_alpha {
+        parts[3].parse::<f32>().ok()?.clamp(0.0, 1.0)
+    } else {
+        1.0
+    };
+    let mut color = Color::from_rgba8(channel(parts[0])?, channel(parts[1])?, channel(parts[2])?, 255);
+    color.set_alpha(alpha);
+    Some(color)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PathOp {
+    MoveTo(f32, f32),
+    LineTo(f32, f32),
+    Close,
+}
+
+/// A [`DrawTarget`] backed by a tiny-skia pixmap.
+pub struct RasterTarget {
+    pixmap: Option<Pixmap>,
+    fill: Color,
+    stroke: Color,
+    line_width: f32,
+    global_alpha: f32,
+    font_px: f64,
+    text_align: String,
+    text_baseline: String,
+    path: Vec<PathOp>,
+}
+
+impl Default for RasterTarget {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RasterTarget {
+    /// An empty target; the renderer sizes it on the first frame.
+    pub fn new() -> Self {
+        Self {
+            pixmap: None,
+            fill: Color::BLACK,
+            stroke: Color::BLACK,
+            line_width: 1.0,
+            global_alpha: 1.0,
+            font_px: font_px(""),
+            text_align: "left".to_string(),
+            text_baseline: "alphabetic".to_string(),
+            path: Vec::new(),
+        }
+    }
+
+    /// The image as straight (non-premultiplied) RGBA, row by row.
+    pub fn to_rgba(&self) -> Vec<u8> {
+        let Some(pixmap) = &self.pixmap else {
+            return Vec::new();
+        };
+        pixmap
+            .pixels()
+            .iter()
+            .flat_map(|p| {
+                let c = p.demultiply();
+                [c.red(), c.green(), c.blue(), c.alpha()]
+            })
+            .collect()
+    }
+
+    /// The image encoded as PNG.
+    pub fn encode_png(&self) -> io::Result<Vec<u8>> {
+        let pixmap = self
+            .pixmap
+            .as_ref()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "nothing has been rendered"))?;
+        pixmap.encode_png().map_err(io::Error::other)
+    }
+
+    /// Write the image to `path` as PNG.
+    pub fn save_png(&self, path: impl AsRef<FsPath>) -> io::Result<()> {
+        std::fs::write(path, self.encode_png()?)
+    }
+
+    fn paint(&self, color: Color) -> Paint<'static> {
+        let mut color = color;
+        color.apply_opacity(self.global_alpha);
+        let mut paint = Paint::default();
+        paint.set_color(color);
+        paint.anti_alias = true;
+        paint
+    }
+
+    fn fill_path(&mut self, path: &Path, color: Color) {
+        let paint = self.paint(color);
+        if let Some(pixmap) = self.pixmap.as_mut() {
+            pixmap.fill_path(path, &paint, FillRule::Winding, Transform::identity(), None);
+        }
+    }
+
+    fn stroke_path(&mut self, path: &Path) {
+        let paint = self.paint(self.stroke);
+        let stroke = Stroke {
+            width: self.line_width,
+            ..Stroke::default()
+        };
+        if let Some(pixmap) = self.pixmap.as_mut() {
+            pixmap.stroke_path(path, &paint, &stroke, Transform::identity(), None);
+        }
+    }
+
+    fn current_path(&self) -> Option<Path> {
+        let mut pb = PathBuilder::new();
+        for op in &self.path {
+            match *op {
+                PathOp::MoveTo(x, y) => pb.move_to(x, y),
+                PathOp::LineTo(x, y) => pb.line_to(x, y),
+                PathOp::Close => pb.close(),
+            }
+        }
+        pb.finish()
+    }
+}
+
+fn glyph(c: char) -> Option<[u8; 8]> {
+    BASIC_FONTS
+        .get(c)
+        .or_else(|| LATIN_FONTS.get(c))
+        .or_else(|| BOX_FONTS.get(c))
+        .or_else(|| BLOCK_FONTS.get(c))
+}
+
+fn rect(x: f64, y: f64, w: f64, h: f64) -> Option<Rect> {
+    // Canvas accepts negative sizes and draws towards the origin
+    let (x, w) = if w < 0.0 { (x + w, -w) } else { (x, w) };
+    let (y, h) = if h < 0.0 { (y + h, -h) } else { (y, h) };
+    Rect::from_xywh(x as f32, y as f32, w as f32, h as f32)
+}
+
+impl DrawTarget for RasterTarget {
+    fn size(&self) -> (u32, u32) {
+        self.pixmap
+            .as_ref()
+            .map_or((0, 0), |p| (p.width(), p.height()))
+    }
+
+    fn set_size(&mut self, width: u32, height: u32) {
+        self.pixmap = Pixmap::new(width, height);
+    }
+
+    fn set_fill_style(&mut self, style: &str) {
+        if let Some(color) = parse_css_color(style) {
+            self.fill = color;
+        }
+    }
+
+    fn set_stroke_style(&mut self, style: &str) {
+        if let Some(color) = parse_css_color(style) {
+            self.stroke = color;
+        }
+    }
+
+    fn set_line_width(&mut self, width: f64) {
+        if width.is_finite() && width > 0.0 {
+            self.line_width = width as f32;
+        }
+    }
+
+    fn set_global_alpha(&mut self, alpha: f64) {
+        if (0.0..=1.0).contains(&alpha) {
+            self.global_alpha = alpha as f32;
+        }
+    }
+
+    fn set_font(&mut self, font: &str) {
+        self.font_px = font_px(font);
+    }
+
+    fn set_text_align(&mut self, align: &str) {
+        self.text_align = align.to_string();
+    }
+
+    fn set_text_baseline(&mut self, baseline: &str) {
+        self.text_baseline = baseline.to_string();
+    }
+
+    fn fill_rect(&mut self, x: f64, y: f64, w: f64, h: f64) {
+        if let Some(r) = rect(x, y, w, h) {
+            self.fill_path(&PathBuilder::from_rect(r), self.fill);
+        }
+    }
+
+    fn stroke_rect(&mut self, x: f64, y: f64, w: f64, h: f64) {
+        if let Some(r) = rect(x, y, w, h) {
+            self.stroke_path(&PathBuilder::from_rect(r));
+        }
+    }
+
+    fn begin_path(&mut self) {
+        self.path.clear();
+    }
+
+    fn move_to(&mut self, x: f64, y: f64) {
+        self.path.push(PathOp::MoveTo(x as f32, y as f32));
+    }
+
+    fn line_to(&mut self, x: f64, y: f64) {
+        if self.path.is_empty() {
+            self.path.push(PathOp::MoveTo(x as f32, y as f32));
+        } else {
+            self.path.push(PathOp::LineTo(x as f32, y as f32));
+        }
+    }
+
+    fn close_path(&mut self) {
+        self.path.push(PathOp::Close);
+    }
+
+    fn stroke(&mut self) {
+        if let Some(path) = self.current_path() {
+            self.stroke_path(&path);
+        }
+    }
+
+    fn fill(&mut self) {
+        if let Some(path) = self.current_path() {
+            self.fill_path(&path, self.fill);
+        }
+    }
+
+    fn fill_text(&mut self, text: &str, x: f64, y: f64) {
+        let width = self.measure_text(text);
+        let left = match self.text_align.as_str() {
+            "right" | "end" => x - width,
+            "center" => x - width / 2.0,
+            _ => x,
+        };
+        // Glyphs are 8x8 with the baseline under row 6; scale them to one
+        // monospace advance by the font size.
+        let (sx, sy) = (self.font_px * MONO_ADVANCE / 8.0, self.font_px / 8.0);
+        let top = match self.text_baseline.as_str() {
+            "top" | "hanging" => y,
+            "middle" => y - 4.0 * sy,
+            "bottom" => y - 8.0 * sy,
+            _ => y - 7.0 * sy,
+        };
+
+        let mut pb = PathBuilder::new();
+        for (i, c) in text.chars().enumerate() {
+            let Some(rows) = glyph(c) else { continue };
+            let gx = left + i as f64 * 8.0 * sx;
+            for (row, bits) in rows.iter().enumerate() {
+                for col in 0..8 {
+                    if bits & (1 << col) != 0 {
+                        if let Some(r) = rect(gx + col as f64 * sx, top + row as f64 * sy, sx, sy) {
+                            pb.push_rect(r);
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(path) = pb.finish() {
+            self.fill_path(&path, self.fill);
+        }
+    }
+
+    fn measure_text(&self, text: &str) -> f64 {
+        text.chars().count() as f64 * self.font_px * MONO_ADVANCE
+    }
+
+    fn create_layer(&self) -> Option<Self> {
+        Some(Self::new())
+    }
+
+    fn draw_layer(&mut self, layer: &Self, src: LayoutRect, dst: LayoutRect) {
+        let (Some(pixmap), Some(from)) = (self.pixmap.as_mut(), layer.pixmap.as_ref()) else {
+            return;
+        };
+        let Some(area) = rect(dst.x, dst.y, dst.w, dst.h).filter(|_| src.w > 0.0 && src.h > 0.0) else {
+            return;
+        };
+        // Map the source rectangle onto the destination, sampling nearest
+        // pixels like the browser canvas with smoothing off
+        let (sx, sy) = (dst.w / src.w, dst.h / src.h);
+        let transform = Transform::from_row(
+            sx as f32,
+            0.0,
+            0.0,
+            sy as f32,
+            (dst.x - src.x * sx) as f32,
+            (dst.y - src.y * sy) as f32,
+        );
+        let paint = Paint {
+            shader: Pattern::new(from.as_ref(), SpreadMode::Pad, FilterQuality::Nearest, self.global_alpha, transform),
+            ..Paint::default()
+        };
+        pixmap.fill_rect(area, &paint, Transform::identity(), None);
+    }
+}
+
+/// Render one frame of `state` into a fresh raster target. Animations are
+/// frozen at time zero; `wall_ms` decides which timed banners are shown.
+pub fn render_state(state: &GameState, wall_ms: f64) -> RasterTarget {
+    let mut renderer = Renderer::new(RasterTarget::new());
+    renderer.render(state, 0.0, wall_ms);
+    renderer.into_target()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgba(c: Color) -> [u8; 4] {
+        let c = c.to_color_u8();
+        [c.red(), c.green(), c.blue(), c.alpha()]
+    }
+
+    #[test]
+    fn parses_renderer_colours() {
+        assert_eq!(rgba(parse_css_color("rgb(13,17,23)").unwrap()), [13, 17, 23, 255]);
+        assert_eq!(rgba(parse_css_color("rgba(0,0,0,0.5)").unwrap())[3], 128);
+        assert_eq!(rgba(parse_css_color("white").unwrap()), [255, 255, 255, 255]);
+        assert_eq!(rgba(parse_css_color("#1f6feb").unwrap()), [31, 111, 235, 255]);
+        assert!(parse_css_color("hsl(0, 0%, 0%)").is_none());
+        assert!(parse_css_color("rgb(1,2)").is_none());
+    }
+
+    #[test]
+    fn fills_rects_with_global_alpha() {
+        let mut t = RasterTarget::new();
+        t.set_size(4, 2);
+        t.set_fill_style("rgb(255,0,0)");
+        t.fill_rect(0.0, 0.0, 2.0, 2.0);
+        t.set_global_alpha(0.5);
+        t.set_fill_style("rgb(0,0,255)");
+        t.fill_rect(2.0, 0.0, 2.0, 2.0);
+
+        let px = t.to_rgba();
+        assert_eq!(&px[0..4], &[255, 0, 0, 255]);
+        assert_eq!(px[3 * 4 + 2], 255);
+        assert_eq!(px[3 * 4 + 3], 128);
+    }
+
+    #[test]
+    fn invalid_style_keeps_previous() {
+        let mut t = RasterTarget::new();
+        t.set_size(1, 1);
+        t.set_fill_style("rgb(0,255,0)");
+        t.set_fill_style("not a colour");
+        t.fill_rect(0.0, 0.0, 1.0, 1.0);
+        assert_eq!(t.to_rgba(), vec![0, 255, 0, 255]);
+    }
+
+    #[test]
+    fn text_is_drawn_within_its_measured_box() {
+        let mut t = RasterTarget::new();
+        t.set_size(40, 20);
+        t.set_font("400 10px monospace");
+        t.set_text_baseline("top");
+        t.set_fill_style("white");
+        t.fill_text("AB", 0.0, 0.0);
+        assert_eq!(t.measure_text("AB"), 12.0);
+
+        let px = t.to_rgba();
+        let lit = |x: usize, y: usize| px[(y * 40 + x) * 4 + 3] > 0;
+        assert!((0..12).any(|x| (0..10).any(|y| lit(x, y))));
+        assert!(!(13..40).any(|x| (0..20).any(|y| lit(x, y))));
+    }
+
+    #[test]
+    fn png_round_trips_size() {
+        let mut t = RasterTarget::new();
+        assert!(t.encode_png().is_err());
+        t.set_size(3, 5);
+        let png = t.encode_png().unwrap();
+        let decoded = Pixmap::decode_png(&png).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (3, 5));
+    }
+}
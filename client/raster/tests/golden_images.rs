@@ -0,0 +1,166 @@
+//! Golden-image tests of `Renderer` output through the raster backend.
+//! Run with `UPDATE_GOLDEN=1` to accept changes, then review the PNGs
+//! under `tests/golden/`. On a mismatch the actual image is written next
+//! to the test binaries' scratch directory for comparison.
+
+use std::path::PathBuf;
+
+use empire_client_core::icons::UnitStyle;
+use empire_client_core::state::GameState;
+use empire_client_core::renderer::Renderer;
+use empire_client_core::theme::Theme;
+use empire_client_raster::{render_state, RasterTarget};
+use serde_json::{json, Value};
+use tiny_skia::Pixmap;
+
+const WALL_MS: f64 = 1_700_000_000_000.0;
+// Per-channel slack for anti-aliasing differences between platforms
+const CHANNEL_TOLERANCE: u8 = 2;
+
+fn fixture() -> Value {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "fixtures", "small_map.json"]
+        .iter()
+        .collect();
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+fn state_from(value: Value) -> GameState {
+    let mut st = GameState::new();
+    st.apply_frame(&value.to_string()).unwrap();
+    st.show_help_overlay = false;
+    st.show_inspector = false;
+    st
+}
+
+fn assert_golden(name: &str, state: &GameState) {
+    let png = render_state(state, WALL_MS).encode_png().unwrap();
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "golden", &format!("{}.png", name)]
+        .iter()
+        .collect();
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, &png).unwrap();
+        return;
+    }
+    let expected = Pixmap::load_png(&path)
+        .unwrap_or_else(|_| panic!("missing golden image {:?}; run with UPDATE_GOLDEN=1", path));
+    let actual = Pixmap::decode_png(&png).unwrap();
+
+    let actual_path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("{}.actual.png", name));
+    let size_ok = (expected.width(), expected.height()) == (actual.width(), actual.height());
+    let differing = if size_ok {
+        expected
+            .data()
+            .iter()
+            .zip(actual.data())
+            .filter(|(e, a)| e.abs_diff(**a) > CHANNEL_TOLERANCE)
+            .count()
+    } else {
+        usize::MAX
+    };
+    if differing != 0 {
+        std::fs::write(&actual_path, &png).unwrap();
+        panic!(
+            "golden image {} differs ({} channels; expected {}x{}, got {}x{}); actual written to {:?}, \
+             run with UPDATE_GOLDEN=1 to accept",
+            name,
+            if size_ok { differing.to_string() } else { "size".to_string() },
+            expected.width(),
+            expected.height(),
+            actual.width(),
+            actual.height(),
+            actual_path,
+        );
+    }
+}
+
+#[test]
+fn map() {
+    assert_golden("map", &state_from(fixture()));
+}
+
+#[test]
+fn error_and_selection() {
+    let mut value = fixture();
+    value["error_message"] = json!("Cannot move there");
+    value["error_until"] = json!(WALL_MS + 5000.0);
+    value["selected_cell"] = json!([2, 2]);
+    assert_golden("error_and_selection", &state_from(value));
+}
+
+#[test]
+fn load_menu() {
+    let mut value = fixture();
+    value["load_menu"] = json!({"files": ["autosave.edn", "invasion.edn"], "hovered": 1});
+    assert_golden("load_menu", &state_from(value));
+}
+
+#[test]
+fn help_and_inspector_panels() {
+    let mut st = state_from(fixture());
+    st.show_help_overlay = true;
+    st.show_inspector = true;
+    st.selected_col = Some(1);
+    st.selected_row = Some(1);
+    assert_golden("help_and_inspector", &st);
+}
+
+#[test]
+fn renders_opaque_canvas_of_expected_size() {
+    let target = render_state(&state_from(fixture()), WALL_MS);
+    let rgba = target.to_rgba();
+    // 16 cols * 14 is below the minimum width; 12 rows * 20 + message area
+    assert_eq!(rgba.len(), 700 * (240 + 8 + 60) * 4);
+    assert!(rgba.chunks(4).all(|p| p[3] == 255));
+}
+
+#[test]
+fn cached_terrain_matches_a_fresh_render() {
+    let mut st = state_from(fixture());
+    let mut renderer = Renderer::new(RasterTarget::new());
+    renderer.render(&st, 0.0, WALL_MS);
+
+    // Reveal every cell one patch at a time, as a game in progress would
+    let (cols, rows) = st.map_size;
+    st.apply_frame(&json!({"type": "patch", "seq": 2, "cells": [[0, 0, {"t": "sea"}], [cols - 1, rows - 1, {"t": "land", "cid": 3}]]}).to_string())
+        .unwrap();
+    renderer.render(&st, 0.0, WALL_MS);
+    assert_eq!(renderer.terrain_painted(), 2);
+
+    assert!(renderer.target().to_rgba() == render_state(&st, WALL_MS).to_rgba());
+}
+
+#[test]
+fn switching_theme_repaints_cached_terrain() {
+    let mut st = state_from(fixture());
+    let mut renderer = Renderer::new(RasterTarget::new());
+    renderer.render(&st, 0.0, WALL_MS);
+
+    st.theme = Theme::light();
+    renderer.render(&st, 0.0, WALL_MS);
+    assert!(renderer.target().to_rgba() == render_state(&st, WALL_MS).to_rgba());
+}
+
+#[test]
+fn unit_icons() {
+    // Every unit type, zoomed in so the silhouettes are legible
+    let mut value = fixture();
+    value["cells"][6][1]["u"] = json!({"t": "patrol-boat", "o": "player", "m": "awake"});
+    value["cells"][6][3]["u"] = json!({"t": "submarine", "o": "computer", "m": "awake"});
+    value["cells"][6][5]["u"] = json!({"t": "carrier", "o": "player", "m": "sentry"});
+    value["cells"][6][7]["u"] = json!({"t": "battleship", "o": "computer", "m": "awake", "h": 4});
+    value["cells"][6][9]["u"] = json!({"t": "satellite", "o": "player", "m": "explore"});
+    let mut st = state_from(value);
+    st.unit_style = UnitStyle::Icons;
+    st.camera.zoom_at(2, 0.0, 0.0);
+    assert_golden("unit_icons", &st);
+}
+
+#[test]
+fn deuteranopia_theme() {
+    let mut st = state_from(fixture());
+    st.theme = Theme::deuteranopia();
+    st.selected_col = Some(1);
+    st.selected_row = Some(1);
+    assert_golden("deuteranopia_theme", &st);
+}
@@ -0,0 +1,116 @@
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+
+use empire_client_core::draw::DrawTarget;
+use empire_client_core::layout::Rect;
+
+/// [`DrawTarget`] backed by an HTML canvas and its 2D context.
+pub struct Canvas2d {
+    canvas: HtmlCanvasElement,
+    ctx: CanvasRenderingContext2d,
+}
+
+impl Canvas2d {
+    pub fn new(canvas: &HtmlCanvasElement) -> Result<Self, wasm_bindgen::JsValue> {
+        let ctx = canvas
+            .get_context("2d")?
+            .unwrap()
+            .dyn_into::<CanvasRenderingContext2d>()?;
+        Ok(Self { canvas: canvas.clone(), ctx })
+    }
+}
+
+impl DrawTarget for Canvas2d {
+    fn size(&self) -> (u32, u32) {
+        (self.canvas.width(), self.canvas.height())
+    }
+
+    fn set_size(&mut self, width: u32, height: u32) {
+        self.canvas.set_width(width);
+        self.canvas.set_height(height);
+    }
+
+    fn set_fill_style(&mut self, style: &str) {
+        self.ctx.set_fill_style_str(style);
+    }
+
+    fn set_stroke_style(&mut self, style: &str) {
+        self.ctx.set_stroke_style_str(style);
+    }
+
+    fn set_line_width(&mut self, width: f64) {
+        self.ctx.set_line_width(width);
+    }
+
+    fn set_global_alpha(&mut self, alpha: f64) {
+        self.ctx.set_global_alpha(alpha);
+    }
+
+    fn set_font(&mut self, font: &str) {
+        self.ctx.set_font(font);
+    }
+
+    fn set_text_align(&mut self, align: &str) {
+        self.ctx.set_text_align(align);
+    }
+
+    fn set_text_baseline(&mut self, baseline: &str) {
+        self.ctx.set_text_baseline(baseline);
+    }
+
+    fn fill_rect(&mut self, x: f64, y: f64, w: f64, h: f64) {
+        self.ctx.fill_rect(x, y, w, h);
+    }
+
+    fn stroke_rect(&mut self, x: f64, y: f64, w: f64, h: f64) {
+        self.ctx.stroke_rect(x, y, w, h);
+    }
+
+    fn begin_path(&mut self) {
+        self.ctx.begin_path();
+    }
+
+    fn move_to(&mut self, x: f64, y: f64) {
+        self.ctx.move_to(x, y);
+    }
+
+    fn line_to(&mut self, x: f64, y: f64) {
+        self.ctx.line_to(x, y);
+    }
+
+    fn close_path(&mut self) {
+        self.ctx.close_path();
+    }
+
+    fn stroke(&mut self) {
+        self.ctx.stroke();
+    }
+
+    fn fill(&mut self) {
+        self.ctx.fill();
+    }
+
+    fn fill_text(&mut self, text: &str, x: f64, y: f64) {
+        self.ctx.fill_text(text, x, y).ok();
+    }
+
+    fn measure_text(&self, text: &str) -> f64 {
+        self.ctx.measure_text(text).map(|m| m.width()).unwrap_or(0.0)
+    }
+
+    /// A detached canvas element, never added to the page.
+    fn create_layer(&self) -> Option<Self> {
+        let canvas = web_sys::window()?.document()?.create_element("canvas").ok()?;
+        Self::new(&canvas.dyn_into().ok()?).ok()
+    }
+
+    fn draw_layer(&mut self, layer: &Self, src: Rect, dst: Rect) {
+        // Keep cell edges crisp when zoomed in
+        self.ctx.set_image_smoothing_enabled(false);
+        self.ctx
+            .draw_image_with_html_canvas_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                &layer.canvas, src.x, src.y, src.w, src.h, dst.x, dst.y, dst.w, dst.h,
+            )
+            .ok();
+    }
+}
@@ -0,0 +1,292 @@
+use gloo_timers::callback::{Interval, Timeout};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{MessageEvent, WebSocket};
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+use empire_client_core::protocol::{ClientMessage, CLIENT_FEATURES, PROTOCOL_VERSION};
+use empire_client_core::replay::Recorder;
+use empire_client_core::state::GameState;
+
+use crate::{log, set_status};
+
+// Reconnect backoff: doubles per attempt from the base up to the cap.
+const BACKOFF_BASE_MS: f64 = 500.0;
+const BACKOFF_CAP_MS: f64 = 15000.0;
+// Fraction of the delay that is randomised, so restarted servers aren't stampeded.
+const BACKOFF_JITTER: f64 = 0.5;
+const COUNTDOWN_TICK_MS: u32 = 250;
+
+/// Delay before reconnect attempt `attempt` (0-based). `jitter` is a
+/// uniform random value in [0, 1).
+pub fn backoff_delay_ms(attempt: u32, jitter: f64) -> f64 {
+    let ceiling = (BACKOFF_BASE_MS * 2f64.powi(attempt.min(16) as i32)).min(BACKOFF_CAP_MS);
+    ceiling * (1.0 - BACKOFF_JITTER + BACKOFF_JITTER * jitter)
+}
+
+/// Handlers attached to the current socket. Kept alive here (instead of
+/// `forget`) so they are released when the socket is replaced.
+struct Handlers {
+    _on_open: Closure<dyn FnMut()>,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+    _on_close: Closure<dyn FnMut()>,
+}
+
+/// Owns the WebSocket to the server and replaces it with a fresh one,
+/// with exponential backoff, whenever it closes.
+pub struct Connection {
+    url: String,
+    state: Rc<RefCell<GameState>>,
+    ws: Option<WebSocket>,
+    handlers: Option<Handlers>,
+    attempt: u32,
+    reconnect_pending: bool,
+    countdown: Option<Interval>,
+    /// Set once the connection is given up for good (e.g. for a replay).
+    closed: bool,
+    /// Traffic log while recording; `send` only has `&self`.
+    recorder: RefCell<Option<Recorder>>,
+}
+
+pub type SharedConnection = Rc<RefCell<Connection>>;
+
+impl Connection {
+    pub fn open(url: String, state: Rc<RefCell<GameState>>) -> SharedConnection {
+        let conn = Rc::new(RefCell::new(Connection {
+            url,
+            state,
+            ws: None,
+            handlers: None,
+            attempt: 0,
+            reconnect_pending: false,
+            countdown: None,
+            closed: false,
+            recorder: RefCell::new(None),
+        }));
+        connect(&conn);
+        conn
+    }
+
+    /// A connection that never opens, for replays loaded at startup.
+    pub fn offline(state: Rc<RefCell<GameState>>) -> SharedConnection {
+        Rc::new(RefCell::new(Connection {
+            url: String::new(),
+            state,
+            ws: None,
+            handlers: None,
+            attempt: 0,
+            reconnect_pending: false,
+            countdown: None,
+            closed: true,
+            recorder: RefCell::new(None),
+        }))
+    }
+
+    /// Close the socket and stop reconnecting.
+    pub fn shutdown(&mut self) {
+        self.closed = true;
+        self.countdown = None;
+        if let Some(ws) = self.ws.take() {
+            ws.set_onopen(None);
+            ws.set_onmessage(None);
+            ws.set_onclose(None);
+            let _ = ws.close();
+        }
+        self.handlers = None;
+    }
+
+    /// Start logging traffic. A resync is requested so the recording
+    /// begins with a full state even when patches are in use.
+    pub fn start_recording(&self) {
+        *self.recorder.borrow_mut() = Some(Recorder::new(js_sys::Date::now(), now_ms()));
+        self.send(&ClientMessage::Resync);
+    }
+
+    pub fn stop_recording(&self) -> Option<Recorder> {
+        self.recorder.borrow_mut().take()
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.borrow().is_some()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.ws
+            .as_ref()
+            .is_some_and(|ws| ws.ready_state() == WebSocket::OPEN)
+    }
+
+    /// Serialize and send a message to the server, logging any failure.
+    /// Returns false when the message could not be sent (e.g. while
+    /// reconnecting).
+    pub fn send(&self, msg: &ClientMessage) -> bool {
+        let Some(ws) = self.ws.as_ref().filter(|_| self.is_open()) else {
+            return false;
+        };
+        let text = match serde_json::to_string(msg) {
+            Ok(text) => text,
+            Err(err) => {
+                log(&format!("Serialize error: {}", err));
+                return false;
+            }
+        };
+        if let Err(err) = ws.send_with_str(&text) {
+            log(&format!("Send error: {:?}", err));
+            return false;
+        }
+        if let Some(recorder) = self.recorder.borrow_mut().as_mut() {
+            recorder.record_sent(now_ms(), msg);
+        }
+        true
+    }
+}
+
+/// Monotonic clock in ms, the one recordings are stamped with.
+fn now_ms() -> f64 {
+    web_sys::window()
+        .and_then(|w| w.performance())
+        .map_or_else(js_sys::Date::now, |p| p.now())
+}
+
+fn connect(conn: &SharedConnection) {
+    if conn.borrow().closed {
+        return;
+    }
+    let url = conn.borrow().url.clone();
+    set_status("Connecting...", "connecting");
+
+    let ws = match WebSocket::new(&url) {
+        Ok(ws) => ws,
+        Err(err) => {
+            log(&format!("WebSocket error: {:?}", err));
+            schedule_reconnect(conn);
+            return;
+        }
+    };
+
+    let on_open = {
+        let weak = Rc::downgrade(conn);
+        Closure::<dyn FnMut()>::new(move || {
+            let Some(conn) = weak.upgrade() else { return };
+            let mut c = conn.borrow_mut();
+            c.attempt = 0;
+            c.state.borrow_mut().connected = true;
+            set_status("Connected", "connected");
+            c.send(&ClientMessage::Hello {
+                protocol_version: PROTOCOL_VERSION,
+                features: CLIENT_FEATURES.to_vec(),
+            });
+        })
+    };
+
+    let on_message = {
+        let weak = Rc::downgrade(conn);
+        Closure::<dyn FnMut(MessageEvent)>::new(move |e: MessageEvent| {
+            let Some(conn) = weak.upgrade() else { return };
+            let Some(text) = e.data().as_string() else { return };
+            let c = conn.borrow();
+            if let Some(recorder) = c.recorder.borrow_mut().as_mut() {
+                recorder.record_received(now_ms(), &text);
+            }
+            let resync = {
+                let mut st = c.state.borrow_mut();
+                if let Err(err) = st.apply_frame(&text) {
+                    log(&format!("Parse error: {}", err));
+                }
+                st.take_resync_request()
+            };
+            if resync {
+                log("Patch sequence gap, requesting resync");
+                c.send(&ClientMessage::Resync);
+            }
+        })
+    };
+
+    let on_close = {
+        let weak = Rc::downgrade(conn);
+        Closure::<dyn FnMut()>::new(move || {
+            let Some(conn) = weak.upgrade() else { return };
+            {
+                let c = conn.borrow();
+                let mut st = c.state.borrow_mut();
+                st.connected = false;
+                st.stale = true;
+            }
+            schedule_reconnect(&conn);
+        })
+    };
+
+    ws.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+    ws.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    ws.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+
+    let mut c = conn.borrow_mut();
+    if let Some(old) = c.ws.take() {
+        old.set_onopen(None);
+        old.set_onmessage(None);
+        old.set_onclose(None);
+    }
+    c.ws = Some(ws);
+    c.handlers = Some(Handlers {
+        _on_open: on_open,
+        _on_message: on_message,
+        _on_close: on_close,
+    });
+}
+
+fn schedule_reconnect(conn: &SharedConnection) {
+    let mut c = conn.borrow_mut();
+    if c.reconnect_pending || c.closed {
+        return;
+    }
+    c.reconnect_pending = true;
+
+    let delay = backoff_delay_ms(c.attempt, js_sys::Math::random());
+    c.attempt += 1;
+    let attempt = c.attempt;
+    let deadline = js_sys::Date::now() + delay;
+
+    let update_status = move || {
+        let secs = ((deadline - js_sys::Date::now()) / 1000.0).ceil().max(0.0);
+        set_status(
+            &format!("Disconnected - retrying in {}s (attempt {})", secs, attempt),
+            "disconnected",
+        );
+    };
+    update_status();
+    c.countdown = Some(Interval::new(COUNTDOWN_TICK_MS, update_status));
+
+    let weak: Weak<RefCell<Connection>> = Rc::downgrade(conn);
+    Timeout::new(delay as u32, move || {
+        let Some(conn) = weak.upgrade() else { return };
+        {
+            let mut c = conn.borrow_mut();
+            c.reconnect_pending = false;
+            c.countdown = None;
+        }
+        connect(&conn);
+    })
+    .forget();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_until_cap() {
+        assert_eq!(backoff_delay_ms(0, 1.0), 500.0);
+        assert_eq!(backoff_delay_ms(1, 1.0), 1000.0);
+        assert_eq!(backoff_delay_ms(2, 1.0), 2000.0);
+        assert_eq!(backoff_delay_ms(10, 1.0), BACKOFF_CAP_MS);
+        assert_eq!(backoff_delay_ms(u32::MAX, 1.0), BACKOFF_CAP_MS);
+    }
+
+    #[test]
+    fn jitter_shortens_delay_by_at_most_half() {
+        assert_eq!(backoff_delay_ms(3, 0.0), 2000.0);
+        let d = backoff_delay_ms(3, 0.5);
+        assert!(d > 2000.0 && d < 4000.0);
+    }
+}
@@ -1,16 +1,32 @@
-mod protocol;
-mod renderer;
-mod state;
+mod canvas;
+mod connection;
+mod replay;
+mod theme;
 
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
-use web_sys::{WebSocket, MessageEvent, KeyboardEvent, MouseEvent, HtmlCanvasElement};
-use std::cell::RefCell;
+use web_sys::{KeyboardEvent, MouseEvent, HtmlCanvasElement, WheelEvent};
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
-use crate::state::GameState;
-use crate::renderer::{Renderer, CELL_W, CELL_H};
-use crate::protocol::TutorialMenuMsg;
+use empire_client_core::camera::{edge_scroll, EDGE_SCROLL_SPEED};
+use empire_client_core::input::{
+    activate_control, camera_key, jump_to_minimap, map_key_event, pan_camera, zoom_camera,
+};
+use empire_client_core::layout::{Control, Hit};
+use empire_client_core::protocol::{ClientMessage, MouseButton};
+use empire_client_core::renderer::Renderer;
+use empire_client_core::state::GameState;
+
+use crate::canvas::Canvas2d;
+use crate::connection::Connection;
+use crate::replay::{ReplayTarget, SharedPlayer};
+
+// Share of the browser window the canvas may cover
+const VIEWPORT_FILL: f64 = 0.93;
+
+/// Last pointer position over a map cell, for edge-scrolling.
+type SharedPointer = Rc<Cell<Option<(f64, f64)>>>;
 
 fn log(msg: &str) {
     web_sys::console::log_1(&JsValue::from_str(msg));
@@ -56,7 +72,7 @@ fn fit_canvas_to_viewport(canvas: &HtmlCanvasElement) {
     let vw = window.inner_width().unwrap().as_f64().unwrap_or(cw);
     let vh = window.inner_height().unwrap().as_f64().unwrap_or(ch);
 
-    let scale = (vw / cw).min(vh / ch) * 0.93;
+    let scale = (vw / cw).min(vh / ch) * VIEWPORT_FILL;
     let display_w = (cw * scale).floor();
     let display_h = (ch * scale).floor();
 
@@ -65,6 +81,15 @@ fn fit_canvas_to_viewport(canvas: &HtmlCanvasElement) {
     style.set_property("height", &format!("{}px", display_h)).ok();
 }
 
+/// Canvas pixels the window has room for, so large maps scroll instead
+/// of being shrunk to fit.
+fn screen_size() -> Option<(f64, f64)> {
+    let window = web_sys::window()?;
+    let w = window.inner_width().ok()?.as_f64()?;
+    let h = window.inner_height().ok()?.as_f64()?;
+    Some((w * VIEWPORT_FILL, h * VIEWPORT_FILL))
+}
+
 #[wasm_bindgen(start)]
 pub fn start() -> Result<(), JsValue> {
     let window = web_sys::window().unwrap();
@@ -75,59 +100,48 @@ pub fn start() -> Result<(), JsValue> {
         .dyn_into::<HtmlCanvasElement>()?;
 
     let state = Rc::new(RefCell::new(GameState::new()));
-    let renderer = Rc::new(RefCell::new(Renderer::new(&canvas)?));
+    state.borrow_mut().screen_size = screen_size();
+    theme::restore(&state);
+    let pointer: SharedPointer = Rc::new(Cell::new(None));
+    let renderer = Rc::new(RefCell::new(Renderer::new(Canvas2d::new(&canvas)?)));
 
-    // Connect WebSocket
+    // Connect WebSocket (reconnects automatically when the server restarts)
     let location = window.location();
     let host = location.host()?;
     let protocol = location.protocol()?;
     let ws_protocol = if protocol == "https:" { "wss:" } else { "ws:" };
     let ws_url = format!("{}//{}/ws", ws_protocol, host);
-    let ws = WebSocket::new(&ws_url)?;
-
-    set_status("Connecting...", "connecting");
 
-    // On open
-    {
-        let on_open = Closure::<dyn FnMut()>::new(move || {
-            set_status("Connected", "connected");
-        });
-        ws.set_onopen(Some(on_open.as_ref().unchecked_ref()));
-        on_open.forget();
+    // ?replay=<url> plays a recording instead of connecting; ?record
+    // records from the start; ?theme=<url> loads a custom theme;
+    // ?move_ms=<ms> sets how long units slide between cells
+    let params = web_sys::UrlSearchParams::new_with_str(&location.search()?)?;
+    if let Some(url) = params.get("theme") {
+        theme::load_url(state.clone(), url);
     }
-
-    // On message
-    {
-        let state_clone = state.clone();
-        let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |e: MessageEvent| {
-            if let Some(text) = e.data().as_string() {
-                match serde_json::from_str::<protocol::ServerMessage>(&text) {
-                    Ok(msg) => {
-                        state_clone.borrow_mut().apply_message(msg);
-                    }
-                    Err(err) => {
-                        log(&format!("Parse error: {}", err));
-                    }
-                }
-            }
-        });
-        ws.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
-        on_message.forget();
+    if let Some(ms) = params.get("move_ms").and_then(|ms| ms.parse().ok()) {
+        state.borrow_mut().move_animation_ms = ms;
     }
-
-    // On close
-    {
-        let on_close = Closure::<dyn FnMut()>::new(move || {
-            set_status("Disconnected", "disconnected");
-        });
-        ws.set_onclose(Some(on_close.as_ref().unchecked_ref()));
-        on_close.forget();
+    let replay_url = params.get("replay");
+    let conn = match replay_url {
+        Some(_) => Connection::offline(state.clone()),
+        None => Connection::open(ws_url, state.clone()),
+    };
+    let player: SharedPlayer = Rc::new(RefCell::new(None));
+    let replay = ReplayTarget { conn: conn.clone(), state: state.clone(), player: player.clone() };
+    replay.watch_file_input()?;
+    match replay_url {
+        Some(url) => replay.load_url(url),
+        None if params.has("record") => replay::toggle_recording(&conn),
+        None => {}
     }
 
     // Keyboard input
     {
-        let ws_clone = ws.clone();
+        let conn_clone = conn.clone();
         let state_clone = state.clone();
+        let replay_clone = replay.clone();
+        let renderer_clone = renderer.clone();
         let keydown = Closure::<dyn FnMut(KeyboardEvent)>::new(move |e: KeyboardEvent| {
             e.prevent_default();
 
@@ -146,6 +160,68 @@ pub fn start() -> Result<(), JsValue> {
                 return;
             }
 
+            // Local-only: toggle decoder/connection diagnostics line
+            if e.key() == "F3" {
+                let mut st = state_clone.borrow_mut();
+                st.show_diagnostics = !st.show_diagnostics;
+                return;
+            }
+
+            // Local-only: toggle the minimap
+            if e.key() == "F4" {
+                let mut st = state_clone.borrow_mut();
+                st.show_minimap = !st.show_minimap;
+                return;
+            }
+
+            // Local-only: show every unit's orders, not just the selected one's
+            if e.key() == "F2" {
+                let mut st = state_clone.borrow_mut();
+                st.show_all_orders = !st.show_all_orders;
+                return;
+            }
+
+            // Local-only: switch between letter and icon units
+            if e.key() == "F6" {
+                let mut st = state_clone.borrow_mut();
+                st.unit_style = st.unit_style.toggled();
+                return;
+            }
+
+            // Local-only: cycle the colour themes
+            if e.key() == "F7" {
+                theme::cycle(&state_clone);
+                return;
+            }
+
+            // Local-only: scroll and zoom the map view
+            if camera_key(&mut state_clone.borrow_mut(), renderer_clone.borrow().layout(), &e.key()) {
+                return;
+            }
+
+            // Local-only: load a recording to replay
+            if e.key() == "F8" {
+                replay::pick_file();
+                return;
+            }
+
+            // Replays take no game keys, only their own controls
+            if let Some(player) = replay_clone.player.borrow_mut().as_mut() {
+                let mut st = state_clone.borrow_mut();
+                match e.key().as_str() {
+                    " " => player.toggle_playing(&mut st),
+                    "." => player.step(&mut st),
+                    _ => {}
+                }
+                return;
+            }
+
+            // Local-only: start recording, or stop and download the recording
+            if e.key() == "F9" {
+                replay::toggle_recording(&conn_clone);
+                return;
+            }
+
             // Use selected cell as the "mouse" coordinate for key commands (preferred).
             // Falls back to hover when nothing is selected.
             let (mx, my) = {
@@ -156,7 +232,7 @@ pub fn start() -> Result<(), JsValue> {
                 )
             };
 
-            if let Some((mapped_key, msg)) = map_key_event(&e, mx, my) {
+            if let Some((mapped_key, msg)) = map_key_event(&e.key(), e.shift_key(), mx, my) {
                 {
                     let mut st = state_clone.borrow_mut();
                     match mapped_key.as_str() {
@@ -171,108 +247,151 @@ pub fn start() -> Result<(), JsValue> {
                     }
                 }
 
-                let _ = ws_clone.send_with_str(&msg);
+                if !conn_clone.borrow().send(&msg) {
+                    state_clone.borrow_mut().note_dropped_input(js_sys::Date::now());
+                }
             }
         });
         canvas.add_event_listener_with_callback("keydown", keydown.as_ref().unchecked_ref())?;
         keydown.forget();
     }
     {
-        let ws_clone = ws.clone();
+        let conn_clone = conn.clone();
+        let player_clone = player.clone();
         let keyup = Closure::<dyn FnMut(KeyboardEvent)>::new(move |_e: KeyboardEvent| {
-            let msg = r#"{"type":"key_up"}"#;
-            let _ = ws_clone.send_with_str(msg);
+            if player_clone.borrow().is_some() {
+                return;
+            }
+            conn_clone.borrow().send(&ClientMessage::KeyUp);
         });
         canvas.add_event_listener_with_callback("keyup", keyup.as_ref().unchecked_ref())?;
         keyup.forget();
     }
 
-    // Mouse click - sends col/row computed from canvas-space coordinates
+    // Mouse click - overlay controls first, then the map cell under the pointer
     {
-        let ws_clone = ws.clone();
+        let conn_clone = conn.clone();
         let canvas_clone = canvas.clone();
         let state_clone = state.clone();
+        let renderer_clone = renderer.clone();
+        let replay_clone = replay.clone();
         let mousedown = Closure::<dyn FnMut(MouseEvent)>::new(move |e: MouseEvent| {
             let (x, y) = canvas_coords(&e, &canvas_clone);
+            let hit = renderer_clone.borrow().layout().hit(x, y);
+            let replaying = replay_clone.player.borrow().is_some();
 
-            // Check tutorial menu click first
-            {
-                let st = state_clone.borrow();
-                if let Some(ref menu) = st.tutorial_menu {
-                    let screen_w = canvas_clone.width() as f64;
-                    let screen_h = canvas_clone.height() as f64;
-                    if let Some(idx) = tutorial_menu_hit(menu, x, y, screen_w, screen_h) {
-                        let id = &menu.scenarios[idx].id;
-                        state_clone.borrow_mut().used_tutorial_menu = true;
-                        let msg = format!(
-                            r#"{{"type":"tutorial_select","id":"{}"}}"#,
-                            id
-                        );
-                        let _ = ws_clone.send_with_str(&msg);
+            // Middle button drags the map view (see mousemove)
+            if e.button() == 1 {
+                e.prevent_default();
+                return;
+            }
+
+            let msgs = match hit {
+                Some(Hit::Control(control)) => {
+                    if e.button() != 0 {
+                        return;
+                    }
+                    if control == Control::Minimap {
+                        jump_to_minimap(&mut state_clone.borrow_mut(), renderer_clone.borrow().layout(), x, y);
                         return;
                     }
+                    if replaying {
+                        replay_clone.activate(control, x, renderer_clone.borrow().layout());
+                        return;
+                    }
+                    activate_control(&mut state_clone.borrow_mut(), control)
                 }
-            }
-
-            let col = (x / CELL_W) as i32;
-            let row = (y / CELL_H) as i32;
-
-            // Immediate local selection feedback
-            {
-                let mut st = state_clone.borrow_mut();
-                if col >= 0 && row >= 0 {
-                    st.selected_col = Some(col as usize);
-                    st.selected_row = Some(row as usize);
+                Some(Hit::Cell { col, row }) => {
+                    // Immediate local selection feedback
+                    {
+                        let mut st = state_clone.borrow_mut();
+                        st.selected_col = Some(col);
+                        st.selected_row = Some(row);
+                    }
+                    if replaying {
+                        return;
+                    }
+                    let button = match e.button() {
+                        2 => MouseButton::Right,
+                        _ => MouseButton::Left,
+                    };
+                    vec![ClientMessage::Click { col: col as i32, row: row as i32, button }]
                 }
-            }
-
-            let button = match e.button() {
-                0 => "left",
-                2 => "right",
-                _ => "left",
+                // Overlay backgrounds and empty canvas swallow the click
+                Some(Hit::Overlay) | None => return,
             };
-            let msg = format!(
-                r#"{{"type":"click","col":{},"row":{},"button":"{}"}}"#,
-                col, row, button
-            );
-            let _ = ws_clone.send_with_str(&msg);
+
+            let c = conn_clone.borrow();
+            if !msgs.iter().all(|msg| c.send(msg)) {
+                state_clone.borrow_mut().note_dropped_input(js_sys::Date::now());
+            }
         });
         canvas.add_event_listener_with_callback("mousedown", mousedown.as_ref().unchecked_ref())?;
         mousedown.forget();
     }
 
-    // Mouse move (hover) - throttled, sends col/row
+    // Mouse move (hover) - tracks the control or cell under the pointer,
+    // throttled hover messages carry the cell (-1,-1 when off the map)
     {
-        let ws_clone = ws.clone();
+        let conn_clone = conn.clone();
         let canvas_clone = canvas.clone();
         let state_clone = state.clone();
+        let renderer_clone = renderer.clone();
+        let replay_clone = replay.clone();
+        let pointer_clone = pointer.clone();
         let last_hover = Rc::new(RefCell::new(0.0f64));
+        let drag_from: Rc<Cell<Option<(f64, f64)>>> = Rc::new(Cell::new(None));
         let mousemove = Closure::<dyn FnMut(MouseEvent)>::new(move |e: MouseEvent| {
             let (x, y) = canvas_coords(&e, &canvas_clone);
 
-            // Compute col/row from canvas-space coordinates
-            let col = (x / CELL_W) as isize;
-            let row = (y / CELL_H) as isize;
-
-            // Update client-side hover tracking (always, for hover highlight)
-            {
-                let mut st = state_clone.borrow_mut();
-                if col >= 0 && row >= 0 {
-                    st.hover_col = Some(col as usize);
-                    st.hover_row = Some(row as usize);
-                } else {
-                    st.hover_col = None;
-                    st.hover_row = None;
+            // Dragging with the middle button held pans the map
+            if e.buttons() & 4 != 0 {
+                if let Some((from_x, from_y)) = drag_from.get() {
+                    pan_camera(&mut state_clone.borrow_mut(), renderer_clone.borrow().layout(), from_x - x, from_y - y);
                 }
+                drag_from.set(Some((x, y)));
+                pointer_clone.set(None);
+                return;
+            }
+            drag_from.set(None);
+
+            let hit = renderer_clone.borrow().layout().hit(x, y);
+            pointer_clone.set(matches!(hit, Some(Hit::Cell { .. })).then_some((x, y)));
+
+            // Dragging over the minimap moves the view along
+            if e.buttons() & 1 != 0 && hit == Some(Hit::Control(Control::Minimap)) {
+                jump_to_minimap(&mut state_clone.borrow_mut(), renderer_clone.borrow().layout(), x, y);
+            }
 
-                // Track tutorial menu hover
-                if let Some(ref menu) = st.tutorial_menu {
-                    let screen_w = canvas_clone.width() as f64;
-                    let screen_h = canvas_clone.height() as f64;
-                    st.tutorial_menu_hovered = tutorial_menu_hit(menu, x, y, screen_w, screen_h);
-                } else {
-                    st.tutorial_menu_hovered = None;
+            // Dragging along the replay timeline scrubs
+            let replaying = replay_clone.player.borrow().is_some();
+            if replaying && e.buttons() & 1 != 0 && hit == Some(Hit::Control(Control::ReplaySeek)) {
+                replay_clone.activate(Control::ReplaySeek, x, renderer_clone.borrow().layout());
+            }
+
+            let (col, row) = {
+                let mut st = state_clone.borrow_mut();
+                st.hovered_control = match hit {
+                    Some(Hit::Control(control)) => Some(control),
+                    _ => None,
+                };
+                match hit {
+                    Some(Hit::Cell { col, row }) => {
+                        st.hover_col = Some(col);
+                        st.hover_row = Some(row);
+                        (col as i32, row as i32)
+                    }
+                    _ => {
+                        st.hover_col = None;
+                        st.hover_row = None;
+                        (-1, -1)
+                    }
                 }
+            };
+            let cursor = if matches!(hit, Some(Hit::Control(_))) { "pointer" } else { "default" };
+            canvas_clone.style().set_property("cursor", cursor).ok();
+            if replaying {
+                return;
             }
 
             // Throttle WebSocket messages to 10/sec
@@ -281,13 +400,43 @@ pub fn start() -> Result<(), JsValue> {
             if now - *last < 100.0 { return; }
             *last = now;
 
-            let msg = format!(r#"{{"type":"hover","col":{},"row":{}}}"#, col, row);
-            let _ = ws_clone.send_with_str(&msg);
+            conn_clone.borrow().send(&ClientMessage::Hover { col, row });
         });
         canvas.add_event_listener_with_callback("mousemove", mousemove.as_ref().unchecked_ref())?;
         mousemove.forget();
     }
 
+    // Stop edge-scrolling once the pointer leaves the canvas
+    {
+        let pointer_clone = pointer.clone();
+        let mouseleave = Closure::<dyn FnMut(MouseEvent)>::new(move |_e: MouseEvent| {
+            pointer_clone.set(None);
+        });
+        canvas.add_event_listener_with_callback("mouseleave", mouseleave.as_ref().unchecked_ref())?;
+        mouseleave.forget();
+    }
+
+    // Mouse wheel zooms the map view around the pointer
+    {
+        let canvas_clone = canvas.clone();
+        let state_clone = state.clone();
+        let renderer_clone = renderer.clone();
+        let wheel = Closure::<dyn FnMut(WheelEvent)>::new(move |e: WheelEvent| {
+            e.prevent_default();
+            if e.delta_y() == 0.0 {
+                return;
+            }
+            let (x, y) = canvas_coords(&e, &canvas_clone);
+            let layout = renderer_clone.borrow();
+            if layout.layout().map.contains(x, y) {
+                let steps = if e.delta_y() < 0.0 { 1 } else { -1 };
+                zoom_camera(&mut state_clone.borrow_mut(), layout.layout(), steps, x, y);
+            }
+        });
+        canvas.add_event_listener_with_callback("wheel", wheel.as_ref().unchecked_ref())?;
+        wheel.forget();
+    }
+
     // Prevent context menu on right-click
     {
         let contextmenu = Closure::<dyn FnMut(MouseEvent)>::new(move |e: MouseEvent| {
@@ -300,7 +449,9 @@ pub fn start() -> Result<(), JsValue> {
     // Window resize handler to refit canvas
     {
         let canvas_clone = canvas.clone();
+        let state_clone = state.clone();
         let on_resize = Closure::<dyn FnMut()>::new(move || {
+            state_clone.borrow_mut().screen_size = screen_size();
             fit_canvas_to_viewport(&canvas_clone);
         });
         window.add_event_listener_with_callback("resize", on_resize.as_ref().unchecked_ref())?;
@@ -311,28 +462,52 @@ pub fn start() -> Result<(), JsValue> {
     let _ = canvas.focus();
 
     // Start render loop
-    start_render_loop(state, renderer, canvas);
+    start_render_loop(state, renderer, player, pointer, canvas);
 
     Ok(())
 }
 
+type FrameCallback = Rc<RefCell<Option<Closure<dyn FnMut()>>>>;
+
 fn start_render_loop(
     state: Rc<RefCell<GameState>>,
-    renderer: Rc<RefCell<Renderer>>,
+    renderer: Rc<RefCell<Renderer<Canvas2d>>>,
+    player: SharedPlayer,
+    pointer: SharedPointer,
     canvas: HtmlCanvasElement,
 ) {
-    let f: Rc<RefCell<Option<Closure<dyn FnMut()>>>> = Rc::new(RefCell::new(None));
+    let f: FrameCallback = Rc::new(RefCell::new(None));
     let g = f.clone();
 
     let window = web_sys::window().unwrap();
     let perf = window.performance().unwrap();
+    let mut last = perf.now();
 
     *g.borrow_mut() = Some(Closure::new(move || {
         let now = perf.now();
+        // Replays run on the recording's clock so deadlines like
+        // error_until expire as they did live
+        let wall_ms = match player.borrow_mut().as_mut() {
+            Some(p) => {
+                p.advance(now - last, &mut state.borrow_mut());
+                p.wall_ms()
+            }
+            None => js_sys::Date::now(),
+        };
+        // Scroll while the pointer rests near the edge of the map view
+        if let Some((x, y)) = pointer.get() {
+            let r = renderer.borrow();
+            let (dx, dy) = edge_scroll(r.layout().map, x, y);
+            if (dx, dy) != (0.0, 0.0) {
+                let step = EDGE_SCROLL_SPEED * (now - last) / 1000.0;
+                pan_camera(&mut state.borrow_mut(), r.layout(), dx * step, dy * step);
+            }
+        }
+        last = now;
         let st = state.borrow();
-        if st.map_size.0 > 0 {
+        if st.map_size.0 > 0 || st.protocol_error.is_some() {
             let mut r = renderer.borrow_mut();
-            r.render(&st, now, &canvas);
+            r.render(&st, now, wall_ms);
             // Fit canvas to viewport after render sets canvas dimensions
             fit_canvas_to_viewport(&canvas);
         }
@@ -346,85 +521,3 @@ fn start_render_loop(
         g.borrow().as_ref().unwrap().as_ref().unchecked_ref(),
     );
 }
-
-/// Compute which tutorial menu item (index) is at canvas position (x, y),
-/// given the screen dimensions. Returns None if not over any item.
-fn tutorial_menu_hit(menu: &TutorialMenuMsg, x: f64, y: f64, screen_w: f64, screen_h: f64) -> Option<usize> {
-    let padding = 24.0;
-    let item_height = 48.0;
-    let title_height = 40.0;
-    let hint_height = 30.0;
-    let menu_w = 520.0f64.min(screen_w * 0.85);
-    let scenario_count = menu.scenarios.len();
-    let menu_h = (title_height + padding + scenario_count as f64 * item_height + hint_height + padding)
-        .min(screen_h * 0.85);
-    let left = (screen_w - menu_w) / 2.0;
-    let top = (screen_h - menu_h) / 2.0;
-    let content_top = top + padding + title_height;
-
-    if x < left || x > left + menu_w || y < content_top {
-        return None;
-    }
-
-    let rel_y = y - content_top;
-    let idx = (rel_y / item_height) as usize;
-    if idx < scenario_count && rel_y >= 0.0 {
-        Some(idx)
-    } else {
-        None
-    }
-}
-
-fn map_key_event(e: &KeyboardEvent, mouse_x: i32, mouse_y: i32) -> Option<(String, String)> {
-    let key = e.key();
-    let shift = e.shift_key();
-
-    let mapped = match key.as_str() {
-        // Movement keys
-        "q" | "w" | "e" | "a" | "d" | "z" | "x" | "c" => {
-            if shift { key.to_uppercase() } else { key.clone() }
-        }
-        "Q" | "W" | "E" | "A" | "D" | "Z" | "X" | "C" => key.clone(),
-        // Production keys
-        "f" | "t" | "p" | "s" | "b" => {
-            if shift { key.to_uppercase() } else { key.clone() }
-        }
-        "F" | "T" | "P" | "S" | "B" => key.clone(),
-        // Special keys
-        " " => "space".to_string(),
-        "Escape" => "escape".to_string(),
-        "`" => "`".to_string(),
-        "!" => "!".to_string(),
-        "." => ".".to_string(),
-        "*" => "*".to_string(),
-        "+" => "+".to_string(),
-        "^" => "^".to_string(),
-        // Unit command keys
-        "u" => if shift { "U".to_string() } else { "u".to_string() },
-        "U" => "U".to_string(),
-        "l" => if shift { "L".to_string() } else { "l".to_string() },
-        "L" => "L".to_string(),
-        "m" => if shift { "M".to_string() } else { "m".to_string() },
-        "M" => "M".to_string(),
-        "o" => if shift { "O".to_string() } else { "o".to_string() },
-        "O" => "O".to_string(),
-        // Tutorial keys
-        "n" => if shift { "N".to_string() } else { "n".to_string() },
-        "N" => "N".to_string(),
-        "?" => "?".to_string(),
-
-        // Tips toggle (server-side)
-        "h" => "h".to_string(),
-        // Tips toggle (server-side) alternate binding for the browser client.
-        // Lowercase 'h' is reserved for hiding the local help panel.
-        "i" => "h".to_string(),
-        _ => return None,
-    };
-
-    let msg = format!(
-        r#"{{"type":"key","key":"{}","shift":{},"mouse_x":{},"mouse_y":{}}}"#,
-        mapped, shift, mouse_x, mouse_y
-    );
-
-    Some((mapped, msg))
-}
@@ -0,0 +1,194 @@
+//! Browser side of recording and replay: downloading a recording, loading
+//! one by URL or file picker, and driving the replay controls.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gloo_timers::callback::Timeout;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, HtmlInputElement, Response, Url};
+
+use empire_client_core::layout::{Control, Layout};
+use empire_client_core::replay::{Player, Recorder, Recording};
+use empire_client_core::state::GameState;
+
+use crate::connection::SharedConnection;
+use crate::{log, set_status};
+
+/// How long a recording's download link outlives its click, in ms.
+const DOWNLOAD_CLEANUP_MS: u32 = 10_000;
+
+/// The recording being played, if any. `None` while playing live.
+pub type SharedPlayer = Rc<RefCell<Option<Player>>>;
+
+/// Everything a loaded recording replaces.
+#[derive(Clone)]
+pub struct ReplayTarget {
+    pub conn: SharedConnection,
+    pub state: Rc<RefCell<GameState>>,
+    pub player: SharedPlayer,
+}
+
+impl ReplayTarget {
+    /// Drop the live connection and start playing `text`, paused.
+    pub fn start(&self, text: &str, name: &str) {
+        let recording = match Recording::parse(text) {
+            Ok(recording) => recording,
+            Err(err) => {
+                log(&format!("Replay {}: {}", name, err));
+                set_status(&format!("Replay {}: {}", name, err), "disconnected");
+                return;
+            }
+        };
+        self.conn.borrow_mut().shutdown();
+        show_recording_indicator(false);
+        let mut st = self.state.borrow_mut();
+        st.reset_game();
+        *self.player.borrow_mut() = Some(Player::new(recording, &mut st));
+        set_status(&format!("Replay - {}", name), "replay");
+    }
+
+    /// Fetch a recording and play it.
+    pub fn load_url(&self, url: String) {
+        let target = self.clone();
+        set_status(&format!("Loading {}...", url), "connecting");
+        spawn_local(async move {
+            match fetch_text(&url).await {
+                Ok(text) => target.start(&text, &url),
+                Err(err) => {
+                    log(&format!("Replay {}: {:?}", url, err));
+                    set_status(&format!("Replay {}: could not load", url), "disconnected");
+                }
+            }
+        });
+    }
+
+    /// Play the file chosen in the `#replay-file` input.
+    pub fn watch_file_input(&self) -> Result<(), JsValue> {
+        let Some(input) = file_input() else {
+            return Ok(());
+        };
+        let target = self.clone();
+        let input_clone = input.clone();
+        let on_change = Closure::<dyn FnMut()>::new(move || {
+            let Some(file) = input_clone.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+            // Let the same file be picked again later
+            input_clone.set_value("");
+            let target = target.clone();
+            spawn_local(async move {
+                match JsFuture::from(file.text()).await {
+                    Ok(text) => target.start(&text.as_string().unwrap_or_default(), &file.name()),
+                    Err(err) => log(&format!("Replay {}: {:?}", file.name(), err)),
+                }
+            });
+        });
+        input.add_event_listener_with_callback("change", on_change.as_ref().unchecked_ref())?;
+        on_change.forget();
+        Ok(())
+    }
+
+    /// Handle a click on one of the replay controls. `x` is where on the
+    /// canvas the click landed, for the scrubber.
+    pub fn activate(&self, control: Control, x: f64, layout: &Layout) {
+        let mut player = self.player.borrow_mut();
+        let Some(player) = player.as_mut() else {
+            return;
+        };
+        let mut st = self.state.borrow_mut();
+        match control {
+            Control::ReplayPlayPause => player.toggle_playing(&mut st),
+            Control::ReplayStep => player.step(&mut st),
+            Control::ReplaySpeed => player.cycle_speed(&mut st),
+            Control::ReplaySeek => {
+                let duration = st.replay.as_ref().map_or(0.0, |r| r.duration_ms);
+                if let Some(bar) = &layout.replay_bar {
+                    player.seek(bar.time_at(x, duration), &mut st);
+                }
+            }
+            Control::ReplayExit => {
+                // Back to the live game
+                let location = web_sys::window().unwrap().location();
+                if let Ok(path) = location.pathname() {
+                    let _ = location.set_href(&path);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Open the file picker for a recording.
+pub fn pick_file() {
+    if let Some(input) = file_input() {
+        input.click();
+    }
+}
+
+/// Start recording, or stop and download what was recorded.
+pub fn toggle_recording(conn: &SharedConnection) {
+    let c = conn.borrow();
+    match c.stop_recording() {
+        Some(recorder) => {
+            if let Err(err) = download(&recorder) {
+                log(&format!("Recording download failed: {:?}", err));
+            }
+        }
+        None => c.start_recording(),
+    }
+    show_recording_indicator(c.is_recording());
+}
+
+pub fn show_recording_indicator(on: bool) {
+    let document = web_sys::window().unwrap().document().unwrap();
+    if let Some(el) = document.get_element_by_id("recording") {
+        el.set_class_name(if on { "on" } else { "" });
+    }
+}
+
+fn file_input() -> Option<HtmlInputElement> {
+    let document = web_sys::window()?.document()?;
+    document.get_element_by_id("replay-file")?.dyn_into().ok()
+}
+
+pub async fn fetch_text(url: &str) -> Result<String, JsValue> {
+    let window = web_sys::window().unwrap();
+    let response: Response = JsFuture::from(window.fetch_with_str(url)).await?.dyn_into()?;
+    if !response.ok() {
+        return Err(JsValue::from_str(&format!("HTTP {}", response.status())));
+    }
+    let text = JsFuture::from(response.text()?).await?;
+    Ok(text.as_string().unwrap_or_default())
+}
+
+/// Save a recording as `empire-<date>.jsonl` through a temporary link.
+fn download(recorder: &Recorder) -> Result<(), JsValue> {
+    let parts = js_sys::Array::of1(&JsValue::from_str(&recorder.to_jsonl()));
+    let options = BlobPropertyBag::new();
+    options.set_type("application/x-ndjson");
+    let blob = Blob::new_with_str_sequence_and_options(&parts, &options)?;
+    let url = Url::create_object_url_with_blob(&blob)?;
+
+    let document = web_sys::window().unwrap().document().unwrap();
+    let link: HtmlAnchorElement = document.create_element("a")?.dyn_into()?;
+    let date: String = js_sys::Date::new_0().to_iso_string().into();
+    // Colons are not allowed in file names everywhere
+    link.set_download(&format!("empire-{}.jsonl", date.replace(':', "-")));
+    link.set_href(&url);
+    // Some browsers start the download after click() returns, so the link
+    // and its blob URL stay alive for a while
+    if let Some(body) = document.body() {
+        body.append_child(&link)?;
+    }
+    link.click();
+    Timeout::new(DOWNLOAD_CLEANUP_MS, move || {
+        link.remove();
+        let _ = Url::revoke_object_url(&url);
+    })
+    .forget();
+    log(&format!("Recorded {} frames", recorder.frame_count()));
+    Ok(())
+}
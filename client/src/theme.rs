@@ -0,0 +1,61 @@
+//! Browser side of themes: the choice is kept in localStorage, the theme
+//! key cycles the built-ins, and `?theme=<url>` loads a custom theme.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wasm_bindgen_futures::spawn_local;
+use web_sys::Storage;
+
+use empire_client_core::state::GameState;
+use empire_client_core::theme::Theme;
+
+use crate::log;
+use crate::replay::fetch_text;
+
+const STORAGE_KEY: &str = "empire-theme";
+
+fn storage() -> Option<Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+/// Apply the theme saved by an earlier session, if any.
+pub fn restore(state: &Rc<RefCell<GameState>>) {
+    let Some(saved) = storage().and_then(|s| s.get_item(STORAGE_KEY).ok()?) else {
+        return;
+    };
+    match Theme::restore(&saved) {
+        Ok(theme) => state.borrow_mut().theme = theme,
+        Err(err) => log(&format!("Saved theme ignored: {}", err)),
+    }
+}
+
+fn apply(state: &Rc<RefCell<GameState>>, theme: Theme) {
+    if let Some(storage) = storage() {
+        if storage.set_item(STORAGE_KEY, &theme.saved()).is_err() {
+            log("Could not save the theme choice");
+        }
+    }
+    log(&format!("Theme: {}", theme.name));
+    state.borrow_mut().theme = theme;
+}
+
+/// Switch to the next built-in theme and remember it.
+pub fn cycle(state: &Rc<RefCell<GameState>>) {
+    let next = state.borrow().theme.next_builtin();
+    apply(state, next);
+}
+
+/// Fetch a custom theme, apply it and remember it.
+pub fn load_url(state: Rc<RefCell<GameState>>, url: String) {
+    spawn_local(async move {
+        let theme = match fetch_text(&url).await {
+            Ok(text) => Theme::from_json(&text),
+            Err(err) => Err(format!("{:?}", err)),
+        };
+        match theme {
+            Ok(theme) => apply(&state, theme),
+            Err(err) => log(&format!("Theme {}: {}", url, err)),
+        }
+    });
+}
@@ -0,0 +1,93 @@
+//! Terminal key events to game input. Game keys go through the same table
+//! as the browser client (`map_key_event`); arrows, Enter and Ctrl-C are
+//! handled locally.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use empire_client_core::input::map_key_event;
+use empire_client_core::protocol::ClientMessage;
+
+/// What a key press asks the client to do.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    Quit,
+    /// Move the map cursor by (dx, dy) cells.
+    MoveCursor(i32, i32),
+    /// Click the cell under the cursor.
+    Click,
+    ToggleHelp,
+    /// Send a game key to the server.
+    Send(ClientMessage),
+}
+
+/// Name of the key as the browser's `KeyboardEvent.key` would report it.
+fn browser_key(code: KeyCode) -> Option<String> {
+    match code {
+        KeyCode::Char(' ') => Some(" ".to_string()),
+        KeyCode::Char(c) => Some(c.to_string()),
+        KeyCode::Esc => Some("Escape".to_string()),
+        _ => None,
+    }
+}
+
+/// Translate a key event; `cursor` is the (col, row) that key commands
+/// target, like the browser's selected cell.
+pub fn key_action(event: &KeyEvent, cursor: (usize, usize)) -> Option<Action> {
+    if event.kind == KeyEventKind::Release {
+        return None;
+    }
+    let ctrl = event.modifiers.contains(KeyModifiers::CONTROL);
+    match event.code {
+        KeyCode::Char('c') | KeyCode::Char('q') if ctrl => return Some(Action::Quit),
+        KeyCode::Up => return Some(Action::MoveCursor(0, -1)),
+        KeyCode::Down => return Some(Action::MoveCursor(0, 1)),
+        KeyCode::Left => return Some(Action::MoveCursor(-1, 0)),
+        KeyCode::Right => return Some(Action::MoveCursor(1, 0)),
+        KeyCode::Enter => return Some(Action::Click),
+        KeyCode::F(1) => return Some(Action::ToggleHelp),
+        _ if ctrl => return None,
+        _ => {}
+    }
+
+    let key = browser_key(event.code)?;
+    let shift = event.modifiers.contains(KeyModifiers::SHIFT);
+    map_key_event(&key, shift, cursor.0 as i32, cursor.1 as i32).map(|(_, msg)| Action::Send(msg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn game_keys_use_the_browser_table() {
+        let action = key_action(&press(KeyCode::Char('Q'), KeyModifiers::SHIFT), (3, 4));
+        assert_eq!(
+            action,
+            Some(Action::Send(ClientMessage::Key { key: "Q".to_string(), shift: true, mouse_x: 3, mouse_y: 4 }))
+        );
+        let action = key_action(&press(KeyCode::Char(' '), KeyModifiers::NONE), (0, 0));
+        assert!(matches!(action, Some(Action::Send(ClientMessage::Key { ref key, .. })) if key == "space"));
+        let action = key_action(&press(KeyCode::Esc, KeyModifiers::NONE), (0, 0));
+        assert!(matches!(action, Some(Action::Send(ClientMessage::Key { ref key, .. })) if key == "escape"));
+    }
+
+    #[test]
+    fn local_keys() {
+        assert_eq!(key_action(&press(KeyCode::Char('c'), KeyModifiers::CONTROL), (0, 0)), Some(Action::Quit));
+        assert_eq!(key_action(&press(KeyCode::Left, KeyModifiers::NONE), (0, 0)), Some(Action::MoveCursor(-1, 0)));
+        assert_eq!(key_action(&press(KeyCode::Enter, KeyModifiers::NONE), (0, 0)), Some(Action::Click));
+        // Other control chords are not game keys
+        assert_eq!(key_action(&press(KeyCode::Char('s'), KeyModifiers::CONTROL), (0, 0)), None);
+    }
+
+    #[test]
+    fn unmapped_and_release_events_are_ignored() {
+        assert_eq!(key_action(&press(KeyCode::Char('j'), KeyModifiers::NONE), (0, 0)), None);
+        let mut release = press(KeyCode::Char('a'), KeyModifiers::NONE);
+        release.kind = KeyEventKind::Release;
+        assert_eq!(key_action(&release, (0, 0)), None);
+    }
+}
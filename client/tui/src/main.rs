@@ -0,0 +1,259 @@
+//! Terminal client: plays over the same `/ws` endpoint as the browser
+//! client, drawing the map as coloured characters.
+//!
+//!     empire-tui [ws://host:8080/ws] [--once]
+//!
+//! `--once` prints the first full state as plain text and exits, for
+//! scripted tests.
+
+mod keys;
+mod view;
+
+use std::io::{self, Write};
+use std::net::TcpStream;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, MouseButton as TermButton, MouseEventKind};
+use crossterm::style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor};
+use crossterm::{cursor, execute, queue, terminal};
+use empire_client_core::protocol::{ClientMessage, MouseButton, CLIENT_FEATURES, PROTOCOL_VERSION};
+use empire_client_core::state::GameState;
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+use keys::{key_action, Action};
+use view::{compose, Screen, Viewport, FOOTER_ROWS, HEADER_ROWS};
+
+const DEFAULT_URL: &str = "ws://localhost:8080/ws";
+const USAGE: &str = "usage: empire-tui [ws://host:port/ws] [--once]";
+// How long a socket read may block before the loop checks the keyboard.
+const READ_TIMEOUT: Duration = Duration::from_millis(20);
+const INPUT_POLL: Duration = Duration::from_millis(10);
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+type Socket = WebSocket<MaybeTlsStream<TcpStream>>;
+
+/// The server connection, reopened after a fixed delay when it drops.
+struct Connection {
+    url: String,
+    ws: Option<Socket>,
+    retry_at: Instant,
+}
+
+impl Connection {
+    fn new(url: String) -> Self {
+        Self { url, ws: None, retry_at: Instant::now() }
+    }
+
+    fn open(&mut self) -> Result<(), tungstenite::Error> {
+        let (mut ws, _) = tungstenite::connect(self.url.as_str())?;
+        if let MaybeTlsStream::Plain(stream) = ws.get_mut() {
+            stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        }
+        let hello = ClientMessage::Hello { protocol_version: PROTOCOL_VERSION, features: CLIENT_FEATURES.to_vec() };
+        ws.send(Message::text(serde_json::to_string(&hello).unwrap_or_default()))?;
+        self.ws = Some(ws);
+        Ok(())
+    }
+
+    /// Connect if disconnected and the retry delay has passed. Returns true
+    /// when a new connection was opened.
+    fn ensure_open(&mut self, state: &mut GameState) -> bool {
+        if self.ws.is_some() || Instant::now() < self.retry_at {
+            return false;
+        }
+        match self.open() {
+            Ok(()) => {
+                state.connected = true;
+                true
+            }
+            Err(_) => {
+                self.retry_at = Instant::now() + RECONNECT_DELAY;
+                false
+            }
+        }
+    }
+
+    fn drop_connection(&mut self, state: &mut GameState) {
+        self.ws = None;
+        self.retry_at = Instant::now() + RECONNECT_DELAY;
+        state.connected = false;
+        state.stale = true;
+    }
+
+    fn send(&mut self, msg: &ClientMessage, state: &mut GameState) {
+        let Some(ws) = self.ws.as_mut() else { return };
+        let text = serde_json::to_string(msg).unwrap_or_default();
+        if ws.send(Message::text(text)).is_err() {
+            self.drop_connection(state);
+        }
+    }
+
+    /// Read whatever frames are waiting. Returns true if any was applied.
+    fn pump(&mut self, state: &mut GameState) -> bool {
+        let mut changed = false;
+        while let Some(ws) = self.ws.as_mut() {
+            match ws.read() {
+                Ok(Message::Text(text)) => {
+                    // Bad frames are counted by the state and otherwise ignored
+                    let _ = state.apply_frame(text.as_str());
+                    changed = true;
+                }
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(err))
+                    if matches!(err.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) =>
+                {
+                    break;
+                }
+                Err(_) => {
+                    self.drop_connection(state);
+                    changed = true;
+                }
+            }
+        }
+        if state.take_resync_request() {
+            self.send(&ClientMessage::Resync, state);
+        }
+        changed
+    }
+}
+
+/// Raw mode and the alternate screen, restored on drop (including panics).
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide, event::EnableMouseCapture)?;
+        Ok(Self)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), event::DisableMouseCapture, cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn rgb([r, g, b]: [u8; 3]) -> Color {
+    Color::Rgb { r, g, b }
+}
+
+fn draw(out: &mut impl Write, screen: &Screen) -> io::Result<()> {
+    for y in 0..screen.height {
+        queue!(out, cursor::MoveTo(0, y as u16))?;
+        let mut colors = None;
+        for glyph in screen.row(y) {
+            if colors != Some((glyph.fg, glyph.bg)) {
+                queue!(out, SetForegroundColor(rgb(glyph.fg)), SetBackgroundColor(rgb(glyph.bg)))?;
+                colors = Some((glyph.fg, glyph.bg));
+            }
+            queue!(out, Print(glyph.ch))?;
+        }
+    }
+    queue!(out, ResetColor)?;
+    out.flush()
+}
+
+/// Wait for the first full state and print it.
+fn run_once(url: String) -> Result<(), String> {
+    let mut conn = Connection::new(url);
+    let mut state = GameState::new();
+    conn.open().map_err(|err| format!("{}: {}", conn.url, err))?;
+    state.connected = true;
+    while state.map_size.0 == 0 {
+        conn.pump(&mut state);
+        if conn.ws.is_none() {
+            return Err(format!("{}: connection closed", conn.url));
+        }
+    }
+    if let Some(ws) = conn.ws.as_mut() {
+        let _ = ws.close(None);
+        let _ = ws.flush();
+    }
+    // Large enough for the whole map and the status lines
+    let (cols, rows) = state.map_size;
+    let mut view = Viewport::default();
+    print!("{}", compose(&state, &mut view, cols.max(80), rows + HEADER_ROWS + FOOTER_ROWS, false).to_text());
+    Ok(())
+}
+
+fn run(url: String) -> io::Result<()> {
+    let _guard = TerminalGuard::enter()?;
+    let mut out = io::stdout();
+    let mut conn = Connection::new(url);
+    let mut state = GameState::new();
+    let mut view = Viewport::default();
+    let mut show_help = true;
+    let mut dirty = true;
+
+    loop {
+        dirty |= conn.ensure_open(&mut state);
+        dirty |= conn.pump(&mut state);
+
+        while event::poll(if conn.ws.is_some() { Duration::ZERO } else { INPUT_POLL })? {
+            match event::read()? {
+                Event::Key(key) => match key_action(&key, view.cursor) {
+                    Some(Action::Quit) => return Ok(()),
+                    Some(Action::MoveCursor(dx, dy)) => view.move_cursor(dx, dy, state.map_size),
+                    Some(Action::Click) => {
+                        let (col, row) = view.cursor;
+                        let click = ClientMessage::Click { col: col as i32, row: row as i32, button: MouseButton::Left };
+                        conn.send(&click, &mut state);
+                    }
+                    Some(Action::ToggleHelp) => show_help = !show_help,
+                    // The terminal reports no key releases, so release right away
+                    Some(Action::Send(msg)) => {
+                        conn.send(&msg, &mut state);
+                        conn.send(&ClientMessage::KeyUp, &mut state);
+                    }
+                    None => continue,
+                },
+                Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(TermButton::Left) => {
+                    let Some((col, row)) = view.cell_at(mouse.column as usize, mouse.row as usize, state.map_size)
+                    else {
+                        continue;
+                    };
+                    view.cursor = (col, row);
+                    let click = ClientMessage::Click { col: col as i32, row: row as i32, button: MouseButton::Left };
+                    conn.send(&click, &mut state);
+                }
+                Event::Resize(..) => {}
+                _ => continue,
+            }
+            dirty = true;
+        }
+
+        if dirty {
+            let (width, height) = terminal::size()?;
+            let screen = compose(&state, &mut view, width as usize, height as usize, show_help);
+            draw(&mut out, &screen)?;
+            dirty = false;
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let once = args.iter().any(|a| a == "--once");
+    let urls: Vec<&String> = args.iter().filter(|a| !a.starts_with("--")).collect();
+    let url = match urls[..] {
+        [] => DEFAULT_URL.to_string(),
+        [url] => url.clone(),
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let result = if once { run_once(url) } else { run(url).map_err(|err| err.to_string()) };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
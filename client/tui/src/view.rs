@@ -0,0 +1,256 @@
+//! Composes a `GameState` into a grid of coloured terminal cells: one
+//! character per map cell (the canvas glyphs from `unit_char`, coloured
+//! like the canvas renderer) plus status and message lines.
+
+use empire_client_core::protocol::{CellMsg, Owner, Terrain};
+use empire_client_core::renderer::{cell_color, unit_color};
+use empire_client_core::state::GameState;
+use empire_client_core::theme::Theme;
+use empire_client_core::units::unit_char;
+
+const TEXT: [u8; 3] = [230, 237, 243];
+const TEXT_DIM: [u8; 3] = [139, 148, 158];
+const ERROR: [u8; 3] = [248, 81, 73];
+const ACCENT: [u8; 3] = [88, 166, 255];
+const BACKGROUND: [u8; 3] = [13, 17, 23];
+const CURSOR_BG: [u8; 3] = [31, 111, 235];
+
+// One status line above the map, three message lines below it
+pub const HEADER_ROWS: usize = 1;
+pub const FOOTER_ROWS: usize = 3;
+
+/// One terminal character cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Glyph {
+    pub ch: char,
+    pub fg: [u8; 3],
+    pub bg: [u8; 3],
+}
+
+impl Glyph {
+    const BLANK: Glyph = Glyph { ch: ' ', fg: TEXT, bg: BACKGROUND };
+}
+
+/// A composed screen, row-major.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Screen {
+    pub width: usize,
+    pub height: usize,
+    pub cells: Vec<Glyph>,
+}
+
+impl Screen {
+    fn new(width: usize, height: usize) -> Self {
+        Self { width, height, cells: vec![Glyph::BLANK; width * height] }
+    }
+
+    pub fn row(&self, y: usize) -> &[Glyph] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    fn put(&mut self, x: usize, y: usize, glyph: Glyph) {
+        if x < self.width && y < self.height {
+            self.cells[y * self.width + x] = glyph;
+        }
+    }
+
+    fn text(&mut self, x: usize, y: usize, text: &str, fg: [u8; 3]) {
+        for (i, ch) in text.chars().enumerate() {
+            self.put(x + i, y, Glyph { ch, fg, bg: BACKGROUND });
+        }
+    }
+
+    /// The characters only, one line per row with trailing blanks trimmed.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for y in 0..self.height {
+            let line: String = self.row(y).iter().map(|g| g.ch).collect();
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Map character and colours for one cell, as the canvas draws it.
+pub fn cell_glyph(theme: &Theme, cell: Option<&CellMsg>) -> Glyph {
+    let Some(cell) = cell else {
+        return Glyph { ch: ' ', fg: TEXT, bg: BACKGROUND };
+    };
+    let bg = cell_color(theme, cell);
+    if let Some(unit) = &cell.u {
+        let ch = unit_char(&unit.t);
+        let ch = if unit.o == Owner::Computer { ch.to_lowercase() } else { ch.to_string() };
+        return Glyph { ch: ch.chars().next().unwrap_or('?'), fg: unit_color(theme, unit), bg };
+    }
+    if cell.t == Terrain::City {
+        if let Some(prod) = &cell.prod {
+            return Glyph { ch: unit_char(&prod.item).chars().next().unwrap_or('?'), fg: theme.production, bg };
+        }
+    }
+    if cell.wp == Some(true) {
+        return Glyph { ch: '*', fg: theme.waypoint, bg };
+    }
+    Glyph { ch: ' ', fg: TEXT, bg }
+}
+
+/// First visible map column or row: scroll so `cursor` stays in view,
+/// keeping the previous origin when possible.
+pub fn scroll_origin(origin: usize, cursor: usize, visible: usize, total: usize) -> usize {
+    if visible == 0 || total <= visible {
+        return 0;
+    }
+    let origin = if cursor < origin {
+        cursor
+    } else if cursor >= origin + visible {
+        cursor + 1 - visible
+    } else {
+        origin
+    };
+    origin.min(total - visible)
+}
+
+/// Viewport state that persists between frames.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Viewport {
+    pub cursor: (usize, usize),
+    pub origin: (usize, usize),
+}
+
+impl Viewport {
+    /// Move the cursor by (dx, dy), clamped to the map.
+    pub fn move_cursor(&mut self, dx: i32, dy: i32, map_size: (usize, usize)) {
+        let clamp = |v: usize, d: i32, n: usize| (v as i64 + d as i64).clamp(0, n.saturating_sub(1) as i64) as usize;
+        self.cursor = (clamp(self.cursor.0, dx, map_size.0), clamp(self.cursor.1, dy, map_size.1));
+    }
+
+    /// Map cell at screen position (x, y), if it shows one.
+    pub fn cell_at(&self, x: usize, y: usize, map_size: (usize, usize)) -> Option<(usize, usize)> {
+        let row = y.checked_sub(HEADER_ROWS)? + self.origin.1;
+        let col = x + self.origin.0;
+        (col < map_size.0 && row < map_size.1).then_some((col, row))
+    }
+}
+
+/// Compose the whole screen. Updates the viewport origin to keep the
+/// cursor visible.
+pub fn compose(state: &GameState, view: &mut Viewport, width: usize, height: usize, show_help: bool) -> Screen {
+    let mut screen = Screen::new(width, height);
+    let (cols, rows) = state.map_size;
+    let map_h = height.saturating_sub(HEADER_ROWS + FOOTER_ROWS);
+    view.origin = (
+        scroll_origin(view.origin.0, view.cursor.0, width, cols),
+        scroll_origin(view.origin.1, view.cursor.1, map_h, rows),
+    );
+
+    // Status line
+    let mut status = format!("Round {}", state.round);
+    if state.paused || state.pause_requested {
+        status.push_str("  PAUSED");
+    }
+    status.push_str(&format!("  cursor {},{}", view.cursor.0, view.cursor.1));
+    if !state.connected || state.stale {
+        status.push_str("  [reconnecting]");
+    }
+    screen.text(0, 0, &status, if state.stale { ERROR } else { ACCENT });
+    if show_help {
+        let help = "arrows/click: cursor  enter: click  F1: help  ctrl-c: quit";
+        screen.text(width.saturating_sub(help.chars().count()), 0, help, TEXT_DIM);
+    }
+
+    // Map
+    for y in 0..map_h.min(rows) {
+        for x in 0..width.min(cols) {
+            let (col, row) = (x + view.origin.0, y + view.origin.1);
+            let cell = state.cells.get(col).and_then(|c| c.get(row)).and_then(|c| c.as_ref());
+            let mut glyph = cell_glyph(&state.theme, cell);
+            if (col, row) == view.cursor {
+                glyph.bg = CURSOR_BG;
+            }
+            screen.put(x, HEADER_ROWS + y, glyph);
+        }
+    }
+
+    // Messages
+    let footer = HEADER_ROWS + map_h.min(rows);
+    screen.text(0, footer, &state.attention_message, TEXT);
+    let second = if state.turn_message.is_empty() { &state.hover_message } else { &state.turn_message };
+    screen.text(0, footer + 1, second, TEXT_DIM);
+    if let Some(err) = &state.protocol_error {
+        screen.text(0, footer + 2, err, ERROR);
+    } else if !state.error_message.is_empty() {
+        screen.text(0, footer + 2, &state.error_message, ERROR);
+    } else if !state.production_status.is_empty() {
+        screen.text(0, footer + 2, &state.production_status, TEXT_DIM);
+    }
+    screen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn state() -> GameState {
+        let frame = json!({
+            "type": "state", "map_size": [3, 2], "round": 7,
+            "cells": [
+                [{"t": "land", "cid": 1, "u": {"t": "army", "o": "player", "m": "awake"}}, {"t": "sea"}],
+                [{"t": "city", "cs": "player", "prod": {"item": "fighter", "remaining": 3}}, null],
+                [{"t": "sea", "u": {"t": "destroyer", "o": "computer", "m": "sentry"}}, {"t": "land", "wp": true}]
+            ],
+            "paused": false, "pause_requested": false, "waiting_for_input": true,
+            "attention_coords": [], "attention_message": "Army needs orders", "turn_message": "",
+            "error_message": "Cannot move there", "error_until": 0, "hover_message": "",
+            "selected_cell": null, "production_status": "", "destination": null,
+            "map_to_display": "player-map", "debug_message": "", "load_menu": null,
+            "tutorial": null, "tutorial_menu": null, "tips": null
+        });
+        let mut st = GameState::new();
+        st.apply_frame(&frame.to_string()).unwrap();
+        st.connected = true;
+        st
+    }
+
+    #[test]
+    fn uses_canvas_glyphs() {
+        let st = state();
+        let glyph = |c: usize, r: usize| cell_glyph(&st.theme, st.cells[c][r].as_ref()).ch;
+        assert_eq!(glyph(0, 0), 'A');
+        assert_eq!(glyph(1, 0), 'F');
+        assert_eq!(glyph(2, 0), 'd');
+        assert_eq!(glyph(2, 1), '*');
+        assert_eq!(glyph(1, 1), ' ');
+    }
+
+    #[test]
+    fn composes_status_map_and_messages() {
+        let mut view = Viewport::default();
+        let screen = compose(&state(), &mut view, 20, 6, false);
+        assert_eq!(
+            screen.to_text(),
+            "Round 7  cursor 0,0\nAFd\n  *\nArmy needs orders\n\nCannot move there\n"
+        );
+        assert_eq!(screen.row(1)[0].bg, CURSOR_BG);
+    }
+
+    #[test]
+    fn viewport_scrolls_to_cursor() {
+        assert_eq!(scroll_origin(0, 5, 10, 100), 0);
+        assert_eq!(scroll_origin(0, 12, 10, 100), 3);
+        assert_eq!(scroll_origin(20, 12, 10, 100), 12);
+        assert_eq!(scroll_origin(95, 99, 10, 100), 90);
+        assert_eq!(scroll_origin(7, 3, 10, 5), 0);
+    }
+
+    #[test]
+    fn cursor_is_clamped_and_screen_maps_back_to_cells() {
+        let mut view = Viewport::default();
+        view.move_cursor(-1, 5, (3, 2));
+        assert_eq!(view.cursor, (0, 1));
+        view.origin = (1, 0);
+        assert_eq!(view.cell_at(1, 1, (3, 2)), Some((2, 0)));
+        assert_eq!(view.cell_at(1, 0, (3, 2)), None);
+        assert_eq!(view.cell_at(5, 1, (3, 2)), None);
+    }
+}
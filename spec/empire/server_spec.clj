@@ -0,0 +1,87 @@
+(ns empire.server-spec
+  (:require [speclj.core :refer :all]
+            [empire.atoms :as atoms]
+            [empire.server :as server]
+            [empire.test-utils :refer [build-test-map reset-all-atoms!]]
+            [jsonista.core :as json]))
+
+(def changed-cells #'server/changed-cells)
+(def build-patch #'server/build-patch)
+(def broadcast-state! #'server/broadcast-state!)
+
+(describe "changed-cells"
+  (it "finds nothing in identical maps"
+    (let [cells [[{:t "sea"} {:t "land"}] [{:t "sea"} nil]]]
+      (should= [] (changed-cells cells cells))))
+
+  (it "returns exactly the changed cell as [col row cell]"
+    (let [prev [[{:t "sea"} {:t "land"}] [{:t "sea"} nil]]
+          cells (assoc-in prev [1 1] {:t "land" :cid 2})]
+      (should= [[1 1 {:t "land" :cid 2}]] (changed-cells prev cells)))))
+
+(describe "build-patch"
+  (it "returns nil for an unchanged state"
+    (let [state {:type "state" :seq 4 :round 3 :cells [[{:t "sea"}]]}]
+      (should-be-nil (build-patch state (assoc state :seq 5)))))
+
+  (it "holds only the changed cells and fields"
+    (let [prev {:type "state" :round 3 :paused false :cells [[{:t "sea"} {:t "sea"}]]}
+          state (-> prev
+                    (assoc :round 4)
+                    (assoc-in [:cells 0 1] {:t "land"}))]
+      (should= {:type "patch" :round 4 :cells [[0 1 {:t "land"}]]}
+               (build-patch prev state)))))
+
+(describe "broadcast-state!"
+  (around [it]
+    (reset-all-atoms!)
+    (reset! server/previous-state nil)
+    (reset! server/state-seq 0)
+    (reset! server/connected-clients #{:client})
+    (reset! server/client-features {:client #{"patches"}})
+    (reset! atoms/map-size [2 2])
+    (reset! atoms/player-map (build-test-map ["~#"
+                                                "~#"]))
+    (it)
+    (reset! server/connected-clients #{})
+    (reset! server/client-features {}))
+
+  (it "sends nothing when the state has not changed"
+    (let [sent (atom [])]
+      (with-redefs [server/send-raw! (fn [_ text] (swap! sent conj (json/read-value text)))]
+        (broadcast-state!)
+        (broadcast-state!)
+        (should= ["state"] (map #(get % "type") @sent)))))
+
+  (it "patches a single changed cell"
+    (let [sent (atom [])]
+      (with-redefs [server/send-raw! (fn [_ text] (swap! sent conj (json/read-value text)))]
+        (broadcast-state!)
+        (swap! atoms/player-map assoc-in [0 1] {:type :land})
+        (broadcast-state!)
+        (let [patch (last @sent)]
+          (should= "patch" (get patch "type"))
+          (should= [[0 1 {"t" "land"}]] (get patch "cells"))))))
+
+  (it "falls back to a full state when the map size changes"
+    (let [sent (atom [])]
+      (with-redefs [server/send-raw! (fn [_ text] (swap! sent conj (json/read-value text)))]
+        (broadcast-state!)
+        (reset! atoms/map-size [3 2])
+        (reset! atoms/player-map (build-test-map ["~##"
+                                                    "~##"]))
+        (broadcast-state!)
+        (should= ["state" "state"] (map #(get % "type") @sent))
+        (should= [3 2] (get (last @sent) "map_size")))))
+
+  (it "numbers each broadcast one higher than the last"
+    (let [sent (atom [])]
+      (with-redefs [server/send-raw! (fn [_ text] (swap! sent conj (json/read-value text)))]
+        (broadcast-state!)
+        (swap! atoms/player-map assoc-in [0 0] {:type :land})
+        (broadcast-state!)
+        (broadcast-state!)
+        (reset! atoms/round-number 2)
+        (broadcast-state!)
+        (should= [1 2 3] (map #(get % "seq") @sent))
+        (should= 3 @server/state-seq)))))